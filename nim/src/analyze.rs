@@ -0,0 +1,379 @@
+/*
+Exhaustive analysis of a starting position, for designing puzzles.
+
+Every position reachable from the start is solved once by walking the whole game
+tree, positions being remembered by their heap sizes. This does not rely on any
+formula, so it also checks the solver of `core` on every position it reaches.
+*/
+
+use crate::cli;
+use crate::core::{Convention, Heaps, Rules};
+use crate::messages::{Glyph, Message};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Write};
+
+/// Positions solved at most, the tree being kept in memory
+pub const MAX_POSITIONS: usize = 1_000_000;
+
+/// Outcome of a position when both players play perfectly
+#[derive(PartialEq, Debug, Clone)]
+pub struct Solution {
+    /// Whether the player to move wins
+    pub winning: bool,
+    /// Moves until the end of the game, the winner hurrying and the loser holding on
+    pub length: u32,
+    /// Moves `(heap index, sticks to take)` leaving a P-position to the other player
+    pub winning_moves: Vec<(usize, u64)>,
+}
+
+/// A reachable position, solved once all the positions it leads to are
+struct Node {
+    heaps: Heaps,
+    expanded: bool,
+    solution: Option<Solution>,
+}
+
+/// Solutions of every position reachable from a start
+pub struct Analysis {
+    start: Heaps,
+    rules: Rules,
+    index: HashMap<Vec<u64>, usize>,
+    nodes: Vec<Node>,
+}
+
+impl Analysis {
+    /// Solve every position reachable from `start`, or tell why it is too big
+    pub fn new(start: &Heaps, rules: Rules) -> Result<Analysis, String> {
+        // Every position has each heap between empty and its starting size
+        let bound = start.sizes().iter().try_fold(1usize, |product, &size| {
+            usize::try_from(size)
+                .ok()
+                .and_then(|size| size.checked_add(1))
+                .and_then(|choices| product.checked_mul(choices))
+                .filter(|&product| product <= MAX_POSITIONS)
+        });
+        if bound.is_none() {
+            return Err(Message::TooManyPositions(MAX_POSITIONS).to_string());
+        }
+        let mut analysis = Analysis {
+            start: start.clone(),
+            rules,
+            index: HashMap::new(),
+            nodes: Vec::new(),
+        };
+        let root = analysis.insert(start.clone());
+        // Depth first without recursion, as a single heap can be very deep
+        let mut stack = vec![root];
+        while let Some(&node) = stack.last() {
+            if analysis.nodes[node].solution.is_some() {
+                stack.pop();
+            } else if analysis.nodes[node].expanded {
+                let solution = analysis.solve(node);
+                analysis.nodes[node].solution = Some(solution);
+                stack.pop();
+            } else {
+                analysis.nodes[node].expanded = true;
+                for (_, next) in analysis.children(node) {
+                    let child = match analysis.index.get(&next.sizes()) {
+                        Some(&child) => child,
+                        None => analysis.insert(next),
+                    };
+                    if analysis.nodes[child].solution.is_none() {
+                        stack.push(child);
+                    }
+                }
+            }
+        }
+        Ok(analysis)
+    }
+
+    fn insert(&mut self, heaps: Heaps) -> usize {
+        let node = self.nodes.len();
+        self.index.insert(heaps.sizes(), node);
+        self.nodes.push(Node {
+            heaps,
+            expanded: false,
+            solution: None,
+        });
+        node
+    }
+
+    /// Positions reached by each legal move
+    fn children(&self, node: usize) -> Vec<((usize, u64), Heaps)> {
+        let heaps = &self.nodes[node].heaps;
+        heaps
+            .legal_moves()
+            .into_iter()
+            .filter_map(|(heap, take)| {
+                let mut next = heaps.clone();
+                next.remove_sticks(heap, Ok(take)).ok()?;
+                Some(((heap, take), next))
+            })
+            .collect()
+    }
+
+    /// Solve a position whose children are all solved
+    fn solve(&self, node: usize) -> Solution {
+        let children: Vec<((usize, u64), &Solution)> = self
+            .children(node)
+            .into_iter()
+            .filter_map(|(mv, next)| Some((mv, self.solution(&next)?)))
+            .collect();
+        if children.is_empty() {
+            // The previous player took the last stick
+            return Solution {
+                winning: self.rules.convention == Convention::Misere,
+                length: 0,
+                winning_moves: Vec::new(),
+            };
+        }
+        let winning: Vec<&((usize, u64), &Solution)> = children
+            .iter()
+            .filter(|(_, solution)| !solution.winning)
+            .collect();
+        match winning.iter().map(|(_, solution)| solution.length).min() {
+            Some(shortest) => Solution {
+                winning: true,
+                length: shortest + 1,
+                winning_moves: winning.iter().map(|&&(mv, _)| mv).collect(),
+            },
+            None => Solution {
+                winning: false,
+                length: children
+                    .iter()
+                    .map(|(_, solution)| solution.length + 1)
+                    .max()
+                    .unwrap_or(0),
+                winning_moves: Vec::new(),
+            },
+        }
+    }
+
+    /// Solution of a position reachable from the start
+    pub fn solution(&self, heaps: &Heaps) -> Option<&Solution> {
+        let &node = self.index.get(&heaps.sizes())?;
+        self.nodes[node].solution.as_ref()
+    }
+
+    /// Every reachable position with its solution
+    pub fn solutions(&self) -> impl Iterator<Item = (&Heaps, &Solution)> {
+        self.nodes
+            .iter()
+            .filter_map(|node| Some((&node.heaps, node.solution.as_ref()?)))
+    }
+
+    pub fn positions(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Number of positions lost by the player to move
+    pub fn p_positions(&self) -> usize {
+        self.nodes
+            .iter()
+            .filter(|node| node.solution.as_ref().is_some_and(|s| !s.winning))
+            .count()
+    }
+
+    /// Positions where the solver of `core` disagrees with the game tree
+    pub fn disagreements(&self) -> Vec<&Heaps> {
+        self.nodes
+            .iter()
+            .filter(|node| {
+                node.solution
+                    .as_ref()
+                    .is_some_and(|s| s.winning != node.heaps.is_winning(&self.rules))
+            })
+            .map(|node| &node.heaps)
+            .collect()
+    }
+
+    /// A move the way the table lists it
+    fn describe_move(&self, (heap, take): (usize, u64)) -> String {
+        if self.start.len() == 1 {
+            take.to_string()
+        } else if Some(heap) == self.start.both_heaps() {
+            format!("both:{}", take)
+        } else {
+            format!("{}:{}", heap + 1, take)
+        }
+    }
+}
+
+pub fn describe_sizes(heaps: &Heaps) -> String {
+    let sizes: Vec<String> = heaps.sizes().iter().map(|size| size.to_string()).collect();
+    sizes.join(" ")
+}
+
+impl fmt::Display for Analysis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reachable = Message::PositionsReachable {
+            positions: self.positions(),
+            losing: self.p_positions(),
+        };
+        writeln!(f, "  | {}", reachable)?;
+        match self.solution(&self.start) {
+            Some(solution) if solution.winning => {
+                writeln!(f, "  | {}", Message::FirstPlayerWins(solution.length))?
+            }
+            Some(solution) => writeln!(f, "  | {}", Message::FirstPlayerLoses(solution.length))?,
+            None => {}
+        }
+        match self.disagreements()[..] {
+            [] => writeln!(f, "  | {}", Message::SolverAgrees)?,
+            ref wrong => {
+                let wrong: Vec<String> = wrong.iter().map(|heaps| describe_sizes(heaps)).collect();
+                writeln!(f, "  | {}", Message::SolverDisagrees(&wrong.join(", ")))?
+            }
+        }
+
+        let mut rows: Vec<&Node> = self.nodes.iter().collect();
+        rows.sort_by_key(|node| std::cmp::Reverse(node.heaps.sizes()));
+        let [position, result, length] = [
+            Message::ColumnPosition,
+            Message::ColumnResult,
+            Message::ColumnLength,
+        ]
+        .map(|header| header.to_string());
+        let moves = if self.start.len() == 1 {
+            Message::ColumnWinningMoves
+        } else {
+            Message::ColumnWinningHeapMoves
+        };
+        let width = rows
+            .iter()
+            .map(|node| describe_sizes(&node.heaps).len())
+            .max()
+            .unwrap_or(0)
+            .max(position.chars().count());
+        // Translated headers may be longer than the results and lengths under them
+        let result_width = result.chars().count().max(6);
+        let length_width = length.chars().count().max(6);
+        writeln!(f, "  |")?;
+        writeln!(
+            f,
+            "  | {:<width$}  {:<result_width$}  {:>length_width$}  {}",
+            position, result, length, moves
+        )?;
+        for node in rows {
+            let Some(solution) = &node.solution else {
+                continue;
+            };
+            let winning_moves: Vec<String> = solution
+                .winning_moves
+                .iter()
+                .map(|&mv| self.describe_move(mv))
+                .collect();
+            writeln!(
+                f,
+                "  | {:<width$}  {:<result_width$}  {:>length_width$}  {}",
+                describe_sizes(&node.heaps),
+                if solution.winning { "N" } else { "P" },
+                solution.length,
+                winning_moves.join(", ")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+pub fn show_analysis<W: Write>(config: &cli::Config, output: &mut W) -> io::Result<()> {
+    let invalid = |e: String| io::Error::new(io::ErrorKind::InvalidInput, e);
+    let sizes = config
+        .sticks
+        .as_deref()
+        .ok_or_else(|| invalid(Message::PositionNeeded.to_string()))?;
+    let heaps = config.heaps(sizes).map_err(invalid)?;
+    let rules = Rules::new(config.convention.unwrap_or(Convention::Normal));
+    let analysis = Analysis::new(&heaps, rules).map_err(invalid)?;
+    let analysis_of = Message::AnalysisOf {
+        position: &describe_sizes(&heaps),
+        convention: &rules.convention,
+    };
+    writeln!(
+        output,
+        "{}  | {}\n{}",
+        Glyph::Title,
+        analysis_of,
+        "-".repeat(80)
+    )?;
+    write!(output, "{}", analysis)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::StickNumber;
+
+    const NORMAL: Rules = Rules {
+        convention: Convention::Normal,
+    };
+    const MISERE: Rules = Rules {
+        convention: Convention::Misere,
+    };
+
+    #[test]
+    fn test_single_heap() {
+        let start = Heaps::single(StickNumber::new(10, 1, 3));
+        let analysis = Analysis::new(&start, NORMAL).unwrap();
+        assert_eq!((analysis.positions(), analysis.p_positions()), (11, 3));
+        assert_eq!(
+            analysis.solution(&start),
+            Some(&Solution {
+                winning: true,
+                length: 5,
+                winning_moves: vec![(0, 2)],
+            })
+        );
+        let mut eight = start.clone();
+        eight.remove_sticks(0, Ok(2)).unwrap();
+        assert_eq!(
+            analysis.solution(&eight).map(|s| (s.winning, s.length)),
+            Some((false, 4))
+        );
+        assert!(analysis.disagreements().is_empty());
+
+        let analysis = Analysis::new(&start, MISERE).unwrap();
+        assert_eq!(
+            analysis.solution(&start).unwrap().winning_moves,
+            vec![(0, 1)]
+        );
+        assert!(analysis.disagreements().is_empty());
+    }
+
+    #[test]
+    fn test_agrees_with_solver() {
+        for rules in [NORMAL, MISERE] {
+            for start in [
+                Heaps::new(&[3, 4, 5]),
+                Heaps::new(&[1, 1, 2, 6]),
+                Heaps::wythoff(7, 9),
+                Heaps::single(StickNumber::with_moves(30, &[2, 5, 6])),
+            ] {
+                let analysis = Analysis::new(&start, rules).unwrap();
+                assert!(analysis.disagreements().is_empty(), "{:?}", start);
+            }
+        }
+        // Every position of the heaps counts once
+        let analysis = Analysis::new(&Heaps::new(&[3, 4, 5]), NORMAL).unwrap();
+        assert_eq!(analysis.positions(), 4 * 5 * 6);
+    }
+
+    #[test]
+    fn test_report() {
+        let start = Heaps::wythoff(1, 2);
+        let text = Analysis::new(&start, NORMAL).unwrap().to_string();
+        assert!(text.starts_with("  | 6 positions reachable, 2 P-positions"));
+        assert!(text.contains("  | The first player loses against perfect play"));
+        assert!(text.contains("  | 1 1       N            1  both:1\n"));
+        assert!(text.contains("  | 0 2       N            1  2:2\n"));
+        assert!(text.contains("  | 0 0       P            0  \n"));
+
+        let start = Heaps::single(StickNumber::new(u64::MAX, 1, 3));
+        assert!(Analysis::new(&start, NORMAL)
+            .err()
+            .is_some_and(|e| e.starts_with("more than")));
+        let start = Heaps::new(&[1000, u64::MAX, 1000]);
+        assert!(Analysis::new(&start, NORMAL).is_err());
+    }
+}
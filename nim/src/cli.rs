@@ -0,0 +1,1006 @@
+use crate::clock::{Penalty, TimeControl};
+use crate::core::{Convention, Heaps, StickNumber};
+use crate::grundy::Position;
+use crate::learn;
+use crate::messages::{self, Language, Locale, Message};
+use crate::net;
+use crate::profile;
+use crate::server;
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Who takes the first turn
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum First {
+    Player(usize),
+    Random,
+}
+
+/// Whether the game is played locally or over the network
+#[derive(PartialEq, Debug, Clone)]
+pub enum Mode {
+    Local,
+    Host {
+        port: u16,
+    },
+    Join {
+        address: String,
+    },
+    /// Computer strategies playing each other, all registered ones if none are named
+    Tournament {
+        strategies: Option<Vec<String>>,
+        rounds: u32,
+        seed: u64,
+    },
+    /// Leaderboard of the recorded games, with the last `recent` ones
+    Stats {
+        recent: usize,
+    },
+    /// Solve every position reachable from the one given by `--sticks`
+    Analyze,
+    /// Learn a strategy for the position given by `--sticks` by self-play, into
+    /// `table` or the default table file
+    Train {
+        episodes: u32,
+        seed: u64,
+        table: Option<PathBuf>,
+    },
+    /// Step through the game of a transcript once checked
+    Replay {
+        path: PathBuf,
+    },
+    /// Answer the HTTP JSON API of the engine, the heaps, rules and players given
+    /// being the defaults of new games
+    Serve {
+        address: IpAddr,
+        port: u16,
+    },
+}
+
+/// Game setup given on the command line, `None` values being asked interactively
+#[derive(PartialEq, Debug)]
+pub struct Config {
+    pub sticks: Option<Vec<u64>>,
+    pub min_remove: u64,
+    pub max_remove: u64,
+    /// Allowed removals of a subtraction game, replacing the limits
+    pub moves: Option<Vec<u64>>,
+    pub players: Option<Vec<String>>,
+    pub convention: Option<Convention>,
+    pub first: Option<First>,
+    pub resume: Option<PathBuf>,
+    /// Team of each player, counted from 0
+    pub teams: Option<Vec<usize>>,
+    pub shuffle: bool,
+    pub tui: bool,
+    /// Comment on each human move once played
+    pub coach: bool,
+    /// Play Wythoff's game on two heaps instead of Nim
+    pub wythoff: bool,
+    /// Impartial games played side by side instead of the heaps
+    pub games: Option<Vec<Position>>,
+    /// Data file of the player profiles, the default one if `None`
+    pub profiles: Option<PathBuf>,
+    /// Time limits of the human players, none if `None`
+    pub time_control: Option<TimeControl>,
+    /// File the transcript of the game is written to once over
+    pub transcript: Option<PathBuf>,
+    pub mode: Mode,
+}
+
+impl Config {
+    /// Heaps of the given sizes following the configured move rules
+    pub fn heaps(&self, sizes: &[u64]) -> Result<Heaps, String> {
+        match (sizes, &self.moves) {
+            ([total], Some(moves)) => Ok(Heaps::single(StickNumber::with_moves(*total, moves))),
+            ([total], None) => Ok(Heaps::single(StickNumber::new(
+                *total,
+                self.min_remove,
+                self.max_remove,
+            ))),
+            (_, Some(_)) => Err(Message::MovesSingleHeap.to_string()),
+            (&[first, second], None) if self.wythoff => Ok(Heaps::wythoff(first, second)),
+            (_, None) if self.wythoff => Err(Message::WythoffTwoHeaps.to_string()),
+            (_, None) => Ok(Heaps::new(sizes)),
+        }
+    }
+}
+
+fn parse_first(value: &str) -> Result<First, String> {
+    if value.trim().eq_ignore_ascii_case("random") {
+        return Ok(First::Random);
+    }
+    match value.trim().parse::<usize>() {
+        Ok(index) if index >= 1 => Ok(First::Player(index - 1)),
+        _ => Err(String::from(
+            "expected a player number starting at 1 or random",
+        )),
+    }
+}
+
+/// Teams need a player each and at least two of them to play against
+pub fn check_teams(teams: &[usize], number_players: Option<usize>) -> Result<(), String> {
+    if let Some(number_players) = number_players {
+        if teams.len() != number_players {
+            return Err(Message::TeamPerPlayer(number_players).to_string());
+        }
+    }
+    if teams.iter().all(|&team| team == teams[0]) {
+        return Err(Message::TwoTeams.to_string());
+    }
+    Ok(())
+}
+
+pub fn command() -> Command {
+    Command::new("nim")
+        .author("Ammar Mian")
+        .about("Play the game of Nim in the terminal")
+        .subcommand(
+            Command::new("host")
+                .about("Host a game and wait for another player to join")
+                .arg(
+                    Arg::new("port")
+                        .long("port")
+                        .value_name("PORT")
+                        .value_parser(value_parser!(u16))
+                        .help("Port to listen on, 7878 by default"),
+                ),
+        )
+        .subcommand(
+            Command::new("join")
+                .about("Join a game hosted by another player")
+                .arg(
+                    Arg::new("address")
+                        .value_name("ADDRESS")
+                        .required(true)
+                        .help("Address of the host, as HOST:PORT"),
+                ),
+        )
+        .subcommand(
+            Command::new("tournament")
+                .about("Play computer strategies against each other and rank them")
+                .arg(
+                    Arg::new("strategies")
+                        .long("strategies")
+                        .value_name("STRATEGIES")
+                        .value_delimiter(',')
+                        .help("Comma separated strategies taking part, all registered ones by default"),
+                )
+                .arg(
+                    Arg::new("rounds")
+                        .long("rounds")
+                        .value_name("ROUNDS")
+                        .value_parser(value_parser!(u32).range(1..))
+                        .default_value("100")
+                        .help("Number of games played by each pair of strategies"),
+                )
+                .arg(
+                    Arg::new("seed")
+                        .long("seed")
+                        .value_name("SEED")
+                        .value_parser(value_parser!(u64))
+                        .default_value("0")
+                        .help("Seed of the random choices, the same seed giving the same results"),
+                ),
+        )
+        .subcommand(
+            Command::new("stats")
+                .about("Show the leaderboard of the recorded games")
+                .arg(
+                    Arg::new("recent")
+                        .long("recent")
+                        .value_name("GAMES")
+                        .value_parser(value_parser!(usize))
+                        .help("Number of last games listed, 5 by default"),
+                ),
+        )
+        .subcommand(Command::new("analyze").about(
+            "Solve every position reachable from --sticks and list the winning moves",
+        ))
+        .subcommand(
+            Command::new("train")
+                .about("Learn a strategy for --sticks by self-play and save it for learned players")
+                .arg(
+                    Arg::new("episodes")
+                        .long("episodes")
+                        .value_name("GAMES")
+                        .value_parser(value_parser!(u32).range(1..))
+                        .help("Number of games played against itself, 20000 by default"),
+                )
+                .arg(
+                    Arg::new("seed")
+                        .long("seed")
+                        .value_name("SEED")
+                        .value_parser(value_parser!(u64))
+                        .default_value("0")
+                        .help("Seed of the random choices, the same seed learning the same table"),
+                )
+                .arg(
+                    Arg::new("table")
+                        .long("table")
+                        .value_name("FILE")
+                        .value_parser(value_parser!(PathBuf))
+                        .help("File the learned table is written to, ~/.nim_table.json by default"),
+                ),
+        )
+        .subcommand(
+            Command::new("serve")
+                .about("Serve games over an HTTP JSON API")
+                .arg(
+                    Arg::new("address")
+                        .long("address")
+                        .value_name("ADDRESS")
+                        .value_parser(value_parser!(IpAddr))
+                        .help("Address to listen on, 127.0.0.1 by default, 0.0.0.0 for every interface"),
+                )
+                .arg(
+                    Arg::new("port")
+                        .long("port")
+                        .value_name("PORT")
+                        .value_parser(value_parser!(u16))
+                        .help("Port to listen on, 8080 by default"),
+                ),
+        )
+        .subcommand(
+            Command::new("replay")
+                .about("Check a game transcript and step through its moves")
+                .arg(
+                    Arg::new("file")
+                        .value_name("FILE")
+                        .value_parser(value_parser!(PathBuf))
+                        .required(true)
+                        .help("Transcript written with --transcript"),
+                ),
+        )
+        .arg(
+            Arg::new("sticks")
+                .global(true)
+                .long("sticks")
+                .value_name("STICKS")
+                .value_delimiter(',')
+                .value_parser(value_parser!(u64))
+                .help("Total number of sticks, or comma separated heap sizes for a multi-heap game"),
+        )
+        .arg(
+            Arg::new("min")
+                .global(true)
+                .long("min")
+                .value_name("MIN")
+                .value_parser(value_parser!(u64).range(1..))
+                .default_value("1")
+                .help("Minimum number of sticks removed per turn in a single heap game"),
+        )
+        .arg(
+            Arg::new("max")
+                .global(true)
+                .long("max")
+                .value_name("MAX")
+                .value_parser(value_parser!(u64).range(1..))
+                .default_value("3")
+                .help("Maximum number of sticks removed per turn in a single heap game"),
+        )
+        .arg(
+            Arg::new("moves")
+                .long("moves")
+                .global(true)
+                .value_name("MOVES")
+                .value_delimiter(',')
+                .value_parser(value_parser!(u64).range(1..))
+                .conflicts_with_all(["min", "max"])
+                .help("Comma separated amounts allowed per turn in a single heap game, e.g. 1,3,4"),
+        )
+        .arg(
+            Arg::new("wythoff")
+                .long("wythoff")
+                .global(true)
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["moves", "min", "max", "games"])
+                .help("Play Wythoff's game: take from one of two heaps, or the same amount from both"),
+        )
+        .arg(
+            Arg::new("players")
+                .global(true)
+                .long("players")
+                .value_name("PLAYERS")
+                .value_delimiter(',')
+                .help("Comma separated players in turn order, each a human name or a computer level (easy, medium, hard, mcts or mcts:ITERATIONS, learned or learned:TABLE)"),
+        )
+        .arg(
+            Arg::new("teams")
+                .long("teams")
+                .global(true)
+                .value_name("TEAMS")
+                .value_delimiter(',')
+                .value_parser(value_parser!(u64).range(1..))
+                .help("Comma separated team number of each player, e.g. 1,2,1,2"),
+        )
+        .arg(
+            Arg::new("shuffle")
+                .long("shuffle")
+                .global(true)
+                .action(ArgAction::SetTrue)
+                .help("Seat the players in a random turn order"),
+        )
+        .arg(
+            Arg::new("variant")
+                .global(true)
+                .long("variant")
+                .value_name("VARIANT")
+                .value_parser(|value: &str| value.parse::<Convention>())
+                .help("Play convention (normal: last stick wins, misere: last stick loses)"),
+        )
+        .arg(
+            Arg::new("first")
+                .global(true)
+                .long("first")
+                .value_name("FIRST")
+                .value_parser(parse_first)
+                .help("Number of the player moving first, or random"),
+        )
+        .arg(
+            Arg::new("resume")
+                .long("resume")
+                .value_name("FILE")
+                .value_parser(value_parser!(PathBuf))
+                .help("Resume a game saved with the save command"),
+        )
+        .arg(
+            Arg::new("games")
+                .long("games")
+                .global(true)
+                .value_name("GAMES")
+                .value_delimiter(',')
+                .value_parser(|value: &str| value.parse::<Position>())
+                .conflicts_with_all(["sticks", "moves", "min", "max", "variant", "teams"])
+                .help("Comma separated impartial games played as one sum, among kayles:N, grundy:N and subtraction:N:A/B/..."),
+        )
+        .arg(
+            Arg::new("profiles")
+                .long("profiles")
+                .global(true)
+                .value_name("FILE")
+                .value_parser(value_parser!(PathBuf))
+                .help("Data file recording the results of each player, ~/.nim_profiles.json by default"),
+        )
+        .arg(
+            Arg::new("coach")
+                .long("coach")
+                .global(true)
+                .action(ArgAction::SetTrue)
+                .help("Comment on the quality of each human move, type hint at the prompt for the best one"),
+        )
+        .arg(
+            Arg::new("transcript")
+                .long("transcript")
+                .global(true)
+                .value_name("FILE")
+                .value_parser(value_parser!(PathBuf))
+                .help("Write the transcript of the game to FILE once it is over"),
+        )
+        .arg(
+            Arg::new("lang")
+                .long("lang")
+                .global(true)
+                .value_name("LANG")
+                .value_parser(|value: &str| value.parse::<Language>())
+                .help("Language of the messages (en or fr), from NIM_LANG or the locale by default"),
+        )
+        .arg(
+            Arg::new("ascii")
+                .long("ascii")
+                .global(true)
+                .action(ArgAction::SetTrue)
+                .help("Plain ASCII output for terminals without Nerd Font or emoji glyphs, also set by NIM_ASCII"),
+        )
+        .arg(
+            Arg::new("move-time")
+                .long("move-time")
+                .value_name("SECONDS")
+                .value_parser(value_parser!(u64).range(1..))
+                .help("Longest a human player may take over a single move"),
+        )
+        .arg(
+            Arg::new("time-bank")
+                .long("time-bank")
+                .value_name("SECONDS")
+                .value_parser(value_parser!(u64).range(1..))
+                .help("Time each human player has for all their moves, like a chess clock"),
+        )
+        .arg(
+            Arg::new("timeout-penalty")
+                .long("timeout-penalty")
+                .value_name("PENALTY")
+                .value_parser(|value: &str| value.parse::<Penalty>())
+                .help("What happens to a player running out of time (forfeit: the game is lost, random: a random move is played), forfeit by default"),
+        )
+        .arg(
+            Arg::new("tui")
+                .long("tui")
+                .action(ArgAction::SetTrue)
+                .help("Play full screen, choosing moves with the arrow keys"),
+        )
+}
+
+pub fn config_from_matches(matches: &ArgMatches) -> Result<Config, String> {
+    let min_remove = *matches.get_one::<u64>("min").unwrap_or(&1);
+    let max_remove = *matches.get_one::<u64>("max").unwrap_or(&3);
+    if min_remove > max_remove {
+        return Err(Message::MinAboveMax {
+            min: min_remove,
+            max: max_remove,
+        }
+        .to_string());
+    }
+    let sticks: Option<Vec<u64>> = matches
+        .get_many::<u64>("sticks")
+        .map(|values| values.copied().collect());
+    if let Some(sizes) = &sticks {
+        if sizes.iter().all(|&size| size == 0) {
+            return Err(Message::SticksWithoutStick.to_string());
+        }
+    }
+    let wythoff = matches.get_flag("wythoff");
+    if let (true, Some(sizes)) = (wythoff, &sticks) {
+        if sizes.len() != 2 {
+            return Err(Message::WythoffSticks.to_string());
+        }
+    }
+    let moves: Option<Vec<u64>> = matches
+        .get_many::<u64>("moves")
+        .map(|values| values.copied().collect());
+    if let (Some(_), Some(sizes)) = (&moves, &sticks) {
+        if sizes.len() > 1 {
+            return Err(Message::MovesSingleHeap.to_string());
+        }
+    }
+    let players: Option<Vec<String>> = matches
+        .get_many::<String>("players")
+        .map(|values| values.cloned().collect());
+    let first = matches.get_one::<First>("first").copied();
+    if let Some(players) = &players {
+        if players.len() < 2 {
+            return Err(Message::TooFewNamedPlayers.to_string());
+        }
+    }
+    let teams: Option<Vec<usize>> = matches
+        .get_many::<u64>("teams")
+        .map(|values| values.map(|&team| team as usize - 1).collect());
+    if let Some(teams) = &teams {
+        check_teams(teams, players.as_ref().map(|players| players.len()))?;
+    }
+    if let (Some(First::Player(index)), Some(players)) = (first, &players) {
+        if index >= players.len() {
+            return Err(Message::FirstOutOfRange(players.len()).to_string());
+        }
+    }
+    let mode = match matches.subcommand() {
+        Some(("host", host)) => Mode::Host {
+            port: *host.get_one::<u16>("port").unwrap_or(&net::DEFAULT_PORT),
+        },
+        Some(("join", join)) => Mode::Join {
+            address: join
+                .get_one::<String>("address")
+                .cloned()
+                .unwrap_or_default(),
+        },
+        Some(("tournament", tournament)) => Mode::Tournament {
+            strategies: tournament
+                .get_many::<String>("strategies")
+                .map(|values| values.cloned().collect()),
+            rounds: *tournament.get_one::<u32>("rounds").unwrap_or(&100),
+            seed: *tournament.get_one::<u64>("seed").unwrap_or(&0),
+        },
+        Some(("stats", stats)) => Mode::Stats {
+            recent: *stats
+                .get_one::<usize>("recent")
+                .unwrap_or(&profile::DEFAULT_RECENT),
+        },
+        Some(("analyze", _)) => Mode::Analyze,
+        Some(("train", train)) => Mode::Train {
+            episodes: *train
+                .get_one::<u32>("episodes")
+                .unwrap_or(&learn::DEFAULT_EPISODES),
+            seed: *train.get_one::<u64>("seed").unwrap_or(&0),
+            table: train.get_one::<PathBuf>("table").cloned(),
+        },
+        Some(("replay", replay)) => Mode::Replay {
+            path: replay
+                .get_one::<PathBuf>("file")
+                .cloned()
+                .unwrap_or_default(),
+        },
+        Some(("serve", serve)) => Mode::Serve {
+            address: *serve
+                .get_one::<IpAddr>("address")
+                .unwrap_or(&server::DEFAULT_ADDRESS),
+            port: *serve
+                .get_one::<u16>("port")
+                .unwrap_or(&server::DEFAULT_PORT),
+        },
+        _ => Mode::Local,
+    };
+    if let Mode::Tournament { strategies, .. } = &mode {
+        if matches!(strategies, Some(strategies) if strategies.len() < 2) {
+            return Err(Message::TooFewStrategies.to_string());
+        }
+        if ["players", "teams", "first", "resume"]
+            .iter()
+            .any(|id| matches.contains_id(id))
+            || matches.get_flag("shuffle")
+            || matches.get_flag("tui")
+        {
+            return Err(Message::TournamentSetup.to_string());
+        }
+    }
+    if let Mode::Analyze | Mode::Train { .. } = &mode {
+        let command = if mode == Mode::Analyze {
+            "analyze"
+        } else {
+            "train"
+        };
+        if !matches.contains_id("sticks") {
+            return Err(Message::NeedsSticks(command).to_string());
+        }
+        if ["players", "teams", "first", "resume"]
+            .iter()
+            .any(|id| matches.contains_id(id))
+            || matches.get_flag("shuffle")
+            || matches.get_flag("tui")
+        {
+            return Err(Message::OnlyHeapsAndRules(command).to_string());
+        }
+    }
+    // The hosting player always sits first, the joining one second
+    if let (Mode::Host { .. }, true) = (&mode, matches.get_flag("shuffle")) {
+        return Err(Message::NoShuffleHosted.to_string());
+    }
+    // Hosted games are new games between the host and the joining player
+    if let Mode::Host { .. } = &mode {
+        if matches.contains_id("players") || matches.contains_id("resume") {
+            return Err(Message::HostedNewGames.to_string());
+        }
+    }
+    if let Mode::Serve { .. } = &mode {
+        if ["teams", "first", "resume"]
+            .iter()
+            .any(|id| matches.contains_id(id))
+            || matches.get_flag("shuffle")
+            || matches.get_flag("tui")
+            || matches.get_flag("coach")
+        {
+            return Err(Message::ServeSetup.to_string());
+        }
+    }
+    let games: Option<Vec<Position>> = matches
+        .get_many::<Position>("games")
+        .map(|values| values.cloned().collect());
+    if games.is_some()
+        && (mode != Mode::Local || matches.contains_id("resume") || matches.get_flag("tui"))
+    {
+        return Err(Message::GamesLocalOnly.to_string());
+    }
+    let seconds = |id: &str| {
+        matches
+            .get_one::<u64>(id)
+            .map(|&value| Duration::from_secs(value))
+    };
+    let time_control = match (seconds("move-time"), seconds("time-bank")) {
+        (None, None) if matches.contains_id("timeout-penalty") => {
+            return Err(Message::PenaltyNeedsClock.to_string())
+        }
+        (None, None) => None,
+        (per_move, bank) => Some(TimeControl {
+            per_move,
+            bank,
+            penalty: matches
+                .get_one::<Penalty>("timeout-penalty")
+                .copied()
+                .unwrap_or_default(),
+        }),
+    };
+    if time_control.is_some() && (mode != Mode::Local || games.is_some() || matches.get_flag("tui"))
+    {
+        return Err(Message::ClockLocalOnly.to_string());
+    }
+    let transcript = matches.get_one::<PathBuf>("transcript").cloned();
+    if transcript.is_some() && (!matches!(mode, Mode::Local | Mode::Host { .. }) || games.is_some())
+    {
+        return Err(Message::TranscriptLocalOnly.to_string());
+    }
+    Ok(Config {
+        sticks,
+        min_remove,
+        max_remove,
+        moves,
+        players,
+        convention: matches.get_one::<Convention>("variant").copied(),
+        first,
+        resume: matches.get_one::<PathBuf>("resume").cloned(),
+        teams,
+        shuffle: matches.get_flag("shuffle"),
+        tui: matches.get_flag("tui"),
+        coach: matches.get_flag("coach"),
+        wythoff,
+        games,
+        profiles: matches.get_one::<PathBuf>("profiles").cloned(),
+        time_control,
+        transcript,
+        mode,
+    })
+}
+
+/// Language and glyphs asked for, the environment choosing what is not given
+fn locale(matches: &ArgMatches) -> Locale {
+    Locale {
+        language: matches
+            .get_one::<Language>("lang")
+            .copied()
+            .unwrap_or_else(Language::from_env),
+        ascii: matches.get_flag("ascii") || messages::ascii_from_env(),
+    }
+}
+
+pub fn parse() -> Config {
+    let mut command = command();
+    let matches = command.get_matches_mut();
+    // Set before the setup is checked, so that its errors are in that language
+    messages::set_locale(locale(&matches));
+    match config_from_matches(&matches) {
+        Ok(config) => config,
+        Err(e) => command
+            .error(clap::error::ErrorKind::ArgumentConflict, e)
+            .exit(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(args: &[&str]) -> Result<Config, String> {
+        let matches = command()
+            .try_get_matches_from(args)
+            .map_err(|e| e.to_string())?;
+        config_from_matches(&matches)
+    }
+
+    #[test]
+    fn test_defaults_leave_values_to_prompts() {
+        let config = config(&["nim"]).unwrap();
+        assert_eq!(config.sticks, None);
+        assert_eq!(config.players, None);
+        assert_eq!(config.convention, None);
+        assert_eq!(config.first, None);
+        assert_eq!((config.min_remove, config.max_remove), (1, 3));
+        assert_eq!(config.moves, None);
+        assert_eq!(config.mode, Mode::Local);
+    }
+
+    #[test]
+    fn test_full_setup() {
+        let config = config(&[
+            "nim",
+            "--sticks",
+            "3,4,5",
+            "--min",
+            "2",
+            "--max",
+            "4",
+            "--players",
+            "alice,hard",
+            "--variant",
+            "misere",
+            "--first",
+            "2",
+        ])
+        .unwrap();
+        assert_eq!(config.sticks, Some(vec![3, 4, 5]));
+        assert_eq!((config.min_remove, config.max_remove), (2, 4));
+        assert_eq!(
+            config.players,
+            Some(vec![String::from("alice"), String::from("hard")])
+        );
+        assert_eq!(config.convention, Some(Convention::Misere));
+        assert_eq!(config.first, Some(First::Player(1)));
+    }
+
+    #[test]
+    fn test_subtraction_set() {
+        let setup = config(&["nim", "--sticks", "20", "--moves", "1,3,4"]).unwrap();
+        assert_eq!(setup.moves, Some(vec![1, 3, 4]));
+    }
+
+    #[test]
+    fn test_sum_of_games() {
+        let setup = config(&["nim", "--games", "kayles:7,grundy:9,subtraction:10:1/3/4"]).unwrap();
+        assert_eq!(
+            setup.games,
+            Some(vec![
+                Position::Kayles(7),
+                Position::Grundy(9),
+                Position::Subtraction {
+                    sticks: 10,
+                    moves: vec![1, 3, 4]
+                }
+            ])
+        );
+        assert!(config(&["nim", "--games", "kayles:7", "--sticks", "4"]).is_err());
+        assert!(config(&["nim", "--games", "kayles:7", "--variant", "misere"]).is_err());
+        assert!(config(&["nim", "host", "--games", "kayles:7"]).is_err());
+        assert!(config(&["nim", "--games", "kayles:7", "--tui"]).is_err());
+    }
+
+    #[test]
+    fn test_wythoff_variant() {
+        let setup = config(&["nim", "--wythoff", "--sticks", "3,5"]).unwrap();
+        assert!(setup.wythoff);
+        assert!(!config(&["nim"]).unwrap().wythoff);
+        assert!(config(&["nim", "--wythoff", "--sticks", "3,5,7"]).is_err());
+        assert!(config(&["nim", "--wythoff", "--max", "2"]).is_err());
+        assert!(config(&["nim", "--wythoff", "--games", "kayles:3"]).is_err());
+    }
+
+    #[test]
+    fn test_teams_of_players() {
+        let setup = config(&[
+            "nim",
+            "--players",
+            "alice,bob,carol,hard",
+            "--teams",
+            "1,2,1,2",
+            "--shuffle",
+            "--first",
+            "4",
+        ])
+        .unwrap();
+        assert_eq!(setup.players.map(|players| players.len()), Some(4));
+        assert_eq!(setup.teams, Some(vec![0, 1, 0, 1]));
+        assert!(setup.shuffle);
+        assert_eq!(setup.first, Some(First::Player(3)));
+        assert!(!config(&["nim"]).unwrap().shuffle);
+        assert!(config(&["nim", "--tui"]).unwrap().tui);
+        assert!(config(&["nim", "--coach"]).unwrap().coach);
+    }
+
+    #[test]
+    fn test_network_modes() {
+        let setup = config(&["nim", "host", "--sticks", "15"]).unwrap();
+        assert_eq!(
+            setup.mode,
+            Mode::Host {
+                port: net::DEFAULT_PORT
+            }
+        );
+        assert_eq!(setup.sticks, Some(vec![15]));
+        let setup = config(&["nim", "host", "--port", "9000"]).unwrap();
+        assert_eq!(setup.mode, Mode::Host { port: 9000 });
+        let setup = config(&["nim", "join", "127.0.0.1:9000"]).unwrap();
+        assert_eq!(
+            setup.mode,
+            Mode::Join {
+                address: String::from("127.0.0.1:9000")
+            }
+        );
+        assert!(config(&["nim", "join"]).is_err());
+        assert!(config(&["nim", "host", "--shuffle"]).is_err());
+        assert!(config(&["nim", "host", "--players", "alice,bob"]).is_err());
+        assert!(config(&["nim", "host", "--resume", "game.json"]).is_err());
+    }
+
+    #[test]
+    fn test_tournament() {
+        let setup = config(&["nim", "tournament", "--sticks", "3,4,5"]).unwrap();
+        assert_eq!(
+            setup.mode,
+            Mode::Tournament {
+                strategies: None,
+                rounds: 100,
+                seed: 0
+            }
+        );
+        let setup = config(&[
+            "nim",
+            "tournament",
+            "--strategies",
+            "random,optimal",
+            "--rounds",
+            "10",
+            "--seed",
+            "42",
+        ])
+        .unwrap();
+        assert_eq!(
+            setup.mode,
+            Mode::Tournament {
+                strategies: Some(vec![String::from("random"), String::from("optimal")]),
+                rounds: 10,
+                seed: 42
+            }
+        );
+        assert!(config(&["nim", "tournament", "--strategies", "random"]).is_err());
+        assert!(config(&["nim", "tournament", "--rounds", "0"]).is_err());
+        assert!(config(&["nim", "tournament", "--players", "a,b"]).is_err());
+        assert!(config(&["nim", "tournament", "--shuffle"]).is_err());
+    }
+
+    #[test]
+    fn test_stats() {
+        let setup = config(&["nim", "stats"]).unwrap();
+        assert_eq!(
+            setup.mode,
+            Mode::Stats {
+                recent: profile::DEFAULT_RECENT
+            }
+        );
+        assert_eq!(setup.profiles, None);
+        let setup = config(&["nim", "stats", "--recent", "0", "--profiles", "club.json"]).unwrap();
+        assert_eq!(setup.mode, Mode::Stats { recent: 0 });
+        assert_eq!(setup.profiles, Some(PathBuf::from("club.json")));
+        assert!(config(&["nim", "stats", "--recent", "-1"]).is_err());
+    }
+
+    #[test]
+    fn test_analyze() {
+        let setup =
+            config(&["nim", "analyze", "--sticks", "3,4,5", "--variant", "misere"]).unwrap();
+        assert_eq!(setup.mode, Mode::Analyze);
+        assert_eq!(setup.sticks, Some(vec![3, 4, 5]));
+        assert!(config(&["nim", "analyze", "--wythoff", "--sticks", "3,5"]).is_ok());
+        assert!(config(&["nim", "analyze"]).is_err());
+        assert!(config(&["nim", "analyze", "--sticks", "9", "--players", "a,b"]).is_err());
+        assert!(config(&["nim", "analyze", "--games", "kayles:3"]).is_err());
+    }
+
+    #[test]
+    fn test_train() {
+        let setup = config(&["nim", "train", "--sticks", "21", "--moves", "1,3,4"]).unwrap();
+        assert_eq!(
+            setup.mode,
+            Mode::Train {
+                episodes: learn::DEFAULT_EPISODES,
+                seed: 0,
+                table: None
+            }
+        );
+        assert_eq!(setup.moves, Some(vec![1, 3, 4]));
+        let setup = config(&[
+            "nim",
+            "train",
+            "--sticks",
+            "9",
+            "--episodes",
+            "500",
+            "--seed",
+            "3",
+            "--table",
+            "t.json",
+        ])
+        .unwrap();
+        assert_eq!(
+            setup.mode,
+            Mode::Train {
+                episodes: 500,
+                seed: 3,
+                table: Some(PathBuf::from("t.json"))
+            }
+        );
+        assert!(config(&["nim", "train"]).is_err());
+        assert!(config(&["nim", "train", "--sticks", "9", "--episodes", "0"]).is_err());
+        assert!(config(&["nim", "train", "--sticks", "9", "--players", "a,b"]).is_err());
+    }
+
+    #[test]
+    fn test_languages() {
+        let locale = |args: &[&str]| locale(&command().try_get_matches_from(args).unwrap());
+        assert_eq!(locale(&["nim"]).language, Language::from_env());
+        assert_eq!(
+            locale(&["nim", "--lang", "fr", "--ascii"]),
+            Locale {
+                language: Language::French,
+                ascii: true
+            }
+        );
+        let english = locale(&["nim", "join", "localhost:7878", "--lang", "english"]);
+        assert_eq!(english.language, Language::English);
+        assert!(config(&["nim", "--lang", "klingon"]).is_err());
+
+        messages::set_locale(Locale {
+            language: Language::French,
+            ascii: false,
+        });
+        assert_eq!(
+            config(&["nim", "--min", "4", "--max", "2"]).err().unwrap(),
+            "--min (4) ne doit pas dépasser --max (2)"
+        );
+        messages::set_locale(Locale::default());
+    }
+
+    #[test]
+    fn test_serve() {
+        let setup = config(&["nim", "serve"]).unwrap();
+        assert_eq!(
+            setup.mode,
+            Mode::Serve {
+                address: server::DEFAULT_ADDRESS,
+                port: server::DEFAULT_PORT
+            }
+        );
+        let setup = config(&["nim", "serve", "--port", "9000", "--sticks", "3,4,5"]).unwrap();
+        assert_eq!(
+            setup.mode,
+            Mode::Serve {
+                address: server::DEFAULT_ADDRESS,
+                port: 9000
+            }
+        );
+        assert_eq!(setup.sticks, Some(vec![3, 4, 5]));
+        let setup = config(&["nim", "serve", "--address", "0.0.0.0"]).unwrap();
+        assert!(matches!(setup.mode, Mode::Serve { address, .. } if address.is_unspecified()));
+        assert!(config(&["nim", "serve", "--address", "localhost:80"]).is_err());
+        assert!(config(&["nim", "serve", "--players", "alice,hard"]).is_ok());
+        assert!(config(&["nim", "serve", "--tui"]).is_err());
+        assert!(config(&["nim", "serve", "--teams", "1,2"]).is_err());
+        assert!(config(&["nim", "serve", "--games", "kayles:3"]).is_err());
+    }
+
+    #[test]
+    fn test_transcripts() {
+        let setup = config(&["nim", "--transcript", "game.txt"]).unwrap();
+        assert_eq!(setup.transcript, Some(PathBuf::from("game.txt")));
+        let setup = config(&["nim", "host", "--transcript", "game.txt"]).unwrap();
+        assert_eq!(setup.transcript, Some(PathBuf::from("game.txt")));
+        let setup = config(&["nim", "replay", "game.txt"]).unwrap();
+        assert_eq!(
+            setup.mode,
+            Mode::Replay {
+                path: PathBuf::from("game.txt")
+            }
+        );
+        assert!(config(&["nim", "replay"]).is_err());
+        assert!(config(&["nim", "join", "localhost:7878", "--transcript", "game.txt"]).is_err());
+        assert!(config(&["nim", "--games", "kayles:3", "--transcript", "game.txt"]).is_err());
+    }
+
+    #[test]
+    fn test_time_control() {
+        assert_eq!(config(&["nim"]).unwrap().time_control, None);
+        let setup = config(&["nim", "--move-time", "30", "--time-bank", "300"]).unwrap();
+        assert_eq!(
+            setup.time_control,
+            Some(TimeControl {
+                per_move: Some(Duration::from_secs(30)),
+                bank: Some(Duration::from_secs(300)),
+                penalty: Penalty::Forfeit,
+            })
+        );
+        let setup = config(&["nim", "--time-bank", "60", "--timeout-penalty", "random"]).unwrap();
+        assert_eq!(
+            setup
+                .time_control
+                .map(|control| (control.per_move, control.penalty)),
+            Some((None, Penalty::Random))
+        );
+        assert!(config(&["nim", "--timeout-penalty", "random"]).is_err());
+        assert!(config(&["nim", "--move-time", "0"]).is_err());
+        assert!(config(&["nim", "--move-time", "10", "--tui"]).is_err());
+        assert!(config(&["nim", "--move-time", "10", "--games", "kayles:3"]).is_err());
+        assert!(config(&["nim", "--move-time", "10", "host"]).is_err());
+    }
+
+    #[test]
+    fn test_invalid_setups() {
+        assert!(config(&["nim", "--min", "4", "--max", "3"]).is_err());
+        assert!(config(&["nim", "--sticks", "0"]).is_err());
+        assert!(config(&["nim", "--players", "alice"]).is_err());
+        assert!(config(&["nim", "--moves", "1,3", "--max", "4"]).is_err());
+        assert!(config(&["nim", "--moves", "1,3", "--sticks", "3,4"]).is_err());
+        assert!(config(&["nim", "--moves", "0,3"]).is_err());
+        assert!(config(&["nim", "--players", "a,b,c", "--teams", "1,2"]).is_err());
+        assert!(config(&["nim", "--teams", "1,1,1"]).is_err());
+        assert!(config(&["nim", "--teams", "0,1"]).is_err());
+        assert!(config(&["nim", "--players", "alice,bob", "--first", "3"]).is_err());
+        assert!(config(&["nim", "--variant", "sideways"]).is_err());
+    }
+}
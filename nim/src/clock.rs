@@ -0,0 +1,146 @@
+/*
+Chess clock style time controls.
+
+A time control limits each move, gives each player a bank of time for the whole
+game, or both. Only the time spent at the prompt counts, computer players are not
+timed.
+*/
+
+use std::fmt;
+use std::time::Duration;
+
+/// What happens to a player running out of time
+#[derive(PartialEq, Debug, Clone, Copy, Default)]
+pub enum Penalty {
+    /// The player loses the game
+    #[default]
+    Forfeit,
+    /// A random legal move is played instead
+    Random,
+}
+
+impl std::str::FromStr for Penalty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "forfeit" => Ok(Penalty::Forfeit),
+            "random" => Ok(Penalty::Random),
+            other => Err(format!("Unknown timeout penalty: {}", other)),
+        }
+    }
+}
+
+impl fmt::Display for Penalty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Penalty::Forfeit => write!(f, "forfeit"),
+            Penalty::Random => write!(f, "random"),
+        }
+    }
+}
+
+/// Time allowed to the players, at least one of the limits being set
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct TimeControl {
+    /// Longest a single move may take
+    pub per_move: Option<Duration>,
+    /// Time each player has for all their moves
+    pub bank: Option<Duration>,
+    pub penalty: Penalty,
+}
+
+/// Time left to each player during a game
+pub struct Clock {
+    control: TimeControl,
+    banks: Vec<Option<Duration>>,
+}
+
+impl Clock {
+    pub fn new(control: TimeControl, players: usize) -> Clock {
+        Clock {
+            control,
+            banks: vec![control.bank; players],
+        }
+    }
+
+    pub fn penalty(&self) -> Penalty {
+        self.control.penalty
+    }
+
+    /// Time the player has for their coming move, `None` without any limit
+    pub fn time_left(&self, player: usize) -> Option<Duration> {
+        let bank = self.banks.get(player).copied().flatten();
+        match (self.control.per_move, bank) {
+            (Some(per_move), Some(bank)) => Some(per_move.min(bank)),
+            (per_move, bank) => per_move.or(bank),
+        }
+    }
+
+    /// Whether the player has been thinking for longer than allowed
+    pub fn is_out_of_time(&self, player: usize, elapsed: Duration) -> bool {
+        self.time_left(player).is_some_and(|left| elapsed > left)
+    }
+
+    /// Take the time spent on a move off the player's bank
+    pub fn charge(&mut self, player: usize, elapsed: Duration) {
+        if let Some(Some(bank)) = self.banks.get_mut(player) {
+            *bank = bank.saturating_sub(elapsed);
+        }
+    }
+}
+
+/// A duration as minutes and seconds, like a chess clock shows it
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seconds(seconds: u64) -> Duration {
+        Duration::from_secs(seconds)
+    }
+
+    #[test]
+    fn test_per_move_and_bank() {
+        let control = TimeControl {
+            per_move: Some(seconds(30)),
+            bank: Some(seconds(50)),
+            penalty: Penalty::Forfeit,
+        };
+        let mut clock = Clock::new(control, 2);
+        assert_eq!(clock.time_left(0), Some(seconds(30)));
+        assert!(!clock.is_out_of_time(0, seconds(25)));
+        clock.charge(0, seconds(25));
+        assert_eq!(clock.time_left(0), Some(seconds(25)));
+        assert_eq!(clock.time_left(1), Some(seconds(30)));
+        assert!(clock.is_out_of_time(0, seconds(26)));
+        clock.charge(0, seconds(26));
+        assert_eq!(clock.time_left(0), Some(seconds(0)));
+        assert!(clock.is_out_of_time(1, seconds(31)));
+
+        let clock = Clock::new(
+            TimeControl {
+                per_move: None,
+                bank: None,
+                penalty: Penalty::Random,
+            },
+            2,
+        );
+        assert_eq!(clock.time_left(0), None);
+        assert!(!clock.is_out_of_time(0, seconds(3600)));
+        assert_eq!(clock.penalty(), Penalty::Random);
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(seconds(0)), "0:00");
+        assert_eq!(format_duration(Duration::from_millis(65_900)), "1:05");
+        assert_eq!(format_duration(seconds(3600)), "60:00");
+        assert_eq!("Random".parse::<Penalty>(), Ok(Penalty::Random));
+        assert!("never".parse::<Penalty>().is_err());
+    }
+}
@@ -0,0 +1,326 @@
+/*
+Explanations of the solver's moves, for the `hint` command and the `--coach` mode.
+
+Each kind of position has its own reason for a move being winning: the period of a
+heap with a range of moves, the losing positions of a subtraction set, the nim-sum
+of several heaps and the cold positions of Wythoff's game.
+*/
+
+use crate::core::{Convention, Game, Heaps, LosingPositions, Move, Rules, StickNumber};
+use crate::messages::Message;
+
+/// Losing positions listed at most in a hint, smallest first
+const LISTED_POSITIONS: usize = 8;
+
+/// Describe taking `amount` from heap `heap` the way the prompts name heaps
+fn describe_move(heaps: &Heaps, heap: usize, amount: u64) -> String {
+    if heaps.len() == 1 {
+        Message::Take(amount).to_string()
+    } else if Some(heap) == heaps.both_heaps() {
+        Message::TakeFromBoth(amount).to_string()
+    } else {
+        Message::TakeFromHeap {
+            amount,
+            heap: heap + 1,
+        }
+        .to_string()
+    }
+}
+
+fn after_move(heaps: &Heaps, heap: usize, amount: u64) -> Option<Heaps> {
+    let mut after = heaps.clone();
+    after.remove_sticks(heap, Ok(amount)).ok()?;
+    Some(after)
+}
+
+/// Position of `remaining` within the period of a heap with a range of moves
+fn describe_offset(remaining: u64, period: u128) -> String {
+    match u128::from(remaining) % period {
+        0 => Message::MultipleOf(period).to_string(),
+        offset => Message::MoreThanMultiple { offset, period }.to_string(),
+    }
+}
+
+fn explain_range(sticks: &StickNumber, rules: &Rules) -> String {
+    let remaining = sticks.get_remaining();
+    let period = u128::from(sticks.get_min_remove()) + u128::from(sticks.get_max_remove());
+    let answer = Message::AnswerWith(period).to_string();
+    let hint = match sticks.winning_move(rules) {
+        Some(take) => Message::HintLeave {
+            take,
+            left: remaining - take,
+            position: &describe_offset(remaining - take, period),
+            reason: &answer,
+        }
+        .to_string(),
+        None => Message::NoWinningMove {
+            remaining,
+            position: &describe_offset(remaining, period),
+            reason: &answer,
+            take: sticks.get_min_remove(),
+        }
+        .to_string(),
+    };
+    format!("  | {}\n", hint)
+}
+
+fn explain_moves(sticks: &StickNumber, moves: &[u64], rules: &Rules) -> String {
+    let remaining = sticks.get_remaining();
+    let losing: Vec<String> = LosingPositions::new(moves, rules)
+        .first(LISTED_POSITIONS, remaining)
+        .iter()
+        .map(|position| position.to_string())
+        .collect();
+    let allowed: Vec<String> = moves.iter().map(|take| take.to_string()).collect();
+    let reason = Message::TakingPerTurn {
+        allowed: &allowed.join("/"),
+        losing: &losing.join(", "),
+    }
+    .to_string();
+    let hint = match sticks.winning_move(rules) {
+        Some(take) => Message::HintLeaveLosing {
+            take,
+            left: remaining - take,
+            reason: &reason,
+        },
+        None => Message::NoWinningMoveLosing {
+            remaining,
+            reason: &reason,
+        },
+    };
+    format!("  | {}\n", hint)
+}
+
+/// Heap sizes in binary with their XOR underneath
+fn nim_sum_breakdown(heaps: &Heaps) -> String {
+    let sizes: Vec<u64> = (0..heaps.len())
+        .filter_map(|index| heaps.get_heap(index))
+        .map(|heap| heap.get_remaining())
+        .collect();
+    let bits = sizes
+        .iter()
+        .map(|size| 64 - size.leading_zeros() as usize)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let mut text = String::new();
+    for (index, size) in sizes.iter().enumerate() {
+        text.push_str(&format!(
+            "  |   {}: {:>3} = {:0bits$b}\n",
+            Message::HeapLabel(index + 1),
+            size,
+            size
+        ));
+    }
+    let nim_sum = heaps.nim_sum();
+    text.push_str(&format!(
+        "  |   {}: {:>3} = {:0bits$b}\n",
+        Message::NimSumLabel,
+        nim_sum,
+        nim_sum
+    ));
+    text
+}
+
+fn explain_nim(heaps: &Heaps, rules: &Rules) -> String {
+    let larger = (0..heaps.len())
+        .filter_map(|index| heaps.get_heap(index))
+        .filter(|heap| heap.get_remaining() > 1)
+        .count();
+    if rules.convention == Convention::Misere && larger <= 1 {
+        let reason = Message::MisereNimReason.to_string();
+        return match heaps.winning_move(rules) {
+            Some((heap, take)) => {
+                let mv = describe_move(heaps, heap, take);
+                let hint = Message::HintMove {
+                    mv: &mv,
+                    reason: &reason,
+                };
+                format!("  | {}\n", hint)
+            }
+            None => format!("  | {}\n", Message::NoWinningMoveMisere(&reason)),
+        };
+    }
+    let breakdown = nim_sum_breakdown(heaps);
+    match heaps.winning_move(rules) {
+        Some((heap, take)) => format!(
+            "  | {}\n{}  | {}\n",
+            Message::HintNimSum(&describe_move(heaps, heap, take)),
+            breakdown,
+            Message::NimSumKeeps
+        ),
+        None => format!(
+            "  | {}\n{}  | {}\n",
+            Message::NoWinningMoveNimSum,
+            breakdown,
+            Message::NimSumBreaks
+        ),
+    }
+}
+
+/// Why heaps whose larger one holds `large` sticks form a cold position
+fn wythoff_reason(large: u64, rules: &Rules) -> String {
+    if rules.convention == Convention::Misere && large <= 2 {
+        Message::MisereWythoffReason.to_string()
+    } else {
+        Message::WythoffReason.to_string()
+    }
+}
+
+/// Both heap sizes, smallest first
+fn sorted_sizes(heaps: &Heaps) -> [u64; 2] {
+    let mut sizes =
+        [0, 1].map(|index| heaps.get_heap(index).map_or(0, |heap| heap.get_remaining()));
+    sizes.sort_unstable();
+    sizes
+}
+
+fn explain_wythoff(heaps: &Heaps, rules: &Rules) -> String {
+    match heaps.winning_move(rules) {
+        Some((heap, take)) => {
+            let after = after_move(heaps, heap, take).unwrap_or_else(|| heaps.clone());
+            let [small, large] = sorted_sizes(&after);
+            let hint = Message::HintLeaveCold {
+                mv: &describe_move(heaps, heap, take),
+                small,
+                large,
+                reason: &wythoff_reason(large, rules),
+            };
+            format!("  | {}\n", hint)
+        }
+        None => {
+            let [small, large] = sorted_sizes(heaps);
+            let hint = Message::NoWinningMoveCold {
+                small,
+                large,
+                reason: &wythoff_reason(large, rules),
+            };
+            format!("  | {}\n", hint)
+        }
+    }
+}
+
+/// Best move for the player to move and why it wins, or why nothing does
+pub fn hint(game: &Game) -> String {
+    let heaps = game.heaps();
+    let rules = game.rules();
+    if heaps.both_heaps().is_some() {
+        return explain_wythoff(heaps, rules);
+    }
+    match (heaps.len(), heaps.get_heap(0)) {
+        (1, Some(sticks)) => match sticks.get_moves() {
+            Some(moves) => explain_moves(sticks, moves, rules),
+            None => explain_range(sticks, rules),
+        },
+        _ => explain_nim(heaps, rules),
+    }
+}
+
+/// Comment on a move about to be played from the current position
+pub fn review(game: &Game, mv: Move) -> String {
+    let heaps = game.heaps();
+    let rules = game.rules();
+    let after = match after_move(heaps, mv.heap, mv.amount) {
+        Some(after) => after,
+        None => return String::new(),
+    };
+    match heaps.winning_move(rules) {
+        _ if !after.is_over() && !after.is_winning(rules) => {
+            format!("  | {}\n", Message::CoachGood)
+        }
+        _ if after.is_over() && rules.convention == Convention::Normal => {
+            format!("  | {}\n", Message::CoachLastStick)
+        }
+        Some((heap, take)) => format!(
+            "  | {}\n",
+            Message::CoachMistake(&describe_move(heaps, heap, take))
+        ),
+        None => format!("  | {}\n", Message::CoachHopeless),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_game(heaps: Heaps, convention: Convention) -> Game {
+        let names = vec![String::from("alice"), String::from("bob")];
+        Game::new(heaps, Rules::new(convention), names, 0)
+    }
+
+    #[test]
+    fn test_hint_single_heap() {
+        let game = new_game(
+            Heaps::single(StickNumber::new(10, 1, 3)),
+            Convention::Normal,
+        );
+        assert_eq!(
+            hint(&game),
+            "  | Hint: take 2 to leave 8 sticks, a multiple of 4: whatever the other player \
+             takes, answering with 4 minus it keeps it that way\n"
+        );
+        let game = new_game(
+            Heaps::single(StickNumber::new(10, 1, 3)),
+            Convention::Misere,
+        );
+        assert!(hint(&game).starts_with("  | Hint: take 1 to leave 9 sticks, 1 more than"));
+        let game = new_game(Heaps::single(StickNumber::new(8, 1, 3)), Convention::Normal);
+        assert!(hint(&game)
+            .starts_with("  | Hint: no winning move, 8 sticks is already a multiple of 4"));
+    }
+
+    #[test]
+    fn test_hint_subtraction_set() {
+        let game = new_game(
+            Heaps::single(StickNumber::with_moves(10, &[1, 3, 4])),
+            Convention::Normal,
+        );
+        assert_eq!(
+            hint(&game),
+            "  | Hint: take 1 to leave 9 sticks, a losing position: taking 1/3/4 per turn, \
+             the player to move loses with 0, 2, 7, 9 sticks\n"
+        );
+    }
+
+    #[test]
+    fn test_hint_nim_sum() {
+        let game = new_game(Heaps::new(&[3, 4, 5]), Convention::Normal);
+        let text = hint(&game);
+        assert!(text.starts_with("  | Hint: take 2 from heap 1 so that the nim-sum"));
+        assert!(text.contains("  |   heap 2:   4 = 100\n"));
+        assert!(text.contains("  |   nim-sum:   2 = 010\n"));
+        let game = new_game(Heaps::new(&[1, 2, 3]), Convention::Normal);
+        assert!(hint(&game).starts_with("  | Hint: no winning move, the nim-sum"));
+        let game = new_game(Heaps::new(&[1, 1, 4]), Convention::Misere);
+        assert!(hint(&game).starts_with("  | Hint: take 3 from heap 3: in misère play"));
+    }
+
+    #[test]
+    fn test_hint_wythoff() {
+        let game = new_game(Heaps::wythoff(4, 8), Convention::Normal);
+        assert!(hint(&game).starts_with("  | Hint: take 1 from heap 2 to leave heaps of 4 and 7"));
+        let game = new_game(Heaps::wythoff(3, 5), Convention::Normal);
+        assert!(hint(&game).starts_with("  | Hint: no winning move, heaps of 3 and 5"));
+        let game = new_game(Heaps::wythoff(2, 3), Convention::Misere);
+        assert!(hint(&game).contains("take 1 from heap 2 to leave heaps of 2 and 2"));
+        assert!(hint(&game).contains("the last cold positions are 0 and 1"));
+    }
+
+    #[test]
+    fn test_review() {
+        let game = new_game(Heaps::new(&[3, 4, 5]), Convention::Normal);
+        assert!(review(&game, Move { heap: 0, amount: 2 }).starts_with("  | Coach: good move"));
+        assert_eq!(
+            review(&game, Move { heap: 2, amount: 1 }),
+            "  | Coach: mistake, take 2 from heap 1 was winning. Type hint to see why\n"
+        );
+        let game = new_game(Heaps::single(StickNumber::new(3, 1, 3)), Convention::Normal);
+        assert_eq!(
+            review(&game, Move { heap: 0, amount: 3 }),
+            "  | Coach: good move, that was the last stick\n"
+        );
+        let game = new_game(Heaps::single(StickNumber::new(4, 1, 3)), Convention::Normal);
+        assert!(review(&game, Move { heap: 0, amount: 1 }).starts_with("  | Coach: no move"));
+        assert_eq!(review(&game, Move { heap: 0, amount: 9 }), "");
+    }
+}
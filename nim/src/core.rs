@@ -0,0 +1,1597 @@
+use crate::messages::Message;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::{fmt, num::ParseIntError};
+
+/// Heaps with more sticks than this are drawn with a scaled bar
+pub const DRAWN_STICKS: u64 = 40;
+
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub struct StickNumber {
+    remaining: u64,
+    total: u64,
+    min_remove: u64,
+    max_remove: u64,
+    /// Allowed removals of a subtraction game, any amount within the limits when `None`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    moves: Option<Vec<u64>>,
+}
+
+#[derive(PartialEq, Debug)]
+pub enum StickNumberError {
+    RemainingNegative,
+    TakeOverMax,
+    TakeUnderMin,
+    DisallowedAmount,
+    ParsingError,
+    HeapOutOfRange,
+    GameOver,
+}
+
+#[derive(PartialEq, Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum Convention {
+    /// Whoever takes the last stick wins
+    Normal,
+    /// Whoever takes the last stick loses
+    Misere,
+}
+
+#[derive(PartialEq, Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Rules {
+    pub convention: Convention,
+}
+
+impl Rules {
+    pub fn new(convention: Convention) -> Rules {
+        Rules { convention }
+    }
+
+    /// Index of the winner once `last_player` has taken the last stick, `teams`
+    /// giving the team of each player in turn order
+    pub fn winner(&self, last_player: usize, teams: &[usize]) -> usize {
+        match self.convention {
+            Convention::Normal => last_player,
+            Convention::Misere => next_opponent(last_player, teams),
+        }
+    }
+}
+
+/// The next player in turn order outside the team of `player`
+fn next_opponent(player: usize, teams: &[usize]) -> usize {
+    (1..teams.len())
+        .map(|step| (player + step) % teams.len())
+        .find(|&other| teams[other] != teams[player])
+        .unwrap_or(player)
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules::new(Convention::Normal)
+    }
+}
+
+impl std::str::FromStr for Convention {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "normal" => Ok(Convention::Normal),
+            "misere" | "misère" => Ok(Convention::Misere),
+            other => Err(Message::UnknownConvention(other).to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Convention {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Convention::Normal => write!(f, "normal"),
+            Convention::Misere => write!(f, "misère"),
+        }
+    }
+}
+
+impl StickNumber {
+    pub fn new(total: u64, min_remove: u64, max_remove: u64) -> StickNumber {
+        let remaining = total;
+        StickNumber {
+            remaining,
+            total,
+            min_remove,
+            max_remove,
+            moves: None,
+        }
+    }
+
+    /// Heap of a subtraction game where a move removes one of the given amounts
+    pub fn with_moves(total: u64, moves: &[u64]) -> StickNumber {
+        let mut moves: Vec<u64> = moves.iter().copied().filter(|&take| take > 0).collect();
+        moves.sort_unstable();
+        moves.dedup();
+        StickNumber {
+            remaining: total,
+            total,
+            min_remove: moves.first().copied().unwrap_or(1),
+            max_remove: moves.last().copied().unwrap_or(0),
+            moves: Some(moves),
+        }
+    }
+
+    pub fn get_remaining(&self) -> u64 {
+        self.remaining
+    }
+
+    pub fn get_total(&self) -> u64 {
+        self.total
+    }
+
+    pub fn get_min_remove(&self) -> u64 {
+        self.min_remove
+    }
+
+    pub fn get_max_remove(&self) -> u64 {
+        self.max_remove
+    }
+
+    pub fn get_moves(&self) -> Option<&[u64]> {
+        self.moves.as_deref()
+    }
+
+    /// Amounts that can be removed right now, smallest first
+    ///
+    /// Every amount between the limits is listed, use `next_move` and
+    /// `previous_move` to walk through them on big heaps.
+    pub fn legal_moves(&self) -> Vec<u64> {
+        let mut moves = Vec::new();
+        let mut take = 0;
+        while let Some(next) = self.next_move(take) {
+            moves.push(next);
+            take = next;
+        }
+        moves
+    }
+
+    /// Number of amounts that can be removed right now, without listing them
+    pub fn count_moves(&self) -> u64 {
+        match (&self.moves, self.next_move(0), self.largest_move()) {
+            (Some(moves), _, _) => moves
+                .iter()
+                .filter(|&&allowed| allowed <= self.remaining)
+                .count() as u64,
+            (None, Some(smallest), Some(largest)) => largest - smallest + 1,
+            (None, _, _) => 0,
+        }
+    }
+
+    /// Smallest legal amount above `take`
+    pub fn next_move(&self, take: u64) -> Option<u64> {
+        match &self.moves {
+            Some(moves) => moves
+                .iter()
+                .copied()
+                .find(|&allowed| allowed > take && allowed <= self.remaining),
+            None => {
+                let next = take.checked_add(1)?.max(self.min_remove);
+                (next <= self.max_remove.min(self.remaining)).then_some(next)
+            }
+        }
+    }
+
+    /// Largest legal amount below `take`
+    pub fn previous_move(&self, take: u64) -> Option<u64> {
+        match &self.moves {
+            Some(moves) => moves
+                .iter()
+                .copied()
+                .rev()
+                .find(|&allowed| allowed < take && allowed <= self.remaining),
+            None => {
+                let previous = take
+                    .checked_sub(1)?
+                    .min(self.max_remove.min(self.remaining));
+                (previous >= self.min_remove).then_some(previous)
+            }
+        }
+    }
+
+    /// Largest legal amount, the most a greedy player can take
+    pub fn largest_move(&self) -> Option<u64> {
+        match &self.moves {
+            Some(moves) => moves
+                .iter()
+                .copied()
+                .rev()
+                .find(|&allowed| allowed <= self.remaining),
+            None => self
+                .next_move(0)
+                .map(|_| self.max_remove.min(self.remaining)),
+        }
+    }
+
+    /// The allowed removals as shown to players
+    pub fn describe_moves(&self) -> String {
+        match &self.moves {
+            Some(moves) => {
+                let moves: Vec<String> = moves.iter().map(|take| take.to_string()).collect();
+                Message::MoveSet(&moves.join(", ")).to_string()
+            }
+            None => Message::MoveRange {
+                min: self.min_remove,
+                max: self.max_remove,
+            }
+            .to_string(),
+        }
+    }
+
+    fn verify_remove_value(
+        &self,
+        value: Result<u64, ParseIntError>,
+    ) -> Result<u64, StickNumberError> {
+        match value {
+            Ok(val) => {
+                if val > self.max_remove {
+                    Err(StickNumberError::TakeOverMax)
+                } else if val < self.min_remove {
+                    Err(StickNumberError::TakeUnderMin)
+                } else if self
+                    .moves
+                    .as_ref()
+                    .is_some_and(|moves| !moves.contains(&val))
+                {
+                    Err(StickNumberError::DisallowedAmount)
+                } else if val > self.remaining {
+                    Err(StickNumberError::RemainingNegative)
+                } else {
+                    Ok(val)
+                }
+            }
+            Err(_) => Err(StickNumberError::ParsingError),
+        }
+    }
+
+    pub fn remove_sticks(
+        &mut self,
+        value: Result<u64, ParseIntError>,
+    ) -> Result<u64, StickNumberError> {
+        let val = self.verify_remove_value(value)?;
+        self.remaining -= val;
+        Ok(self.remaining)
+    }
+
+    /// Give back sticks taken by an undone move, never going over the total
+    pub fn put_back(&mut self, value: u64) -> u64 {
+        self.remaining = self.remaining.saturating_add(value).min(self.total);
+        self.remaining
+    }
+
+    /// Whether the player to move has no legal move left
+    pub fn is_over(&self) -> bool {
+        self.next_move(0).is_none()
+    }
+
+    /// Whether the player to move with `remaining` sticks loses with perfect play
+    ///
+    /// With min_remove = 1 the losing positions are remaining = 0 (mod max_remove + 1)
+    /// in normal play and remaining = 1 (mod max_remove + 1) in misère play. A larger
+    /// min_remove stretches the period to min_remove + max_remove. Other move sets
+    /// go through the dynamic programming solver.
+    fn is_losing(&self, remaining: u64, rules: &Rules) -> bool {
+        if let Some(moves) = &self.moves {
+            return LosingPositions::new(moves, rules).is_losing(remaining);
+        }
+        let (low, high) = self.losing_offsets(rules);
+        let offset = u128::from(remaining) % self.period();
+        offset >= low && offset < high
+    }
+
+    /// Length of the cycle of losing positions with a range of moves
+    fn period(&self) -> u128 {
+        u128::from(self.min_remove) + u128::from(self.max_remove)
+    }
+
+    /// Losing positions with a range of moves, as offsets within the period
+    fn losing_offsets(&self, rules: &Rules) -> (u128, u128) {
+        let min_remove = u128::from(self.min_remove);
+        match rules.convention {
+            Convention::Normal => (0, min_remove),
+            Convention::Misere => (min_remove, 2 * min_remove),
+        }
+    }
+
+    /// Largest losing position between `low` and `high` with a range of moves
+    fn largest_losing(&self, low: u64, high: u64, rules: &Rules) -> Option<u64> {
+        let (first, end) = self.losing_offsets(rules);
+        let period = self.period();
+        let high = u128::from(high);
+        let offset = high % period;
+        let largest = if offset >= first {
+            high - offset + offset.min(end - 1)
+        } else {
+            (high - offset).checked_sub(period)? + end - 1
+        };
+        u64::try_from(largest)
+            .ok()
+            .filter(|&largest| largest >= low)
+    }
+
+    /// Whether the player to move wins with perfect play
+    pub fn is_winning(&self, rules: &Rules) -> bool {
+        !self.is_losing(self.remaining, rules)
+    }
+
+    /// Smallest removal leaving a losing position, if the position allows one
+    pub fn winning_move(&self, rules: &Rules) -> Option<u64> {
+        let smallest = self.next_move(0)?;
+        if let Some(moves) = &self.moves {
+            let losing = LosingPositions::new(moves, rules);
+            return self
+                .legal_moves()
+                .into_iter()
+                .find(|&take| losing.is_losing(self.remaining - take));
+        }
+        let largest = self.largest_move()?;
+        self.largest_losing(self.remaining - largest, self.remaining - smallest, rules)
+            .map(|left| self.remaining - left)
+    }
+}
+
+/// P-positions of the subtraction game with the given moves, by dynamic programming
+///
+/// `is_losing(n)` tells whether the player to move with n sticks loses with perfect
+/// play: a position is losing when every move leads to a winning one. Without any
+/// move left the player to move has lost in normal play and won in misère play.
+/// Once every move fits, a position only depends on as many previous ones as the
+/// largest move, so the table stops at the first repeated window and cycles after.
+pub struct LosingPositions {
+    losing: Vec<bool>,
+    /// Where the cycle starts and how long it is
+    start: usize,
+    period: usize,
+}
+
+impl LosingPositions {
+    pub fn new(moves: &[u64], rules: &Rules) -> LosingPositions {
+        let moves: Vec<usize> = moves
+            .iter()
+            .filter_map(|&take| usize::try_from(take).ok())
+            .filter(|&take| take > 0)
+            .collect();
+        let window = moves.iter().copied().max().unwrap_or(0);
+        let mut losing: Vec<bool> = Vec::new();
+        let mut seen: HashMap<Vec<bool>, usize> = HashMap::new();
+        for sticks in 0.. {
+            if sticks >= window {
+                let last = losing[sticks - window..].to_vec();
+                if let Some(&start) = seen.get(&last) {
+                    return LosingPositions {
+                        losing,
+                        start: start - window,
+                        period: sticks - start,
+                    };
+                }
+                seen.insert(last, sticks);
+            }
+            let next: Vec<bool> = moves
+                .iter()
+                .filter(|&&take| take <= sticks)
+                .map(|&take| losing[sticks - take])
+                .collect();
+            losing.push(if next.is_empty() {
+                rules.convention == Convention::Normal
+            } else {
+                !next.contains(&true)
+            });
+        }
+        unreachable!("the windows of losing positions repeat")
+    }
+
+    pub fn is_losing(&self, sticks: u64) -> bool {
+        let index = match usize::try_from(sticks) {
+            Ok(index) if index < self.losing.len() => index,
+            _ => {
+                let start = self.start as u64;
+                self.start + ((sticks - start) % self.period as u64) as usize
+            }
+        };
+        self.losing[index]
+    }
+
+    /// The first `count` losing positions up to `sticks`
+    pub fn first(&self, count: usize, sticks: u64) -> Vec<u64> {
+        // Without a losing position in the cycle there is none past the table
+        let end = if self.losing[self.start..].contains(&true) {
+            sticks
+        } else {
+            sticks.min(self.losing.len() as u64)
+        };
+        (0..=end)
+            .filter(|&sticks| self.is_losing(sticks))
+            .take(count)
+            .collect()
+    }
+}
+
+/// Share of `width` cells standing for `count` out of `total`, at least one cell for
+/// any stick left so that a heap never looks empty before it is
+pub fn scale(count: u64, total: u64, width: u64) -> u64 {
+    if total == 0 || count == 0 {
+        return 0;
+    }
+    let cells = u128::from(count) * u128::from(width) / u128::from(total);
+    (cells as u64).clamp(1, width)
+}
+
+impl fmt::Display for StickNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (kept, width) = if self.total <= DRAWN_STICKS {
+            (self.remaining, self.total)
+        } else {
+            (
+                scale(self.remaining, self.total, DRAWN_STICKS),
+                DRAWN_STICKS,
+            )
+        };
+        let hashtags_str = "#".repeat(kept as usize);
+        let space_str = " ".repeat((width - kept) as usize);
+        write!(
+            f,
+            "{}/{}[{}{}]",
+            self.remaining, self.total, hashtags_str, space_str
+        )
+    }
+}
+
+impl fmt::Display for StickNumberError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Message::InvalidMove(self))
+    }
+}
+
+/// Which moves the heaps allow
+#[derive(PartialEq, Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum Variant {
+    /// Take from a single heap
+    #[default]
+    Nim,
+    /// Take from one of two heaps, or the same amount from both
+    Wythoff,
+}
+
+/// Whether the player to move loses Wythoff's game on heaps `x` and `y`
+///
+/// In normal play the losing positions are (⌊kφ⌋, ⌊kφ²⌋) = (⌊kφ⌋, ⌊kφ⌋ + k). Misère
+/// play only swaps the two smallest ones, (0, 0) and (1, 2), for (0, 1) and (2, 2).
+fn wythoff_is_losing(x: u64, y: u64, rules: &Rules) -> bool {
+    let (small, large) = (x.min(y), x.max(y));
+    if rules.convention == Convention::Misere && large <= 2 {
+        return (small, large) == (0, 1) || (small, large) == (2, 2);
+    }
+    u128::from(small) == times_phi(u128::from(large - small))
+}
+
+/// ⌊n√5⌋ for n up to u64::MAX, without squaring past u128
+///
+/// Writing ⌊n√5⌋ = 2n + m, m is the largest number with (2n + m)² ≤ 5n², that is
+/// m(m + 4n) ≤ n², and it stays below n/4.
+fn times_sqrt5(n: u128) -> u128 {
+    let fits = |m: u128| {
+        m.checked_mul(m + 4 * n)
+            .is_some_and(|product| product <= n * n)
+    };
+    let (mut low, mut high) = (0, n / 4 + 1);
+    while high - low > 1 {
+        let middle = low + (high - low) / 2;
+        if fits(middle) {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    2 * n + low
+}
+
+/// ⌊kφ⌋ in exact integer arithmetic, as ⌊(k + ⌊k√5⌋) / 2⌋ since k√5 is irrational
+fn times_phi(k: u128) -> u128 {
+    (k + times_sqrt5(k)) / 2
+}
+
+/// ⌊n/φ⌋ = ⌊(⌊n√5⌋ - n) / 2⌋, the same way
+fn over_phi(n: u128) -> u128 {
+    (times_sqrt5(n) - n) / 2
+}
+
+/// The other heap of the normal play losing position holding `sticks`
+///
+/// Every positive number is either ⌊kφ⌋ or ⌊kφ⌋ + k for a single k.
+fn wythoff_partner(sticks: u64) -> u128 {
+    let sticks = u128::from(sticks);
+    if sticks == 0 {
+        return 0;
+    }
+    let k = over_phi(sticks) + 1;
+    if times_phi(k) == sticks {
+        return sticks + k;
+    }
+    let k = sticks - over_phi(sticks);
+    times_phi(k)
+}
+
+/// Several heaps of sticks where a move takes any positive amount from a single heap
+///
+/// A single heap game with its own removal limits is the one heap case. In
+/// Wythoff's game a move may also take from both heaps, which moves name with the
+/// heap index right after the last heap.
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub struct Heaps {
+    heaps: Vec<StickNumber>,
+    #[serde(default)]
+    variant: Variant,
+}
+
+impl Heaps {
+    pub fn new(sizes: &[u64]) -> Heaps {
+        let heaps = sizes
+            .iter()
+            .map(|&size| StickNumber::new(size, 1, size))
+            .collect();
+        Heaps {
+            heaps,
+            variant: Variant::Nim,
+        }
+    }
+
+    pub fn single(sticks: StickNumber) -> Heaps {
+        Heaps {
+            heaps: vec![sticks],
+            variant: Variant::Nim,
+        }
+    }
+
+    /// Two heaps of Wythoff's game
+    pub fn wythoff(first: u64, second: u64) -> Heaps {
+        Heaps {
+            variant: Variant::Wythoff,
+            ..Heaps::new(&[first, second])
+        }
+    }
+
+    /// Heap index of the moves taking from both heaps, in Wythoff's game
+    pub fn both_heaps(&self) -> Option<usize> {
+        match self.variant {
+            Variant::Nim => None,
+            Variant::Wythoff => Some(self.heaps.len()),
+        }
+    }
+
+    /// Most sticks a move can take from both heaps at once
+    pub(crate) fn max_from_both(&self) -> u64 {
+        self.heaps
+            .iter()
+            .map(|heap| heap.get_remaining())
+            .min()
+            .unwrap_or(0)
+    }
+
+    pub fn get_heap(&self, index: usize) -> Option<&StickNumber> {
+        self.heaps.get(index)
+    }
+
+    pub fn len(&self) -> usize {
+        self.heaps.len()
+    }
+
+    /// Iterate over the heaps that still allow a move, with their index
+    pub fn playable(&self) -> impl Iterator<Item = (usize, &StickNumber)> {
+        self.heaps
+            .iter()
+            .enumerate()
+            .filter(|(_, heap)| !heap.is_over())
+    }
+
+    /// Whether no heap allows a move anymore
+    pub fn is_over(&self) -> bool {
+        self.heaps.iter().all(|heap| heap.is_over())
+    }
+
+    /// Sticks left on each heap
+    pub fn sizes(&self) -> Vec<u64> {
+        self.heaps.iter().map(|heap| heap.get_remaining()).collect()
+    }
+
+    /// Every legal move `(heap index, sticks to take)`, heap by heap
+    ///
+    /// All the amounts are listed, which only suits heaps of a reasonable size.
+    pub fn legal_moves(&self) -> Vec<(usize, u64)> {
+        let mut moves: Vec<(usize, u64)> = self
+            .playable()
+            .flat_map(|(index, heap)| {
+                heap.legal_moves()
+                    .into_iter()
+                    .map(move |take| (index, take))
+            })
+            .collect();
+        if let Some(both) = self.both_heaps() {
+            moves.extend((1..=self.max_from_both()).map(|take| (both, take)));
+        }
+        moves
+    }
+
+    /// Number of legal moves, without listing them
+    pub fn count_moves(&self) -> u64 {
+        let both = match self.both_heaps() {
+            Some(_) => self.max_from_both(),
+            None => 0,
+        };
+        self.playable()
+            .map(|(_, heap)| heap.count_moves())
+            .fold(both, u64::saturating_add)
+    }
+
+    /// Whether both allow the same removals from each heap, whatever is left on them
+    pub fn same_moves(&self, other: &Heaps) -> bool {
+        // Only a single heap has limits, any amount can be taken from one of several
+        let limits = |heaps: &Heaps| match &heaps.heaps[..] {
+            [heap] => Some((heap.min_remove, heap.max_remove, heap.moves.clone())),
+            _ => None,
+        };
+        self.variant == other.variant
+            && self.heaps.len() == other.heaps.len()
+            && limits(self) == limits(other)
+    }
+
+    /// Remove sticks from heap `index`, returning what is left on that heap, or on
+    /// the smallest heap when taking from both
+    pub fn remove_sticks(
+        &mut self,
+        index: usize,
+        value: Result<u64, ParseIntError>,
+    ) -> Result<u64, StickNumberError> {
+        if Some(index) == self.both_heaps() {
+            let val = value.map_err(|_| StickNumberError::ParsingError)?;
+            if val == 0 {
+                return Err(StickNumberError::TakeUnderMin);
+            }
+            if val > self.max_from_both() {
+                return Err(StickNumberError::RemainingNegative);
+            }
+            for heap in self.heaps.iter_mut() {
+                heap.remove_sticks(Ok(val))?;
+            }
+            return Ok(self.max_from_both());
+        }
+        match self.heaps.get_mut(index) {
+            Some(heap) => heap.remove_sticks(value),
+            None => Err(StickNumberError::HeapOutOfRange),
+        }
+    }
+
+    /// Give back sticks to heap `index`
+    pub fn put_back(&mut self, index: usize, value: u64) -> Result<u64, StickNumberError> {
+        if Some(index) == self.both_heaps() {
+            for heap in self.heaps.iter_mut() {
+                heap.put_back(value);
+            }
+            return Ok(self.max_from_both());
+        }
+        match self.heaps.get_mut(index) {
+            Some(heap) => Ok(heap.put_back(value)),
+            None => Err(StickNumberError::HeapOutOfRange),
+        }
+    }
+
+    /// XOR of all the heap sizes
+    pub fn nim_sum(&self) -> u64 {
+        self.heaps
+            .iter()
+            .fold(0, |acc, heap| acc ^ heap.get_remaining())
+    }
+
+    fn count_heaps_over_one(&self) -> usize {
+        self.heaps
+            .iter()
+            .filter(|heap| heap.get_remaining() > 1)
+            .count()
+    }
+
+    /// Whether the player to move wins with perfect play
+    ///
+    /// Misère play only differs from normal play once every heap holds at most one
+    /// stick, where the player to move wins with an even number of heaps left.
+    pub fn is_winning(&self, rules: &Rules) -> bool {
+        if let (Variant::Wythoff, [x, y]) = (self.variant, &self.heaps[..]) {
+            return !wythoff_is_losing(x.get_remaining(), y.get_remaining(), rules);
+        }
+        if let [heap] = &self.heaps[..] {
+            return heap.is_winning(rules);
+        }
+        match rules.convention {
+            Convention::Misere if self.count_heaps_over_one() == 0 => {
+                self.playable().count().is_multiple_of(2)
+            }
+            _ => self.nim_sum() != 0,
+        }
+    }
+
+    /// A winning move `(heap index, sticks to take)`, if there is one
+    pub fn winning_move(&self, rules: &Rules) -> Option<(usize, u64)> {
+        if self.variant == Variant::Wythoff {
+            return self.wythoff_winning_move(rules);
+        }
+        if let [heap] = &self.heaps[..] {
+            return heap.winning_move(rules).map(|take| (0, take));
+        }
+        if !self.is_winning(rules) {
+            return None;
+        }
+        if rules.convention == Convention::Misere && self.count_heaps_over_one() <= 1 {
+            return self.misere_endgame_move();
+        }
+        let nim_sum = self.nim_sum();
+        self.heaps.iter().enumerate().find_map(|(index, heap)| {
+            let remaining = heap.get_remaining();
+            let target = remaining ^ nim_sum;
+            if target < remaining {
+                Some((index, remaining - target))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Look for a move leaving a losing position among the moves of both kinds
+    ///
+    /// Rather than trying every move, only the losing positions sharing a heap or
+    /// the difference with the current one are aimed at, along with the misère ones.
+    fn wythoff_winning_move(&self, rules: &Rules) -> Option<(usize, u64)> {
+        let [x, y] =
+            [0, 1].map(|index| self.heaps.get(index).map_or(0, |heap| heap.get_remaining()));
+        let small = x.min(y);
+        let difference = u128::from(x.max(y) - small);
+        let mut targets: Vec<(u128, u128)> = vec![
+            (u128::from(x), wythoff_partner(x)),
+            (wythoff_partner(y), u128::from(y)),
+        ];
+        let lower = times_phi(difference);
+        if x <= y {
+            targets.push((lower, lower + difference));
+        } else {
+            targets.push((lower + difference, lower));
+        }
+        if rules.convention == Convention::Misere {
+            targets.extend([(0, 1), (1, 0), (2, 2)]);
+        }
+
+        let (x, y) = (u128::from(x), u128::from(y));
+        let mut moves: Vec<(usize, u64)> = targets
+            .into_iter()
+            .filter_map(|(target_x, target_y)| {
+                let mv = if target_x == x && target_y < y {
+                    (1, y - target_y)
+                } else if target_y == y && target_x < x {
+                    (0, x - target_x)
+                } else if target_x < x && x - target_x == y.checked_sub(target_y)? {
+                    (2, x - target_x)
+                } else {
+                    return None;
+                };
+                let (target_x, target_y) = (target_x as u64, target_y as u64);
+                wythoff_is_losing(target_x, target_y, rules).then_some((mv.0, mv.1 as u64))
+            })
+            .collect();
+        // Same choice as trying every move from the first heap to both heaps
+        moves.sort_unstable();
+        moves.first().copied()
+    }
+
+    /// Leave an odd number of single stick heaps when at most one heap is larger
+    fn misere_endgame_move(&self) -> Option<(usize, u64)> {
+        let ones = self
+            .heaps
+            .iter()
+            .filter(|heap| heap.get_remaining() == 1)
+            .count();
+        match self.playable().find(|(_, heap)| heap.get_remaining() > 1) {
+            Some((index, heap)) if ones % 2 == 0 => Some((index, heap.get_remaining() - 1)),
+            Some((index, heap)) => Some((index, heap.get_remaining())),
+            None => self.playable().next().map(|(index, _)| (index, 1)),
+        }
+    }
+}
+
+impl fmt::Display for Heaps {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let [heap] = &self.heaps[..] {
+            return write!(f, "{}", heap);
+        }
+        for (index, heap) in self.heaps.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}: {}", index + 1, heap)?;
+        }
+        Ok(())
+    }
+}
+
+/// Take `amount` sticks from heap number `heap`, counted from 0
+#[derive(PartialEq, Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Move {
+    pub heap: usize,
+    pub amount: u64,
+}
+
+/// A move played during the game and who played it
+#[derive(PartialEq, Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Turn {
+    pub player: usize,
+    pub heap: usize,
+    pub amount: u64,
+}
+
+/// What happened during a call to `Game::apply_move`
+#[derive(PartialEq, Debug, Clone)]
+pub enum Event {
+    MovePlayed {
+        player: usize,
+        heap: usize,
+        amount: u64,
+    },
+    TurnStarted {
+        player: usize,
+    },
+    GameOver {
+        winner: usize,
+        turns: u32,
+    },
+}
+
+/// Game engine keeping track of the heaps, the players and whose turn it is
+///
+/// It does not do any input or output, front-ends feed it moves and report the
+/// events it returns.
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub struct Game {
+    heaps: Heaps,
+    rules: Rules,
+    player_names: Vec<String>,
+    current_player: usize,
+    history: Vec<Turn>,
+    #[serde(default)]
+    undone: Vec<Turn>,
+    /// Team of each player, empty when everyone plays on their own
+    #[serde(default)]
+    teams: Vec<usize>,
+    /// Player who gave up the game, as when running out of time
+    #[serde(default)]
+    forfeited: Option<usize>,
+}
+
+impl Game {
+    pub fn new(heaps: Heaps, rules: Rules, player_names: Vec<String>, first: usize) -> Game {
+        Game {
+            heaps,
+            rules,
+            player_names,
+            current_player: first,
+            history: Vec::new(),
+            undone: Vec::new(),
+            teams: Vec::new(),
+            forfeited: None,
+        }
+    }
+
+    /// Group the players in teams, `teams` giving the team of each player
+    pub fn with_teams(mut self, teams: Vec<usize>) -> Game {
+        self.teams = teams;
+        self
+    }
+
+    pub fn heaps(&self) -> &Heaps {
+        &self.heaps
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    pub fn player_names(&self) -> &[String] {
+        &self.player_names
+    }
+
+    pub fn current_player(&self) -> usize {
+        self.current_player
+    }
+
+    pub fn has_teams(&self) -> bool {
+        !self.teams.is_empty()
+    }
+
+    /// Team of each player, a team per player when playing without teams
+    pub fn teams(&self) -> Vec<usize> {
+        if self.has_teams() {
+            self.teams.clone()
+        } else {
+            (0..self.player_names.len()).collect()
+        }
+    }
+
+    /// Players of the given team, in turn order
+    pub fn team_members(&self, team: usize) -> Vec<usize> {
+        self.teams()
+            .iter()
+            .enumerate()
+            .filter(|&(_, &player_team)| player_team == team)
+            .map(|(player, _)| player)
+            .collect()
+    }
+
+    /// Moves played so far, oldest first
+    pub fn history(&self) -> &[Turn] {
+        &self.history
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.forfeited.is_some() || self.heaps.is_over()
+    }
+
+    /// Player who gave up the game, if anyone did
+    pub fn forfeited(&self) -> Option<usize> {
+        self.forfeited
+    }
+
+    /// Index of the winner once the game is over
+    pub fn winner(&self) -> Option<usize> {
+        if let Some(player) = self.forfeited {
+            return Some(next_opponent(player, &self.teams()));
+        }
+        if !self.is_over() {
+            return None;
+        }
+        self.history
+            .last()
+            .map(|turn| self.rules.winner(turn.player, &self.teams()))
+    }
+
+    /// Play a move for the current player, which forgets the undone moves
+    pub fn apply_move(&mut self, mv: Move) -> Result<Vec<Event>, StickNumberError> {
+        let events = self.play(mv)?;
+        self.undone.clear();
+        Ok(events)
+    }
+
+    /// End the game with the current player giving up, the next player outside
+    /// their team winning
+    pub fn forfeit(&mut self) -> Vec<Event> {
+        if self.is_over() {
+            return Vec::new();
+        }
+        self.forfeited = Some(self.current_player);
+        self.undone.clear();
+        self.winner()
+            .map(|winner| Event::GameOver {
+                winner,
+                turns: self.history.len() as u32,
+            })
+            .into_iter()
+            .collect()
+    }
+
+    /// Take back the last move, giving the turn back to whoever played it
+    pub fn undo(&mut self) -> Option<Turn> {
+        self.forfeited = None;
+        let turn = self.history.pop()?;
+        // The heap existed when the move was played
+        let _ = self.heaps.put_back(turn.heap, turn.amount);
+        self.current_player = turn.player;
+        self.undone.push(turn);
+        Some(turn)
+    }
+
+    /// Play again the last undone move
+    pub fn redo(&mut self) -> Option<Vec<Event>> {
+        let turn = self.undone.pop()?;
+        self.play(Move {
+            heap: turn.heap,
+            amount: turn.amount,
+        })
+        .ok()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.undone.is_empty()
+    }
+
+    /// The game as it was before any move was played
+    pub fn initial(&self) -> Game {
+        let mut game = self.clone();
+        while game.undo().is_some() {}
+        game.undone.clear();
+        game
+    }
+
+    fn play(&mut self, mv: Move) -> Result<Vec<Event>, StickNumberError> {
+        if self.is_over() {
+            return Err(StickNumberError::GameOver);
+        }
+        self.heaps.remove_sticks(mv.heap, Ok(mv.amount))?;
+
+        let player = self.current_player;
+        self.history.push(Turn {
+            player,
+            heap: mv.heap,
+            amount: mv.amount,
+        });
+        self.current_player = (player + 1) % self.player_names.len();
+
+        let mut events = vec![Event::MovePlayed {
+            player,
+            heap: mv.heap,
+            amount: mv.amount,
+        }];
+        match self.winner() {
+            Some(winner) => events.push(Event::GameOver {
+                winner,
+                turns: self.history.len() as u32,
+            }),
+            None => events.push(Event::TurnStarted {
+                player: self.current_player,
+            }),
+        }
+        Ok(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MISERE: Rules = Rules {
+        convention: Convention::Misere,
+    };
+    const NORMAL: Rules = Rules {
+        convention: Convention::Normal,
+    };
+
+    /// Brute force search of the heaps position, for checking the solver
+    fn brute_force_is_winning(sizes: &[u64], rules: &Rules) -> bool {
+        if sizes.iter().all(|&size| size == 0) {
+            // The previous player took the last stick
+            return rules.convention == Convention::Misere;
+        }
+        (0..sizes.len()).any(|index| {
+            (1..=sizes[index]).any(|take| {
+                let mut next = sizes.to_vec();
+                next[index] -= take;
+                !brute_force_is_winning(&next, rules)
+            })
+        })
+    }
+
+    #[test]
+    fn test_winning_move_leaves_losing_position() {
+        let sticks = StickNumber::new(10, 1, 3);
+        // 10 -> 9 -> ... leaving 9 = 1 (mod 4) to the opponent
+        assert_eq!(sticks.winning_move(&MISERE), Some(1));
+        assert_eq!(sticks.winning_move(&NORMAL), Some(2));
+        let sticks = StickNumber::new(12, 1, 3);
+        assert_eq!(sticks.winning_move(&MISERE), Some(3));
+    }
+
+    #[test]
+    fn test_winning_move_none_in_losing_position() {
+        let sticks = StickNumber::new(9, 1, 3);
+        assert_eq!(sticks.winning_move(&MISERE), None);
+        let sticks = StickNumber::new(1, 1, 3);
+        assert_eq!(sticks.winning_move(&MISERE), None);
+        let sticks = StickNumber::new(8, 1, 3);
+        assert_eq!(sticks.winning_move(&NORMAL), None);
+    }
+
+    #[test]
+    fn test_winning_move_with_larger_min_remove() {
+        for rules in [NORMAL, MISERE] {
+            for total in 0..30 {
+                let sticks = StickNumber::new(total, 2, 5);
+                // Brute force over the positions reachable by taking 2 to 5 sticks
+                let mut losing = [false; 30];
+                for n in 0..30usize {
+                    let moves: Vec<usize> = (2..=5).filter(|&k| k <= n).collect();
+                    losing[n] = if moves.is_empty() {
+                        rules.convention == Convention::Normal
+                    } else {
+                        moves.iter().all(|&k| !losing[n - k])
+                    };
+                }
+                if sticks.is_over() {
+                    assert_eq!(sticks.winning_move(&rules), None);
+                    continue;
+                }
+                match sticks.winning_move(&rules) {
+                    Some(take) => assert!(losing[(total - take) as usize], "{}", total),
+                    None => assert!(losing[total as usize], "{}", total),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_losing_positions_of_subtraction_set() {
+        // With moves {1, 3, 4} the losing positions are 0 and 2 (mod 7)
+        let losing = LosingPositions::new(&[1, 3, 4], &NORMAL);
+        for n in 0..=1000 {
+            assert_eq!(losing.is_losing(n), n % 7 == 0 || n % 7 == 2, "{}", n);
+        }
+        assert!(losing.is_losing(7_000_000_000_002));
+        assert_eq!(losing.first(5, 20), vec![0, 2, 7, 9, 14]);
+        assert_eq!(losing.first(5, 8), vec![0, 2, 7]);
+        // The range solver agrees with the dynamic programming one
+        for rules in [NORMAL, MISERE] {
+            let losing = LosingPositions::new(&[2, 3, 4, 5], &rules);
+            for total in 0..=30 {
+                let sticks = StickNumber::new(total, 2, 5);
+                assert_eq!(sticks.is_winning(&rules), !losing.is_losing(total));
+            }
+        }
+    }
+
+    #[test]
+    fn test_losing_positions_cycle_matches_table() {
+        // Compare the cycle past the table with a plain table of the first positions
+        for moves in [&[2, 7][..], &[1, 4, 9, 16], &[3, 5, 11], &[6]] {
+            for rules in [NORMAL, MISERE] {
+                let losing = LosingPositions::new(moves, &rules);
+                let mut table: Vec<bool> = Vec::new();
+                for n in 0..500usize {
+                    let next: Vec<bool> = moves
+                        .iter()
+                        .map(|&take| take as usize)
+                        .filter(|&take| take <= n)
+                        .map(|take| table[n - take])
+                        .collect();
+                    table.push(if next.is_empty() {
+                        rules.convention == Convention::Normal
+                    } else {
+                        next.iter().all(|&losing| !losing)
+                    });
+                    assert_eq!(losing.is_losing(n as u64), table[n], "{:?} {}", moves, n);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_subtraction_set_moves() {
+        let mut sticks = StickNumber::with_moves(9, &[4, 1, 3, 1]);
+        assert_eq!(sticks.get_moves(), Some(&[1, 3, 4][..]));
+        assert_eq!(sticks.describe_moves(), "one of 1, 3, 4");
+        assert_eq!(StickNumber::new(9, 1, 3).describe_moves(), "min=1, max=3");
+        assert_eq!(
+            sticks.remove_sticks(Ok(2)),
+            Err(StickNumberError::DisallowedAmount)
+        );
+        assert_eq!(
+            sticks.remove_sticks(Ok(5)),
+            Err(StickNumberError::TakeOverMax)
+        );
+        // 9 = 2 (mod 7) is losing, every move leaves a winning position
+        assert_eq!(sticks.winning_move(&NORMAL), None);
+        assert_eq!(sticks.remove_sticks(Ok(4)), Ok(5));
+        assert_eq!(sticks.winning_move(&NORMAL), Some(3));
+        assert_eq!(sticks.legal_moves(), vec![1, 3, 4]);
+        sticks.remove_sticks(Ok(3)).unwrap();
+        assert_eq!(sticks.legal_moves(), vec![1]);
+        sticks.remove_sticks(Ok(1)).unwrap();
+        sticks.remove_sticks(Ok(1)).unwrap();
+        assert!(sticks.legal_moves().is_empty());
+        assert!(sticks.is_over());
+    }
+
+    #[test]
+    fn test_subtraction_set_with_gap_ends_early() {
+        let mut sticks = StickNumber::with_moves(5, &[2, 5]);
+        sticks.remove_sticks(Ok(2)).unwrap();
+        sticks.remove_sticks(Ok(2)).unwrap();
+        // A single stick is left but no move takes it
+        assert!(sticks.is_over());
+        assert_eq!(sticks.winning_move(&MISERE), None);
+    }
+
+    /// Exhaustive table of Wythoff's game, for checking the golden ratio solver
+    fn brute_force_wythoff(size: usize, rules: &Rules) -> Vec<Vec<bool>> {
+        let mut winning = vec![vec![false; size]; size];
+        for x in 0..size {
+            for y in 0..size {
+                let mut moves = (0..x)
+                    .map(|left| (left, y))
+                    .chain((0..y).map(|left| (x, left)))
+                    .chain((1..=x.min(y)).map(|take| (x - take, y - take)))
+                    .peekable();
+                winning[x][y] = if moves.peek().is_none() {
+                    rules.convention == Convention::Misere
+                } else {
+                    moves.any(|(x, y)| !winning[x][y])
+                };
+            }
+        }
+        winning
+    }
+
+    #[test]
+    fn test_wythoff_solver_matches_brute_force() {
+        for rules in [NORMAL, MISERE] {
+            let table = brute_force_wythoff(20, &rules);
+            for (x, row) in table.iter().enumerate() {
+                for (y, &winning) in row.iter().enumerate() {
+                    let heaps = Heaps::wythoff(x as u64, y as u64);
+                    assert_eq!(heaps.is_winning(&rules), winning, "{} {}", x, y);
+                    match heaps.winning_move(&rules) {
+                        Some((index, take)) => {
+                            let mut next = heaps.clone();
+                            next.remove_sticks(index, Ok(take)).unwrap();
+                            assert!(!next.is_winning(&rules), "{} {}", x, y);
+                        }
+                        None => assert!(!winning || heaps.is_over(), "{} {}", x, y),
+                    }
+                }
+            }
+        }
+        // Far along the golden ratio sequence
+        assert!(!Heaps::wythoff(144, 233).is_winning(&NORMAL));
+        assert!(Heaps::wythoff(144, 234).is_winning(&NORMAL));
+    }
+
+    #[test]
+    fn test_wythoff_takes_from_both_heaps() {
+        let mut heaps = Heaps::wythoff(3, 5);
+        assert_eq!(heaps.both_heaps(), Some(2));
+        assert_eq!(Heaps::new(&[3, 5]).both_heaps(), None);
+        assert_eq!(
+            heaps.remove_sticks(2, Ok(4)),
+            Err(StickNumberError::RemainingNegative)
+        );
+        assert_eq!(
+            heaps.remove_sticks(2, Ok(0)),
+            Err(StickNumberError::TakeUnderMin)
+        );
+        assert_eq!(
+            heaps.remove_sticks(3, Ok(1)),
+            Err(StickNumberError::HeapOutOfRange)
+        );
+        assert_eq!(heaps.remove_sticks(2, Ok(2)), Ok(1));
+        assert_eq!(heaps.to_string(), "1: 1/3[#  ]\n2: 3/5[###  ]");
+
+        let names = vec![String::from("alice"), String::from("bob")];
+        let mut game = Game::new(Heaps::wythoff(3, 5), NORMAL, names, 0);
+        game.apply_move(Move { heap: 2, amount: 3 }).unwrap();
+        assert_eq!(game.heaps().to_string(), "1: 0/3[   ]\n2: 2/5[##   ]");
+        game.undo();
+        assert_eq!(game.heaps(), &Heaps::wythoff(3, 5));
+    }
+
+    #[test]
+    fn test_rules_winner() {
+        assert_eq!(NORMAL.winner(1, &[0, 1]), 1);
+        assert_eq!(MISERE.winner(1, &[0, 1]), 0);
+        assert_eq!(MISERE.winner(3, &[0, 1, 2, 3, 4]), 4);
+        // The next player is a teammate of the loser
+        assert_eq!(MISERE.winner(0, &[0, 0, 1]), 2);
+        assert_eq!(Rules::default(), NORMAL);
+        assert_eq!("misere".parse::<Convention>(), Ok(Convention::Misere));
+        assert!("backwards".parse::<Convention>().is_err());
+    }
+
+    #[test]
+    fn test_heaps_remove_sticks() {
+        let mut heaps = Heaps::new(&[3, 4, 5]);
+        assert_eq!(heaps.remove_sticks(1, Ok(4)), Ok(0));
+        assert_eq!(
+            heaps.to_string(),
+            "1: 3/3[###]\n2: 0/4[    ]\n3: 5/5[#####]"
+        );
+        assert_eq!(
+            heaps.remove_sticks(0, Ok(4)),
+            Err(StickNumberError::TakeOverMax)
+        );
+        assert_eq!(
+            heaps.remove_sticks(1, Ok(1)),
+            Err(StickNumberError::RemainingNegative)
+        );
+        assert_eq!(
+            heaps.remove_sticks(2, Ok(0)),
+            Err(StickNumberError::TakeUnderMin)
+        );
+        assert_eq!(
+            heaps.remove_sticks(3, Ok(1)),
+            Err(StickNumberError::HeapOutOfRange)
+        );
+    }
+
+    #[test]
+    fn test_heaps_legal_moves() {
+        let heaps = Heaps::new(&[2, 0, 1]);
+        assert_eq!(heaps.sizes(), vec![2, 0, 1]);
+        assert_eq!(heaps.legal_moves(), vec![(0, 1), (0, 2), (2, 1)]);
+        assert_eq!(heaps.count_moves(), 3);
+        let heaps = Heaps::wythoff(1, 2);
+        assert_eq!(heaps.legal_moves(), vec![(0, 1), (1, 1), (1, 2), (2, 1)]);
+        assert_eq!(heaps.count_moves(), 4);
+        assert!(heaps.same_moves(&Heaps::wythoff(5, 3)));
+        assert!(!heaps.same_moves(&Heaps::new(&[1, 2])));
+        assert!(Heaps::new(&[1, 2]).same_moves(&Heaps::new(&[7, 4])));
+        let heaps = Heaps::single(StickNumber::with_moves(4, &[1, 3, 5]));
+        assert_eq!(heaps.legal_moves(), vec![(0, 1), (0, 3)]);
+        assert_eq!(heaps.count_moves(), 2);
+        let heaps = Heaps::single(StickNumber::new(u64::MAX, 2, u64::MAX - 1));
+        assert_eq!(heaps.count_moves(), u64::MAX - 2);
+    }
+
+    #[test]
+    fn test_heaps_nim_sum() {
+        assert_eq!(Heaps::new(&[1, 2, 3]).nim_sum(), 0);
+        assert!(!Heaps::new(&[1, 2, 3]).is_winning(&NORMAL));
+        assert_eq!(Heaps::new(&[3, 4, 5]).nim_sum(), 2);
+        assert!(Heaps::new(&[3, 4, 5]).is_winning(&NORMAL));
+        assert!(!Heaps::new(&[]).is_winning(&NORMAL));
+    }
+
+    #[test]
+    fn test_heaps_winning_move() {
+        let mut heaps = Heaps::new(&[3, 4, 5]);
+        assert_eq!(heaps.winning_move(&NORMAL), Some((0, 2)));
+        heaps.remove_sticks(0, Ok(2)).unwrap();
+        assert_eq!(heaps.nim_sum(), 0);
+        assert_eq!(heaps.winning_move(&NORMAL), None);
+    }
+
+    #[test]
+    fn test_heaps_misere_endgame() {
+        // Leave an odd number of single sticks
+        assert_eq!(Heaps::new(&[1, 1, 4]).winning_move(&MISERE), Some((2, 3)));
+        assert_eq!(Heaps::new(&[1, 4]).winning_move(&MISERE), Some((1, 4)));
+        assert!(!Heaps::new(&[1, 1, 1]).is_winning(&MISERE));
+        assert!(Heaps::new(&[1, 1]).is_winning(&MISERE));
+        assert!(Heaps::new(&[]).is_winning(&MISERE));
+    }
+
+    #[test]
+    fn test_heaps_solver_matches_brute_force() {
+        for rules in [NORMAL, MISERE] {
+            for a in 0..4 {
+                for b in 0..4 {
+                    for c in 0..5 {
+                        let sizes = [a, b, c];
+                        let heaps = Heaps::new(&sizes);
+                        let winning = brute_force_is_winning(&sizes, &rules);
+                        assert_eq!(heaps.is_winning(&rules), winning, "{:?}", sizes);
+                        if let Some((index, take)) = heaps.winning_move(&rules) {
+                            let mut next = sizes;
+                            next[index] -= take;
+                            assert!(!brute_force_is_winning(&next, &rules), "{:?}", sizes);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_single_heap_display() {
+        let mut heaps = Heaps::single(StickNumber::new(5, 1, 3));
+        heaps.remove_sticks(0, Ok(2)).unwrap();
+        assert_eq!(heaps.to_string(), "3/5[###  ]");
+        assert_eq!(heaps.winning_move(&NORMAL), Some((0, 3)));
+        assert_eq!(heaps.winning_move(&MISERE), Some((0, 2)));
+    }
+
+    #[test]
+    fn test_big_heap_display() {
+        let mut heaps = Heaps::single(StickNumber::new(1000, 1, 500));
+        heaps.remove_sticks(0, Ok(500)).unwrap();
+        assert_eq!(
+            heaps.to_string(),
+            format!("500/1000[{}{}]", "#".repeat(20), " ".repeat(20))
+        );
+        heaps.remove_sticks(0, Ok(499)).unwrap();
+        assert_eq!(heaps.to_string(), format!("1/1000[#{}]", " ".repeat(39)));
+        assert_eq!(scale(0, 1000, 40), 0);
+        assert_eq!(scale(u64::MAX, u64::MAX, 40), 40);
+    }
+
+    #[test]
+    fn test_big_heap_winning_move() {
+        let sticks = StickNumber::new(u64::MAX, 1, 3);
+        assert_eq!(sticks.winning_move(&NORMAL), Some(3));
+        assert_eq!(sticks.winning_move(&MISERE), Some(2));
+        let sticks = StickNumber::new(u64::MAX, u64::MAX, u64::MAX);
+        assert_eq!(sticks.winning_move(&NORMAL), Some(u64::MAX));
+        assert_eq!(sticks.winning_move(&MISERE), None);
+        for (min_remove, max_remove) in [(7, 1 << 40), (1 << 33, 1 << 35), (1, u64::MAX / 3)] {
+            for rules in [NORMAL, MISERE] {
+                let sticks = StickNumber::new(u64::MAX - 12345, min_remove, max_remove);
+                if let Some(take) = sticks.winning_move(&rules) {
+                    let mut next = sticks.clone();
+                    next.remove_sticks(Ok(take)).unwrap();
+                    assert!(!next.is_winning(&rules), "{} {}", min_remove, max_remove);
+                } else {
+                    assert!(!sticks.is_winning(&rules));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_wythoff_big_heaps() {
+        for n in 0..1 << 20 {
+            assert_eq!(times_sqrt5(n), (5 * n * n).isqrt(), "{}", n);
+        }
+        // Consecutive Fibonacci numbers starting from an even index are cold positions
+        let (mut small, mut large) = (1u64, 2u64);
+        while let Some(next) = small
+            .checked_add(large)
+            .and_then(|next| next.checked_add(large))
+        {
+            (small, large) = (small + large, next);
+        }
+        assert!(!Heaps::wythoff(small, large).is_winning(&NORMAL));
+        assert!(!Heaps::wythoff(large, small).is_winning(&MISERE));
+        let heaps = Heaps::wythoff(small + 1, large + 1);
+        assert_eq!(heaps.winning_move(&NORMAL), Some((2, 1)));
+        let heaps = Heaps::wythoff(small, u64::MAX);
+        assert_eq!(heaps.winning_move(&NORMAL), Some((1, u64::MAX - large)));
+        let heaps = Heaps::wythoff(0, u64::MAX);
+        assert_eq!(heaps.winning_move(&NORMAL), Some((1, u64::MAX)));
+    }
+
+    #[test]
+    fn test_game_turns_and_winner() {
+        let names = vec![String::from("alice"), String::from("bob")];
+        let mut game = Game::new(Heaps::single(StickNumber::new(5, 1, 3)), NORMAL, names, 1);
+        assert_eq!(game.current_player(), 1);
+        assert_eq!(
+            game.apply_move(Move { heap: 0, amount: 3 }),
+            Ok(vec![
+                Event::MovePlayed {
+                    player: 1,
+                    heap: 0,
+                    amount: 3
+                },
+                Event::TurnStarted { player: 0 }
+            ])
+        );
+        assert_eq!(
+            game.apply_move(Move { heap: 0, amount: 4 }),
+            Err(StickNumberError::TakeOverMax)
+        );
+        assert_eq!(game.current_player(), 0);
+        assert_eq!(game.winner(), None);
+        assert_eq!(
+            game.apply_move(Move { heap: 0, amount: 2 }),
+            Ok(vec![
+                Event::MovePlayed {
+                    player: 0,
+                    heap: 0,
+                    amount: 2
+                },
+                Event::GameOver {
+                    winner: 0,
+                    turns: 2
+                }
+            ])
+        );
+        assert!(game.is_over());
+        assert_eq!(game.winner(), Some(0));
+        assert_eq!(
+            game.apply_move(Move { heap: 0, amount: 1 }),
+            Err(StickNumberError::GameOver)
+        );
+    }
+
+    #[test]
+    fn test_game_misere_winner() {
+        let names = vec![String::from("alice"), String::from("bob")];
+        let mut game = Game::new(Heaps::new(&[1, 2]), MISERE, names, 0);
+        game.apply_move(Move { heap: 1, amount: 2 }).unwrap();
+        let events = game.apply_move(Move { heap: 0, amount: 1 }).unwrap();
+        assert_eq!(game.winner(), Some(0));
+        assert_eq!(
+            events.last(),
+            Some(&Event::GameOver {
+                winner: 0,
+                turns: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_game_rotates_through_players() {
+        let names: Vec<String> = ["alice", "bob", "carol"].map(String::from).to_vec();
+        let mut game = Game::new(Heaps::single(StickNumber::new(7, 1, 3)), NORMAL, names, 2);
+        let mut order = Vec::new();
+        while !game.is_over() {
+            order.push(game.current_player());
+            game.apply_move(Move { heap: 0, amount: 1 }).unwrap();
+        }
+        assert_eq!(order, vec![2, 0, 1, 2, 0, 1, 2]);
+        assert_eq!(game.winner(), Some(2));
+    }
+
+    #[test]
+    fn test_game_with_teams() {
+        let names: Vec<String> = ["alice", "bob", "carol", "dave"].map(String::from).to_vec();
+        let game = Game::new(Heaps::new(&[1]), MISERE, names, 0).with_teams(vec![0, 1, 0, 1]);
+        assert!(game.has_teams());
+        assert_eq!(game.team_members(1), vec![1, 3]);
+
+        let mut lost = game.clone();
+        lost.apply_move(Move { heap: 0, amount: 1 }).unwrap();
+        assert_eq!(lost.winner(), Some(1));
+
+        let names: Vec<String> = ["alice", "bob"].map(String::from).to_vec();
+        let game = Game::new(Heaps::new(&[1]), MISERE, names, 0);
+        assert!(!game.has_teams());
+        assert_eq!(game.teams(), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_forfeit() {
+        let names: Vec<String> = ["alice", "bob", "carol"].map(String::from).to_vec();
+        let mut game = Game::new(Heaps::new(&[5]), NORMAL, names, 0).with_teams(vec![0, 0, 1]);
+        game.apply_move(Move { heap: 0, amount: 1 }).unwrap();
+        assert_eq!(
+            game.forfeit(),
+            vec![Event::GameOver {
+                winner: 2,
+                turns: 1
+            }]
+        );
+        assert!(game.is_over());
+        assert_eq!((game.forfeited(), game.winner()), (Some(1), Some(2)));
+        assert_eq!(game.forfeit(), vec![]);
+        assert_eq!(
+            game.apply_move(Move { heap: 0, amount: 1 }),
+            Err(StickNumberError::GameOver)
+        );
+        assert_eq!(game.initial().forfeited(), None);
+    }
+
+    #[test]
+    fn test_undo_and_redo() {
+        let names = vec![String::from("alice"), String::from("bob")];
+        let mut game = Game::new(Heaps::new(&[3, 4]), NORMAL, names, 0);
+        game.apply_move(Move { heap: 0, amount: 2 }).unwrap();
+        game.apply_move(Move { heap: 1, amount: 4 }).unwrap();
+        assert_eq!(
+            game.undo(),
+            Some(Turn {
+                player: 1,
+                heap: 1,
+                amount: 4
+            })
+        );
+        assert_eq!(game.current_player(), 1);
+        assert_eq!(game.heaps().to_string(), "1: 1/3[#  ]\n2: 4/4[####]");
+        assert!(game.can_redo());
+        assert!(game.redo().is_some());
+        assert_eq!(game.history().len(), 2);
+        assert_eq!(game.current_player(), 0);
+        assert!(game.redo().is_none());
+
+        // A new move forgets what was undone
+        game.undo();
+        game.apply_move(Move { heap: 1, amount: 1 }).unwrap();
+        assert!(!game.can_redo());
+    }
+
+    #[test]
+    fn test_initial_game() {
+        let names = vec![String::from("alice"), String::from("bob")];
+        let mut game = Game::new(Heaps::single(StickNumber::new(7, 1, 3)), MISERE, names, 1);
+        let start = game.clone();
+        game.apply_move(Move { heap: 0, amount: 3 }).unwrap();
+        game.apply_move(Move { heap: 0, amount: 1 }).unwrap();
+        assert_eq!(game.initial(), start);
+        assert_eq!(game.undo().map(|turn| turn.player), Some(0));
+        assert_eq!(game.undo().map(|turn| turn.player), Some(1));
+        assert_eq!(game.undo(), None);
+    }
+}
@@ -0,0 +1,396 @@
+/*
+Sprague–Grundy theory for impartial games under normal play: every position is
+worth a nim heap of its Grundy number, and a sum of positions is worth the XOR of
+the Grundy numbers of its parts, the player to move losing when it is 0.
+*/
+
+use crate::core::StickNumberError;
+use crate::messages::Message;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+
+/// Position of an impartial game, both players having the same moves
+pub trait ImpartialGame: Clone + Eq + Hash {
+    /// Positions reachable in one move, a move possibly splitting the position
+    /// into a sum of independent ones
+    fn options(&self) -> Vec<Vec<Self>>;
+}
+
+/// Smallest value missing from `values`
+fn mex(values: &[u32]) -> u32 {
+    (0..).find(|value| !values.contains(value)).unwrap_or(0)
+}
+
+/// Grundy numbers computed so far, shared by every position of a match
+pub struct GrundyCache<P: ImpartialGame> {
+    values: HashMap<P, u32>,
+}
+
+impl<P: ImpartialGame> GrundyCache<P> {
+    pub fn new() -> GrundyCache<P> {
+        GrundyCache {
+            values: HashMap::new(),
+        }
+    }
+
+    /// Grundy number of a position, the mex of the values of its options
+    pub fn grundy(&mut self, position: &P) -> u32 {
+        if let Some(&value) = self.values.get(position) {
+            return value;
+        }
+        let reachable: Vec<u32> = position
+            .options()
+            .iter()
+            .map(|option| self.sum_value(option))
+            .collect();
+        let value = mex(&reachable);
+        self.values.insert(position.clone(), value);
+        value
+    }
+
+    /// Grundy number of a sum of positions
+    pub fn sum_value(&mut self, positions: &[P]) -> u32 {
+        positions
+            .iter()
+            .fold(0, |value, position| value ^ self.grundy(position))
+    }
+}
+
+impl<P: ImpartialGame> Default for GrundyCache<P> {
+    fn default() -> Self {
+        GrundyCache::new()
+    }
+}
+
+/// The impartial games that can be mixed in a match
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub enum Position {
+    /// Heap where a move removes one of the allowed amounts
+    Subtraction { sticks: u8, moves: Vec<u8> },
+    /// Row of pins where a move knocks down one pin or two adjacent ones
+    Kayles(u8),
+    /// Heap where a move splits a heap into two unequal ones
+    Grundy(u8),
+}
+
+impl ImpartialGame for Position {
+    fn options(&self) -> Vec<Vec<Position>> {
+        match self {
+            Position::Subtraction { sticks, moves } => moves
+                .iter()
+                .filter(|&&take| take > 0 && take <= *sticks)
+                .map(|&take| {
+                    vec![Position::Subtraction {
+                        sticks: sticks - take,
+                        moves: moves.clone(),
+                    }]
+                })
+                .collect(),
+            Position::Kayles(pins) => {
+                let mut options = Vec::new();
+                for knocked in 1..=2 {
+                    let Some(left_over) = pins.checked_sub(knocked) else {
+                        continue;
+                    };
+                    // Knocking from either end gives the same rows
+                    for left in 0..=left_over / 2 {
+                        let rows = [left, left_over - left];
+                        options.push(
+                            rows.iter()
+                                .filter(|&&row| row > 0)
+                                .map(|&row| Position::Kayles(row))
+                                .collect(),
+                        );
+                    }
+                }
+                options
+            }
+            Position::Grundy(sticks) => (1..sticks.div_ceil(2))
+                .map(|part| vec![Position::Grundy(part), Position::Grundy(sticks - part)])
+                .collect(),
+        }
+    }
+}
+
+impl std::str::FromStr for Position {
+    type Err = String;
+
+    /// Read `kayles:7`, `grundy:9` or `subtraction:10:1/3/4`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.trim().split(':').collect();
+        let size = |text: &str| {
+            text.trim()
+                .parse::<u8>()
+                .map_err(|_| format!("Invalid size in {}", s.trim()))
+        };
+        match parts[..] {
+            [game, pins] if game.eq_ignore_ascii_case("kayles") => {
+                Ok(Position::Kayles(size(pins)?))
+            }
+            [game, sticks] if game.eq_ignore_ascii_case("grundy") => {
+                Ok(Position::Grundy(size(sticks)?))
+            }
+            [game, sticks, moves] if game.eq_ignore_ascii_case("subtraction") => {
+                let moves: Vec<u8> = moves.split('/').map(size).collect::<Result<_, _>>()?;
+                if moves.is_empty() || moves.contains(&0) {
+                    return Err(format!("Invalid moves in {}", s.trim()));
+                }
+                Ok(Position::Subtraction {
+                    sticks: size(sticks)?,
+                    moves,
+                })
+            }
+            _ => Err(format!(
+                "Unknown game: {} (expected kayles:N, grundy:N or subtraction:N:A/B/...)",
+                s.trim()
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Position::Subtraction { sticks, moves } => {
+                let moves: Vec<String> = moves.iter().map(|take| take.to_string()).collect();
+                let taking = Message::SubtractionHeap {
+                    sticks: *sticks,
+                    moves: &moves.join("/"),
+                };
+                write!(f, "{} [{}]", taking, "|".repeat(usize::from(*sticks)))
+            }
+            Position::Kayles(pins) => write!(
+                f,
+                "{} [{}]",
+                Message::KaylesRow(*pins),
+                "i".repeat(usize::from(*pins))
+            ),
+            Position::Grundy(sticks) => write!(
+                f,
+                "{} [{}]",
+                Message::GrundyHeap(*sticks),
+                "#".repeat(usize::from(*sticks))
+            ),
+        }
+    }
+}
+
+/// Text of a move's outcome, the positions it leaves in place of the one played
+pub fn describe_option<P: fmt::Display>(option: &[P]) -> String {
+    if option.is_empty() {
+        return Message::NothingLeft.to_string();
+    }
+    let parts: Vec<String> = option.iter().map(|position| position.to_string()).collect();
+    parts.join(" + ")
+}
+
+/// Several positions played side by side, a move being made in one of them
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct GameSum<P: ImpartialGame> {
+    components: Vec<P>,
+}
+
+impl<P: ImpartialGame> GameSum<P> {
+    pub fn new(components: Vec<P>) -> GameSum<P> {
+        GameSum { components }
+    }
+
+    pub fn components(&self) -> &[P] {
+        &self.components
+    }
+
+    /// Whether the player to move has no move left, and lost under normal play
+    pub fn is_over(&self) -> bool {
+        self.components
+            .iter()
+            .all(|component| component.options().is_empty())
+    }
+
+    /// Replace a component by the positions of one of its options
+    pub fn play(&mut self, component: usize, option: usize) -> Result<(), StickNumberError> {
+        let options = self
+            .components
+            .get(component)
+            .ok_or(StickNumberError::HeapOutOfRange)?
+            .options();
+        let next = options
+            .get(option)
+            .ok_or(StickNumberError::DisallowedAmount)?;
+        self.components
+            .splice(component..=component, next.iter().cloned());
+        Ok(())
+    }
+
+    pub fn grundy_value(&self, cache: &mut GrundyCache<P>) -> u32 {
+        cache.sum_value(&self.components)
+    }
+
+    /// Component and option bringing the Grundy value of the sum to 0, if any
+    pub fn winning_move(&self, cache: &mut GrundyCache<P>) -> Option<(usize, usize)> {
+        let total = self.grundy_value(cache);
+        if total == 0 {
+            return None;
+        }
+        self.components
+            .iter()
+            .enumerate()
+            .find_map(|(index, component)| {
+                let target = total ^ cache.grundy(component);
+                component
+                    .options()
+                    .iter()
+                    .position(|option| cache.sum_value(option) == target)
+                    .map(|option| (index, option))
+            })
+    }
+}
+
+impl<P: ImpartialGame + fmt::Display> fmt::Display for GameSum<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lines: Vec<String> = self
+            .components
+            .iter()
+            .enumerate()
+            .map(|(index, component)| format!("{}: {}", index + 1, component))
+            .collect();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messages;
+
+    /// Plain nim heap, whose Grundy number is its size
+    #[derive(PartialEq, Eq, Hash, Clone, Debug)]
+    struct NimHeap(u8);
+
+    impl ImpartialGame for NimHeap {
+        fn options(&self) -> Vec<Vec<NimHeap>> {
+            (0..self.0).map(|size| vec![NimHeap(size)]).collect()
+        }
+    }
+
+    /// Whether the player to move wins the sum, searching every line of play
+    fn brute_force_is_winning(positions: &[Position]) -> bool {
+        positions.iter().enumerate().any(|(index, position)| {
+            position.options().iter().any(|option| {
+                let mut next = positions.to_vec();
+                next.splice(index..=index, option.iter().cloned());
+                !brute_force_is_winning(&next)
+            })
+        })
+    }
+
+    #[test]
+    fn test_nim_heap_grundy_is_its_size() {
+        let mut cache = GrundyCache::new();
+        for size in 0..20 {
+            assert_eq!(cache.grundy(&NimHeap(size)), u32::from(size));
+        }
+        assert_eq!(cache.sum_value(&[NimHeap(3), NimHeap(4), NimHeap(5)]), 2);
+    }
+
+    #[test]
+    fn test_known_grundy_sequences() {
+        let mut cache = GrundyCache::new();
+        let kayles: Vec<u32> = (0..12)
+            .map(|pins| cache.grundy(&Position::Kayles(pins)))
+            .collect();
+        assert_eq!(kayles, vec![0, 1, 2, 3, 1, 4, 3, 2, 1, 4, 2, 6]);
+        let grundy: Vec<u32> = (1..14)
+            .map(|size| cache.grundy(&Position::Grundy(size)))
+            .collect();
+        assert_eq!(grundy, vec![0, 0, 1, 0, 2, 1, 0, 2, 1, 0, 2, 1, 3]);
+        let subtraction: Vec<u32> = (0..14)
+            .map(|sticks| {
+                cache.grundy(&Position::Subtraction {
+                    sticks,
+                    moves: vec![1, 3, 4],
+                })
+            })
+            .collect();
+        assert_eq!(subtraction, vec![0, 1, 0, 1, 2, 3, 2, 0, 1, 0, 1, 2, 3, 2]);
+    }
+
+    #[test]
+    fn test_sum_solver_matches_brute_force() {
+        let mut cache = GrundyCache::new();
+        for pins in 0..5 {
+            for size in 1..7 {
+                for sticks in 0..6 {
+                    let sum = GameSum::new(vec![
+                        Position::Kayles(pins),
+                        Position::Grundy(size),
+                        Position::Subtraction {
+                            sticks,
+                            moves: vec![1, 3],
+                        },
+                    ]);
+                    let winning = brute_force_is_winning(sum.components());
+                    assert_eq!(sum.grundy_value(&mut cache) != 0, winning);
+                    match sum.winning_move(&mut cache) {
+                        Some((component, option)) => {
+                            let mut next = sum.clone();
+                            next.play(component, option).unwrap();
+                            assert_eq!(next.grundy_value(&mut cache), 0);
+                        }
+                        None => assert!(!winning),
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_play_replaces_component() {
+        let mut sum = GameSum::new(vec![Position::Grundy(7), Position::Kayles(3)]);
+        // Splitting 7 into 2 and 5
+        sum.play(0, 1).unwrap();
+        assert_eq!(
+            sum.components(),
+            &[
+                Position::Grundy(2),
+                Position::Grundy(5),
+                Position::Kayles(3)
+            ]
+        );
+        assert_eq!(sum.play(3, 0), Err(StickNumberError::HeapOutOfRange));
+        assert_eq!(sum.play(0, 0), Err(StickNumberError::DisallowedAmount));
+        assert!(!sum.is_over());
+        assert!(GameSum::new(vec![Position::Grundy(2), Position::Kayles(0)]).is_over());
+    }
+
+    #[test]
+    fn test_parse_and_display_positions() {
+        assert_eq!("kayles:7".parse::<Position>(), Ok(Position::Kayles(7)));
+        assert_eq!(" Grundy:9 ".parse::<Position>(), Ok(Position::Grundy(9)));
+        assert_eq!(
+            "subtraction:10:1/3/4".parse::<Position>(),
+            Ok(Position::Subtraction {
+                sticks: 10,
+                moves: vec![1, 3, 4]
+            })
+        );
+        assert!("subtraction:10:0/3".parse::<Position>().is_err());
+        assert!("chess:3".parse::<Position>().is_err());
+        assert!("kayles:many".parse::<Position>().is_err());
+
+        assert_eq!(Position::Kayles(3).to_string(), "Kayles row of 3 [iii]");
+        assert_eq!(
+            describe_option(&[Position::Grundy(1), Position::Grundy(2)]),
+            "Grundy heap of 1 [#] + Grundy heap of 2 [##]"
+        );
+        assert_eq!(describe_option::<Position>(&[]), "nothing");
+
+        messages::set_locale(messages::Locale {
+            language: messages::Language::French,
+            ascii: false,
+        });
+        assert_eq!(Position::Grundy(2).to_string(), "Tas de Grundy de 2 [##]");
+        assert_eq!(describe_option::<Position>(&[]), "rien");
+        messages::set_locale(messages::Locale::default());
+    }
+}
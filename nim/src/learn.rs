@@ -0,0 +1,515 @@
+/*
+Strategy learned by self-play, a reinforcement learning demo on the core rules.
+
+Tabular Q-learning: the value of each move in each position is learned by playing
+games against itself, now and then exploring a random move instead of the best
+known one. Values are seen from the player making the move, 1 for a win and -1 for
+a loss, so the value of a position for the player moving next is the opposite of
+its best move. This assumes two players taking turns.
+
+The table is saved as JSON along with the heaps and rules it was trained on, and
+only plays games with the same rules and moves. Every legal move of a position is
+listed, so starting positions with more than `MAX_MOVES` moves cannot be trained on.
+*/
+
+use crate::analyze::{self, Analysis};
+use crate::cli;
+use crate::core::{Convention, Game, Heaps, Move, Rules};
+use crate::grundy::{GameSum, GrundyCache, Position};
+use crate::messages::{Glyph, Message};
+use crate::player::{self, Player, PlayerKind};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::{fmt, fs};
+
+/// Table used by learned players when none is given, in the home directory
+const TABLE_FILE: &str = ".nim_table.json";
+/// Self-play games of a training when not given
+pub const DEFAULT_EPISODES: u32 = 20_000;
+/// Share of the error corrected by each update
+const LEARNING_RATE: f64 = 0.5;
+/// Probability of exploring a random move during self-play
+const EXPLORATION: f64 = 0.2;
+/// Legal moves of a position valued at most
+pub const MAX_MOVES: u64 = 1000;
+/// Games played against a random player to evaluate a table
+const EVALUATION_GAMES: u32 = 200;
+
+pub fn default_path() -> PathBuf {
+    std::env::var_os("HOME")
+        .map(PathBuf::from)
+        .unwrap_or_default()
+        .join(TABLE_FILE)
+}
+
+#[derive(Debug)]
+pub enum LearnError {
+    Io(io::Error),
+    Format(serde_json::Error),
+}
+
+impl fmt::Display for LearnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LearnError::Io(e) => write!(f, "{}", Message::TableAccess(e)),
+            LearnError::Format(e) => write!(f, "{}", Message::TableFormat(e)),
+        }
+    }
+}
+
+impl From<io::Error> for LearnError {
+    fn from(e: io::Error) -> Self {
+        LearnError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for LearnError {
+    fn from(e: serde_json::Error) -> Self {
+        LearnError::Format(e)
+    }
+}
+
+/// Value of a move in a position, one line of the saved table
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+struct Entry {
+    sizes: Vec<u64>,
+    heap: usize,
+    amount: u64,
+    value: f64,
+}
+
+/// Content of a table file
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+struct SavedTable {
+    heaps: Heaps,
+    rules: Rules,
+    episodes: u32,
+    entries: Vec<Entry>,
+}
+
+/// Learned value of the moves of every position met during training
+#[derive(PartialEq, Debug, Clone)]
+pub struct QTable {
+    heaps: Heaps,
+    rules: Rules,
+    episodes: u32,
+    values: HashMap<(Vec<u64>, usize, u64), f64>,
+}
+
+impl QTable {
+    /// An empty table for games starting from `heaps`
+    pub fn new(heaps: &Heaps, rules: Rules) -> QTable {
+        QTable {
+            heaps: heaps.clone(),
+            rules,
+            episodes: 0,
+            values: HashMap::new(),
+        }
+    }
+
+    pub fn episodes(&self) -> u32 {
+        self.episodes
+    }
+
+    /// Number of positions with at least one move valued
+    pub fn positions(&self) -> usize {
+        let mut positions: Vec<&Vec<u64>> = self.values.keys().map(|(sizes, _, _)| sizes).collect();
+        positions.sort();
+        positions.dedup();
+        positions.len()
+    }
+
+    /// Value of a move for the player making it, 0 when never tried
+    pub fn value(&self, heaps: &Heaps, (heap, amount): (usize, u64)) -> f64 {
+        self.values
+            .get(&(heaps.sizes(), heap, amount))
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    /// Legal moves of the highest value, several when they are tied, none when
+    /// there are too many to list
+    pub fn best_moves(&self, heaps: &Heaps) -> Vec<(usize, u64)> {
+        if heaps.count_moves() > MAX_MOVES {
+            return Vec::new();
+        }
+        let moves = heaps.legal_moves();
+        let best = moves
+            .iter()
+            .map(|&mv| self.value(heaps, mv))
+            .fold(f64::NEG_INFINITY, f64::max);
+        moves
+            .into_iter()
+            .filter(|&mv| self.value(heaps, mv) == best)
+            .collect()
+    }
+
+    /// Value of a position for the player to move, their best move's
+    fn position_value(&self, heaps: &Heaps) -> f64 {
+        if heaps.count_moves() > MAX_MOVES {
+            return 0.0;
+        }
+        heaps
+            .legal_moves()
+            .into_iter()
+            .map(|mv| self.value(heaps, mv))
+            .fold(f64::NEG_INFINITY, f64::max)
+    }
+
+    /// Play `episodes` games against itself from the starting heaps, learning
+    /// from every move, nothing being learned above `MAX_MOVES`
+    pub fn train<G: Rng>(&mut self, rng: &mut G, episodes: u32) {
+        // Moves only get fewer as the heaps shrink
+        if self.heaps.count_moves() > MAX_MOVES {
+            return;
+        }
+        // Taking the last stick wins or loses, whoever takes it
+        let reward = if self.rules.winner(0, &[0, 1]) == 0 {
+            1.0
+        } else {
+            -1.0
+        };
+        for _ in 0..episodes {
+            let mut heaps = self.heaps.clone();
+            while !heaps.is_over() {
+                let mv = if rng.gen_bool(EXPLORATION) {
+                    heaps.legal_moves().choose(rng).copied()
+                } else {
+                    self.best_moves(&heaps).choose(rng).copied()
+                };
+                let Some((heap, amount)) = mv else {
+                    break;
+                };
+                let mut next = heaps.clone();
+                if next.remove_sticks(heap, Ok(amount)).is_err() {
+                    break;
+                }
+                let target = if next.is_over() {
+                    reward
+                } else {
+                    -self.position_value(&next)
+                };
+                let value = self
+                    .values
+                    .entry((heaps.sizes(), heap, amount))
+                    .or_insert(0.0);
+                *value += LEARNING_RATE * (target - *value);
+                heaps = next;
+            }
+        }
+        self.episodes += episodes;
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), LearnError> {
+        let mut entries: Vec<Entry> = self
+            .values
+            .iter()
+            .map(|((sizes, heap, amount), &value)| Entry {
+                sizes: sizes.clone(),
+                heap: *heap,
+                amount: *amount,
+                value,
+            })
+            .collect();
+        entries.sort_by(|a, b| (&a.sizes, a.heap, a.amount).cmp(&(&b.sizes, b.heap, b.amount)));
+        let saved = SavedTable {
+            heaps: self.heaps.clone(),
+            rules: self.rules,
+            episodes: self.episodes,
+            entries,
+        };
+        fs::write(path, serde_json::to_string_pretty(&saved)?)?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<QTable, LearnError> {
+        let saved: SavedTable = serde_json::from_str(&fs::read_to_string(path)?)?;
+        Ok(QTable {
+            heaps: saved.heaps,
+            rules: saved.rules,
+            episodes: saved.episodes,
+            values: saved
+                .entries
+                .into_iter()
+                .map(|entry| ((entry.sizes, entry.heap, entry.amount), entry.value))
+                .collect(),
+        })
+    }
+}
+
+/// Table file asked for by a player spec, `learned` or `learned:<file>`
+pub fn parse_spec(spec: &str) -> Option<PathBuf> {
+    let spec = spec.trim();
+    match spec.split_once(':') {
+        None if spec.eq_ignore_ascii_case("learned") => Some(default_path()),
+        Some((kind, path)) if kind.eq_ignore_ascii_case("learned") && !path.is_empty() => {
+            Some(PathBuf::from(path))
+        }
+        _ => None,
+    }
+}
+
+/// Plays the best move of its table, positions it never met being played randomly
+pub struct LearnedPlayer {
+    table: QTable,
+    path: PathBuf,
+    rng: StdRng,
+}
+
+impl LearnedPlayer {
+    pub fn load(path: &Path) -> Result<LearnedPlayer, LearnError> {
+        Ok(LearnedPlayer::with_seed(
+            QTable::load(path)?,
+            path,
+            rand::random(),
+        ))
+    }
+
+    pub fn with_seed(table: QTable, path: &Path, seed: u64) -> LearnedPlayer {
+        LearnedPlayer {
+            table,
+            path: path.to_path_buf(),
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Player for LearnedPlayer {
+    fn name(&self) -> &str {
+        "Computer (learned)"
+    }
+
+    fn kind(&self) -> PlayerKind {
+        PlayerKind::Learned(self.path.clone())
+    }
+
+    fn choose_move(&mut self, game: &Game) -> Option<Move> {
+        let heaps = game.heaps();
+        let (heap, amount) = match self.table.best_moves(heaps).choose(&mut self.rng) {
+            Some(&mv) => mv,
+            None => player::random_move(&mut self.rng, heaps),
+        };
+        Some(Move { heap, amount })
+    }
+
+    /// The table knows nothing of sums of games, their moves are random
+    fn choose_sum_move(
+        &mut self,
+        sum: &GameSum<Position>,
+        _: &mut GrundyCache<Position>,
+    ) -> Option<(usize, usize)> {
+        Some(player::random_sum_move(&mut self.rng, sum))
+    }
+
+    /// Values only make sense under the rules and moves of the training
+    fn check_game(&self, game: &Game) -> Result<(), String> {
+        if self.table.rules == *game.rules() && self.table.heaps.same_moves(game.heaps()) {
+            Ok(())
+        } else {
+            Err(Message::TableMismatch(&self.path.display()).to_string())
+        }
+    }
+}
+
+/// Share of the winning positions where a best move of the table wins, with the
+/// number of winning positions, `None` when there are too many to solve
+pub fn solver_agreement(table: &QTable) -> Option<(f64, usize)> {
+    let analysis = Analysis::new(&table.heaps, table.rules).ok()?;
+    let winning: Vec<_> = analysis
+        .solutions()
+        .filter(|(_, solution)| solution.winning)
+        .collect();
+    let right = winning
+        .iter()
+        .filter(|(heaps, solution)| {
+            let best = table.best_moves(heaps);
+            !best.is_empty() && best.iter().all(|mv| solution.winning_moves.contains(mv))
+        })
+        .count();
+    Some((right as f64 / winning.len().max(1) as f64, winning.len()))
+}
+
+/// Games won by the table's player against a random one, taking turns at moving first
+pub fn wins_against_random(table: &QTable, games: u32, seed: u64) -> u32 {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut learned = LearnedPlayer::with_seed(table.clone(), Path::new(""), seed);
+    let names = vec![String::from("learned"), String::from("random")];
+    let mut wins = 0;
+    for round in 0..games {
+        let first = round as usize % 2;
+        let mut game = Game::new(table.heaps.clone(), table.rules, names.clone(), first);
+        while !game.is_over() {
+            let mv = match game.current_player() {
+                0 => learned.choose_move(&game),
+                _ => {
+                    let (heap, amount) = player::random_move(&mut rng, game.heaps());
+                    Some(Move { heap, amount })
+                }
+            };
+            if mv.is_none_or(|mv| game.apply_move(mv).is_err()) {
+                break;
+            }
+        }
+        wins += u32::from(game.winner() == Some(0));
+    }
+    wins
+}
+
+/// Train a table on the position given by `--sticks`, save it and report how well
+/// it plays
+pub fn train<W: Write>(config: &cli::Config, output: &mut W) -> io::Result<()> {
+    let (episodes, seed, path) = match &config.mode {
+        cli::Mode::Train {
+            episodes,
+            seed,
+            table,
+        } => (*episodes, *seed, table.clone().unwrap_or_else(default_path)),
+        _ => return Ok(()),
+    };
+    let invalid = |e: String| io::Error::new(io::ErrorKind::InvalidInput, e);
+    let sizes = config
+        .sticks
+        .as_deref()
+        .ok_or_else(|| invalid(Message::PositionNeeded.to_string()))?;
+    let heaps = config.heaps(sizes).map_err(invalid)?;
+    if heaps.count_moves() > MAX_MOVES {
+        return Err(invalid(Message::TooManyMoves(MAX_MOVES).to_string()));
+    }
+    let rules = Rules::new(config.convention.unwrap_or(Convention::Normal));
+    let training = Message::TrainingOn {
+        position: &analyze::describe_sizes(&heaps),
+        convention: &rules.convention,
+        episodes,
+        seed,
+    };
+    writeln!(
+        output,
+        "{}  | {}\n{}",
+        Glyph::Title,
+        training,
+        "-".repeat(80)
+    )?;
+
+    let mut table = QTable::new(&heaps, rules);
+    table.train(&mut StdRng::seed_from_u64(seed), episodes);
+    table
+        .save(&path)
+        .map_err(|e| io::Error::other(e.to_string()))?;
+    let learned = Message::PositionsLearned {
+        positions: table.positions(),
+        episodes: table.episodes(),
+        path: &path.display(),
+    };
+    writeln!(output, "  | {}", learned)?;
+    match solver_agreement(&table) {
+        Some((share, positions)) => writeln!(
+            output,
+            "  | {}",
+            Message::SolverAgreement { share, positions }
+        )?,
+        None => writeln!(output, "  | {}", Message::TooManyToCompare)?,
+    }
+    let won = Message::WonAgainstRandom {
+        wins: wins_against_random(&table, EVALUATION_GAMES, seed),
+        games: EVALUATION_GAMES,
+    };
+    writeln!(output, "  | {}", won)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::StickNumber;
+    use crate::messages;
+
+    const NORMAL: Rules = Rules {
+        convention: Convention::Normal,
+    };
+    const MISERE: Rules = Rules {
+        convention: Convention::Misere,
+    };
+
+    #[test]
+    fn test_learns_subtraction_games() {
+        for (rules, heaps) in [
+            (NORMAL, Heaps::single(StickNumber::new(21, 1, 3))),
+            (
+                MISERE,
+                Heaps::single(StickNumber::with_moves(20, &[1, 3, 4])),
+            ),
+        ] {
+            let mut table = QTable::new(&heaps, rules);
+            table.train(&mut StdRng::seed_from_u64(0), 3000);
+            assert_eq!(table.episodes(), 3000);
+            let (share, _) = solver_agreement(&table).unwrap();
+            assert!(share >= 0.9, "{:?}: {}", heaps, share);
+            assert!(wins_against_random(&table, 50, 1) >= 45);
+        }
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let path = std::env::temp_dir().join(format!("nim_table_{}.json", std::process::id()));
+        let heaps = Heaps::new(&[2, 3]);
+        let mut table = QTable::new(&heaps, MISERE);
+        table.train(&mut StdRng::seed_from_u64(4), 200);
+        table.save(&path).unwrap();
+        // Values may differ in their last digit once written as text
+        let loaded = QTable::load(&path).unwrap();
+        assert_eq!(
+            (loaded.episodes(), loaded.positions()),
+            (200, table.positions())
+        );
+        for (key, value) in table.values.iter() {
+            assert!((loaded.values[key] - value).abs() < 1e-12);
+        }
+
+        let player = LearnedPlayer::load(&path).unwrap();
+        assert_eq!(player.kind(), PlayerKind::Learned(path.clone()));
+        fs::write(&path, "{}").unwrap();
+        assert!(matches!(QTable::load(&path), Err(LearnError::Format(_))));
+        messages::set_locale(messages::Locale {
+            language: messages::Language::French,
+            ascii: false,
+        });
+        let error = QTable::load(&path).unwrap_err().to_string();
+        messages::set_locale(messages::Locale::default());
+        assert!(error.starts_with("Le fichier de la table n'est pas une table valide : "));
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(LearnedPlayer::load(&path), Err(LearnError::Io(_))));
+
+        assert_eq!(parse_spec("Learned"), Some(default_path()));
+        assert_eq!(
+            parse_spec("learned:Tables/21.json"),
+            Some(PathBuf::from("Tables/21.json"))
+        );
+        assert_eq!(parse_spec("learned:"), None);
+        assert_eq!(parse_spec("mcts"), None);
+    }
+
+    #[test]
+    fn test_other_games_refused() {
+        let table = QTable::new(&Heaps::single(StickNumber::new(9, 1, 3)), NORMAL);
+        let player = LearnedPlayer::with_seed(table, Path::new("t.json"), 0);
+        let names = vec![String::from("one"), String::from("two")];
+        let game = |heaps, rules| Game::new(heaps, rules, names.clone(), 0);
+        let bigger = game(Heaps::single(StickNumber::new(30, 1, 3)), NORMAL);
+        assert!(player.check_game(&bigger).is_ok());
+        let misere = game(Heaps::single(StickNumber::new(9, 1, 3)), MISERE);
+        assert!(player.check_game(&misere).is_err());
+        let other_moves = game(Heaps::single(StickNumber::new(9, 1, 4)), NORMAL);
+        assert!(player.check_game(&other_moves).is_err());
+        let more_heaps = game(Heaps::new(&[9, 9]), NORMAL);
+        assert!(player.check_game(&more_heaps).is_err());
+
+        // Too many moves to list, nothing is learned or chosen from the table
+        let heaps = Heaps::new(&[u64::MAX, 5]);
+        let mut table = QTable::new(&heaps, NORMAL);
+        table.train(&mut StdRng::seed_from_u64(0), 10);
+        assert_eq!(table.positions(), 0);
+        assert!(table.best_moves(&heaps).is_empty());
+    }
+}
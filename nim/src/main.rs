@@ -4,177 +4,135 @@ Author: Ammar Mian
 Date: 27/02/24
 */
 
-use std::io::{self, Write};
+use messages::{Glyph, Message};
+use std::io;
+use std::net::{TcpListener, TcpStream};
+use std::process;
+use terminal::InputError;
 
-mod core {
-    use std::{fmt, num::ParseIntError};
+mod analyze;
 
-    #[derive(PartialEq, Debug)]
-    pub struct StickNumber {
-        remaining: u8,
-        total: u8,
-        min_remove: u8,
-        max_remove: u8,
-    }
+mod cli;
 
-    #[derive(PartialEq, Debug)]
-    pub enum StickNumberError {
-        RemainingNegative,
-        TakeOverMax,
-        TakeUnderMin,
-        ParsingError,
-    }
+mod clock;
 
-    impl StickNumber {
-        pub fn new(total: u8, min_remove: u8, max_remove: u8) -> StickNumber {
-            let remaining = total;
-            StickNumber {
-                remaining,
-                total,
-                min_remove,
-                max_remove,
-            }
-        }
+mod coach;
 
-        pub fn get_remaining(&self) -> u8 {
-            self.remaining
-        }
+mod core;
 
-        fn verify_remove_value(
-            &self,
-            value: Result<u8, ParseIntError>,
-        ) -> Result<u8, StickNumberError> {
-            match value {
-                Ok(val) => {
-                    if val > self.max_remove {
-                        Err(StickNumberError::TakeOverMax)
-                    } else if val < self.min_remove {
-                        Err(StickNumberError::TakeUnderMin)
-                    } else if val > self.remaining {
-                        Err(StickNumberError::RemainingNegative)
-                    } else {
-                        Ok(val)
-                    }
-                }
-                Err(_) => Err(StickNumberError::ParsingError),
-            }
-        }
+mod grundy;
 
-        pub fn remove_sticks(
-            &mut self,
-            value: Result<u8, ParseIntError>,
-        ) -> Result<u8, StickNumberError> {
-            let val = self.verify_remove_value(value)?;
-            self.remaining -= val;
-            Ok(self.remaining)
-        }
-    }
+mod learn;
 
-    impl fmt::Display for StickNumber {
-        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-            let hashtags_str = "#".repeat(self.remaining.into());
-            let space_str = " ".repeat((self.total - self.remaining).into());
-            write!(
-                f,
-                "{}/{}[{}{}]",
-                self.remaining, self.total, hashtags_str, space_str
-            )
-        }
-    }
+mod mcts;
 
-    impl fmt::Display for StickNumberError {
-        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-            match self {
-                StickNumberError::RemainingNegative => {
-                    write!(f, "Taking that many sticks will lead to a negative number")
-                }
-                StickNumberError::TakeOverMax => {
-                    write!(f, "Trying to take over the maximum limit")
-                }
-                StickNumberError::TakeUnderMin => {
-                    write!(f, "Trying to take under the minimum limit")
-                }
-                StickNumberError::ParsingError => {
-                    write!(f, "Entered value is not compatible with u8")
-                }
-            }
-        }
-    }
-}
+mod messages;
 
-fn read_number_from_terminal<T: std::str::FromStr>() -> Result<T, T::Err> {
-    let mut input_string = String::new();
-    io::stdout().flush().unwrap();
-    io::stdin()
-        .read_line(&mut input_string)
-        .expect("Failed to read line");
-    let number = input_string.trim().parse::<T>()?;
-    Ok(number)
-}
+mod net;
 
-fn read_string_from_terminal() -> String {
-    let mut input_string = String::new();
-    io::stdout().flush().unwrap();
-    io::stdin()
-        .read_line(&mut input_string)
-        .expect("Failed to read line");
-    String::from(input_string.trim_end_matches('\n'))
-}
+mod player;
 
-fn main() {
-    println!("󰊖  | Welcome to Nim Game!");
-    println!("{}", "-".repeat(80));
+mod profile;
 
-    let mut player_names = vec![String::new(), String::new()];
-    for (i, name) in player_names.iter_mut().enumerate() {
-        println!("  | Please enter the name of player {}: ", i + 1);
-        *name = read_string_from_terminal();
-    }
+mod save;
+
+mod server;
+
+mod terminal;
+
+mod tournament;
 
-    let mut total = 0;
-    println!("  | Please enter a number of total sticks:");
-    while total == 0 {
-        match read_number_from_terminal::<u8>() {
-            Ok(val) => {
-                total = val;
-            }
-            Err(e) => {
-                println!("  | Error: {}", e);
-                println!("  | Please enter a valid number of total sticks:");
-            }
-        };
+mod transcript;
+
+mod tui;
+
+/// Keep the result of a finished game in the player profiles, and its transcript
+/// when asked for one
+fn record(config: &cli::Config, game: &core::Game) {
+    let path = config
+        .profiles
+        .clone()
+        .unwrap_or_else(profile::default_path);
+    if let Err(e) = profile::record_game(&path, game) {
+        eprintln!("{}  | {}", Glyph::Error, Message::Error(&e));
     }
-    println!("{}", "-".repeat(80));
-
-    let mut sticks = core::StickNumber::new(total, 1, 3);
-    println!("  | Remaining sticks:");
-    println!("{}", sticks);
-
-    let mut curr_player = 0;
-    let mut number_turns = 0;
-    while sticks.get_remaining() > 0 {
-        println!(
-            " | Player {} - {}: ",
-            curr_player + 1,
-            player_names[curr_player]
-        );
-
-        println!("  | How many to remove (min={}, max={})", 1, 3);
-        let remaining_sticks = read_number_from_terminal::<u8>();
-        match sticks.remove_sticks(remaining_sticks) {
-            Ok(_) => {
-                print!("  | ");
-                println!("{}\n", sticks);
-                curr_player = (curr_player + 1) % 2;
-                number_turns += 1;
-                println!("{}", "-".repeat(50));
-            }
-            Err(e) => println!("  | Error: {}", e),
-        };
+    if let Some(path) = &config.transcript {
+        match transcript::save(path, game) {
+            Ok(()) => println!("  | {}", Message::TranscriptWritten(&path.display())),
+            Err(e) => eprintln!(
+                "{}  | {}",
+                Glyph::Error,
+                Message::Error(&Message::TranscriptFailed(&e))
+            ),
+        }
     }
+}
 
-    println!(
-        "✌️  | Game won by {} in {} turns",
-        player_names[curr_player], number_turns
-    );
+fn main() {
+    let config = cli::parse();
+
+    // A clock reads stdin on its own thread, which must not find it locked here
+    let input: Box<dyn io::BufRead> = match config.time_control {
+        Some(_) => Box::new(io::empty()),
+        None => Box::new(io::stdin().lock()),
+    };
+    let mut terminal = terminal::Terminal::new(input, io::stdout())
+        .with_coach(config.coach)
+        .with_time_control(config.time_control);
+    if config.time_control.is_some() {
+        terminal = terminal.with_input_thread(io::BufReader::new(io::stdin()));
+    }
+    let result = match &config.mode {
+        cli::Mode::Local if config.games.is_some() => terminal.play_sum(&config),
+        cli::Mode::Local if config.tui => {
+            terminal.setup(&config).and_then(|(mut game, mut players)| {
+                tui::play(&mut game, &mut players)?;
+                // Leaving with q or Esc ends the screen before the game
+                if game.is_over() {
+                    record(&config, &game);
+                }
+                Ok(())
+            })
+        }
+        cli::Mode::Local => terminal.setup(&config).and_then(|(mut game, mut players)| {
+            terminal.play(&mut game, &mut players)?;
+            record(&config, &game);
+            Ok(())
+        }),
+        cli::Mode::Host { port } => TcpListener::bind(("0.0.0.0", *port))
+            .map_err(InputError::from)
+            .and_then(|listener| net::host(&mut terminal, &config, listener))
+            .map(|game| record(&config, &game)),
+        cli::Mode::Join { address } => TcpStream::connect(address.as_str())
+            .map_err(InputError::from)
+            .and_then(|stream| net::join(&mut terminal, stream)),
+        cli::Mode::Tournament { .. } => {
+            tournament::play(&config, &mut io::stdout()).map_err(InputError::from)
+        }
+        cli::Mode::Analyze => {
+            analyze::show_analysis(&config, &mut io::stdout()).map_err(InputError::from)
+        }
+        cli::Mode::Train { .. } => {
+            learn::train(&config, &mut io::stdout()).map_err(InputError::from)
+        }
+        cli::Mode::Replay { path } => transcript::load(path)
+            .map_err(|e| InputError::Invalid(e.to_string()))
+            .and_then(|game| terminal.replay(&game)),
+        cli::Mode::Serve { address, port } => TcpListener::bind((*address, *port))
+            .and_then(|listener| server::serve(&config, listener, &mut io::stdout()))
+            .map_err(InputError::from),
+        cli::Mode::Stats { .. } => {
+            profile::show_stats(&config, &mut io::stdout()).map_err(InputError::from)
+        }
+    };
+    match result {
+        Ok(()) => {}
+        // Leaving is not an error, so that scripts piping moves in can stop anywhere
+        Err(InputError::Quit) => println!("  | {}", Message::Goodbye),
+        Err(InputError::Closed) => println!("\n  | {}", Message::InputClosed),
+        Err(e) => {
+            eprintln!("{}  | {}", Glyph::Error, Message::Error(&e));
+            process::exit(1);
+        }
+    }
 }
@@ -0,0 +1,403 @@
+/*
+Monte Carlo tree search player, for variants without a closed-form strategy.
+
+Each iteration walks down the moves already explored, picking them by their upper
+confidence bound, adds one unexplored move, then plays random moves to the end of
+the game and credits the result to every position on the way. Positions are shared
+by every order of moves reaching them. Outcomes found at the end of the game are
+also proven back up, so moves known to lose are left alone. A move proven to win,
+or else the move explored the most, is played. Every legal move of a position is
+listed, so positions with more than `MAX_MOVES` moves are left to the solver.
+
+Both players are assumed to have the same moves, as in every variant of this
+game: partisan rules, where the moves depend on the player, are not covered.
+*/
+
+use crate::core::{Game, Heaps, Move};
+use crate::grundy::{GameSum, GrundyCache, Position};
+use crate::player::{self, Player, PlayerKind};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::collections::HashMap;
+
+/// Iterations of a search when none are given
+pub const DEFAULT_ITERATIONS: u32 = 1000;
+/// Legal moves of the position searched at most, each node listing them all
+pub const MAX_MOVES: u64 = 1000;
+/// Weight of exploring rarely visited moves against playing the best ones so far
+const EXPLORATION: f64 = std::f64::consts::SQRT_2;
+
+/// A position, shared by every order of moves reaching it
+struct Node {
+    /// Player who moved into the position, `None` at the root
+    mover: Option<usize>,
+    /// Moves explored from the position, `(heap index, sticks to take)`, and the
+    /// positions they lead to
+    children: Vec<((usize, u64), usize)>,
+    untried: Vec<(usize, u64)>,
+    visits: u32,
+    /// Playouts won by the team of the mover
+    wins: f64,
+    /// Team winning from here whatever the others play, once proven
+    proven: Option<usize>,
+}
+
+impl Node {
+    fn new(mover: Option<usize>, heaps: &Heaps) -> Node {
+        Node {
+            mover,
+            children: Vec::new(),
+            untried: heaps.legal_moves(),
+            visits: 0,
+            wins: 0.0,
+            proven: None,
+        }
+    }
+
+    /// Upper confidence bound of the node seen from its mover
+    fn uct(&self, parent_visits: u32) -> f64 {
+        let visits = f64::from(self.visits);
+        self.wins / visits + EXPLORATION * (f64::from(parent_visits).ln() / visits).sqrt()
+    }
+}
+
+/// Moves explored from a position, positions being found again by their heap sizes
+/// and the player to move
+struct Tree<'a> {
+    game: &'a Game,
+    teams: Vec<usize>,
+    nodes: Vec<Node>,
+    index: HashMap<(Vec<u64>, usize), usize>,
+}
+
+impl<'a> Tree<'a> {
+    fn new(game: &'a Game) -> Tree<'a> {
+        Tree {
+            game,
+            teams: game.teams(),
+            nodes: vec![Node::new(None, game.heaps())],
+            index: HashMap::new(),
+        }
+    }
+
+    fn next_player(&self, player: usize) -> usize {
+        (player + 1) % self.teams.len()
+    }
+
+    /// Child to explore, moves proven to lose being left alone while others remain
+    fn select(&self, node: usize, player: usize) -> Option<((usize, u64), usize)> {
+        let team = self.teams[player];
+        let parent_visits = self.nodes[node].visits;
+        let children = &self.nodes[node].children;
+        let open: Vec<((usize, u64), usize)> = children
+            .iter()
+            .copied()
+            .filter(|&(_, child)| self.nodes[child].proven.is_none_or(|winner| winner == team))
+            .collect();
+        let candidates = if open.is_empty() { children } else { &open };
+        candidates.iter().copied().max_by(|&(_, a), &(_, b)| {
+            self.nodes[a]
+                .uct(parent_visits)
+                .total_cmp(&self.nodes[b].uct(parent_visits))
+        })
+    }
+
+    /// Team winning from a node whatever the others play, from its children:
+    /// the player to move wins if one move does, or every move leads to the same winner
+    fn prove(&self, node: usize, player: usize) -> Option<usize> {
+        let team = self.teams[player];
+        let node = &self.nodes[node];
+        let mut proven = node
+            .children
+            .iter()
+            .map(|&(_, child)| self.nodes[child].proven);
+        if proven.clone().any(|winner| winner == Some(team)) {
+            return Some(team);
+        }
+        let first = proven.next()??;
+        let settled = node.untried.is_empty() && proven.all(|winner| winner == Some(first));
+        settled.then_some(first)
+    }
+
+    /// Walk down the tree, add a move and play randomly to the end of the game
+    fn iterate<G: Rng>(&mut self, rng: &mut G) {
+        let rules = self.game.rules();
+        let mut heaps = self.game.heaps().clone();
+        let mut player = self.game.current_player();
+        let mut path = vec![(0, player)];
+        let mut node = 0;
+
+        // Selection, down to a node with moves left to explore
+        while self.nodes[node].proven.is_none() && self.nodes[node].untried.is_empty() {
+            let Some(((heap, amount), child)) = self.select(node, player) else {
+                break;
+            };
+            if heaps.remove_sticks(heap, Ok(amount)).is_err() {
+                break;
+            }
+            node = child;
+            player = self.next_player(player);
+            path.push((node, player));
+        }
+
+        // Expansion
+        if self.nodes[node].proven.is_none() && !self.nodes[node].untried.is_empty() {
+            let index = rng.gen_range(0..self.nodes[node].untried.len());
+            let (heap, amount) = self.nodes[node].untried.swap_remove(index);
+            if heaps.remove_sticks(heap, Ok(amount)).is_ok() {
+                let next = self.next_player(player);
+                let key = (heaps.sizes(), next);
+                let child = match self.index.get(&key) {
+                    Some(&child) => child,
+                    None => {
+                        let mut child = Node::new(Some(player), &heaps);
+                        if heaps.is_over() {
+                            child.proven = Some(self.teams[rules.winner(player, &self.teams)]);
+                        }
+                        self.nodes.push(child);
+                        self.index.insert(key, self.nodes.len() - 1);
+                        self.nodes.len() - 1
+                    }
+                };
+                self.nodes[node].children.push(((heap, amount), child));
+                node = child;
+                player = next;
+                path.push((node, player));
+            }
+        }
+
+        // Random playout, unless the outcome is already known
+        let winner = match self.nodes[node].proven {
+            Some(team) => team,
+            None => {
+                let mut last = self.nodes[node].mover.unwrap_or(player);
+                while !heaps.is_over() {
+                    let (heap, amount) = player::random_move(rng, &heaps);
+                    if heaps.remove_sticks(heap, Ok(amount)).is_err() {
+                        break;
+                    }
+                    last = player;
+                    player = self.next_player(player);
+                }
+                self.teams[rules.winner(last, &self.teams)]
+            }
+        };
+
+        for &(visited, to_move) in path.iter().rev() {
+            if self.nodes[visited].proven.is_none() {
+                self.nodes[visited].proven = self.prove(visited, to_move);
+            }
+            let node = &mut self.nodes[visited];
+            node.visits += 1;
+            if node.mover.is_some_and(|mover| self.teams[mover] == winner) {
+                node.wins += 1.0;
+            }
+        }
+    }
+
+    /// A move proven to win, or else the most explored one not proven to lose
+    fn best_move(&self) -> Option<(usize, u64)> {
+        let team = self.teams[self.game.current_player()];
+        let children = &self.nodes[0].children;
+        let proven = |winner: Option<usize>| match winner {
+            Some(winner) if winner == team => 2,
+            Some(_) => 0,
+            None => 1,
+        };
+        children
+            .iter()
+            .max_by_key(|&&(_, child)| {
+                let child = &self.nodes[child];
+                (proven(child.proven), child.visits)
+            })
+            .map(|&(mv, _)| mv)
+    }
+}
+
+/// Search the moves of the current player for `iterations` playouts, `None` once
+/// the game is over
+pub fn search<G: Rng>(rng: &mut G, game: &Game, iterations: u32) -> Option<(usize, u64)> {
+    if game.is_over() {
+        return None;
+    }
+    // Moves only get fewer further down the tree
+    if game.heaps().count_moves() > MAX_MOVES {
+        return game.heaps().winning_move(game.rules());
+    }
+    let mut tree = Tree::new(game);
+    for _ in 0..iterations.max(1) {
+        if tree.nodes[0].proven.is_some() {
+            break;
+        }
+        tree.iterate(rng);
+    }
+    tree.best_move()
+}
+
+/// Iterations asked for by a player spec, `mcts` or `mcts:<iterations>`
+pub fn parse_spec(spec: &str) -> Option<u32> {
+    let spec = spec.trim().to_lowercase();
+    match spec.split_once(':') {
+        None if spec == "mcts" => Some(DEFAULT_ITERATIONS),
+        Some(("mcts", iterations)) => iterations.trim().parse().ok().filter(|&n| n > 0),
+        _ => None,
+    }
+}
+
+pub struct MctsPlayer {
+    name: String,
+    iterations: u32,
+    rng: StdRng,
+}
+
+impl MctsPlayer {
+    pub fn new(iterations: u32) -> MctsPlayer {
+        MctsPlayer::with_rng(iterations, StdRng::from_entropy())
+    }
+
+    pub fn with_seed(iterations: u32, seed: u64) -> MctsPlayer {
+        MctsPlayer::with_rng(iterations, StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(iterations: u32, rng: StdRng) -> MctsPlayer {
+        MctsPlayer {
+            name: format!("Computer (mcts, {} iterations)", iterations),
+            iterations,
+            rng,
+        }
+    }
+}
+
+impl Player for MctsPlayer {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn kind(&self) -> PlayerKind {
+        PlayerKind::Mcts(self.iterations)
+    }
+
+    fn choose_move(&mut self, game: &Game) -> Option<Move> {
+        let (heap, amount) = search(&mut self.rng, game, self.iterations)
+            .unwrap_or_else(|| player::random_move(&mut self.rng, game.heaps()));
+        Some(Move { heap, amount })
+    }
+
+    /// Sums are solved exactly from their Grundy values, no search is needed
+    fn choose_sum_move(
+        &mut self,
+        sum: &GameSum<Position>,
+        cache: &mut GrundyCache<Position>,
+    ) -> Option<(usize, usize)> {
+        let optimal = sum.winning_move(cache);
+        Some(optimal.unwrap_or_else(|| player::random_sum_move(&mut self.rng, sum)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{Convention, Rules, StickNumber};
+
+    const NORMAL: Rules = Rules {
+        convention: Convention::Normal,
+    };
+    const MISERE: Rules = Rules {
+        convention: Convention::Misere,
+    };
+
+    fn new_game(heaps: Heaps, rules: Rules, first: usize) -> Game {
+        let names = vec![String::from("one"), String::from("two")];
+        Game::new(heaps, rules, names, first)
+    }
+
+    #[test]
+    fn test_beats_random_player() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for (rules, heaps) in [
+            (
+                NORMAL,
+                Heaps::single(StickNumber::with_moves(30, &[1, 3, 4])),
+            ),
+            (
+                MISERE,
+                Heaps::single(StickNumber::with_moves(25, &[2, 3, 7])),
+            ),
+            (MISERE, Heaps::new(&[2, 3, 4])),
+        ] {
+            let mut mcts = MctsPlayer::with_seed(300, 5);
+            let mut wins = 0;
+            for round in 0..20 {
+                let mut game = new_game(heaps.clone(), rules, round % 2);
+                while !game.is_over() {
+                    let mv = if game.current_player() == 0 {
+                        mcts.choose_move(&game).unwrap()
+                    } else {
+                        let (heap, amount) = player::random_move(&mut rng, game.heaps());
+                        Move { heap, amount }
+                    };
+                    assert!(game.apply_move(mv).is_ok());
+                }
+                wins += u32::from(game.winner() == Some(0));
+            }
+            assert!(wins >= 18, "{:?} won {} of 20", heaps, wins);
+        }
+    }
+
+    #[test]
+    fn test_finds_solver_moves_in_classic_nim() {
+        // Every winning position of three small heaps, against the exact solver
+        let mut rng = StdRng::seed_from_u64(3);
+        let (mut positions, mut agreements) = (0, 0);
+        for sizes in [
+            [1, 2, 4],
+            [2, 3, 4],
+            [1, 4, 6],
+            [3, 4, 5],
+            [2, 5, 6],
+            [1, 3, 5],
+        ] {
+            let heaps = Heaps::new(&sizes);
+            let game = new_game(heaps.clone(), NORMAL, 0);
+            let Some((heap, amount)) = search(&mut rng, &game, 5000) else {
+                panic!("no move for {:?}", sizes);
+            };
+            let mut next = heaps.clone();
+            next.remove_sticks(heap, Ok(amount)).unwrap();
+            positions += 1;
+            agreements += u32::from(!next.is_winning(&NORMAL));
+        }
+        assert!(
+            agreements >= positions - 1,
+            "{} of {}",
+            agreements,
+            positions
+        );
+    }
+
+    #[test]
+    fn test_specs_and_finished_games() {
+        assert_eq!(parse_spec("MCTS"), Some(DEFAULT_ITERATIONS));
+        assert_eq!(parse_spec("mcts:250"), Some(250));
+        assert_eq!(parse_spec("mcts:0"), None);
+        assert_eq!(parse_spec("mcts:many"), None);
+        assert_eq!(parse_spec("alice"), None);
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let game = new_game(Heaps::new(&[0, 0]), NORMAL, 0);
+        assert_eq!(search(&mut rng, &game, 10), None);
+        let game = new_game(Heaps::new(&[0, 3]), NORMAL, 0);
+        assert_eq!(search(&mut rng, &game, 50), Some((1, 3)));
+        assert_eq!(MctsPlayer::with_seed(10, 0).kind(), PlayerKind::Mcts(10));
+    }
+
+    #[test]
+    fn test_huge_heaps_left_to_solver() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let heaps = Heaps::single(StickNumber::new(u64::MAX, 1, u64::MAX / 2));
+        let game = new_game(heaps.clone(), NORMAL, 0);
+        assert_eq!(search(&mut rng, &game, 10), heaps.winning_move(&NORMAL));
+        let heaps = Heaps::new(&[1 << 40, 3, 1 << 41]);
+        let game = new_game(heaps.clone(), MISERE, 0);
+        assert_eq!(search(&mut rng, &game, 10), heaps.winning_move(&MISERE));
+    }
+}
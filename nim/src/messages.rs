@@ -0,0 +1,1641 @@
+/*
+Message catalog of the game, in English and French, and the glyphs of its output.
+
+Text is picked from the language of the thread showing it, set once by `main` from
+`--lang`, the NIM_LANG variable or the locale, English being the default. Layout
+such as the `  | ` margin stays with the callers, the catalog only holds the words.
+
+The plain ASCII mode, from `--ascii` or the NIM_ASCII variable, replaces the Nerd
+Font and emoji glyphs for terminals that cannot show them.
+*/
+
+use crate::core::StickNumberError;
+use std::cell::Cell;
+use std::fmt;
+
+#[derive(PartialEq, Debug, Clone, Copy, Default)]
+pub enum Language {
+    #[default]
+    English,
+    French,
+}
+
+impl std::str::FromStr for Language {
+    type Err = String;
+
+    /// A language name or code, locales like `fr_FR.UTF-8` giving their language
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lowered = s.trim().to_lowercase();
+        let code = lowered.split(['_', '-', '.']).next().unwrap_or_default();
+        match code {
+            "en" | "english" => Ok(Language::English),
+            "fr" | "french" | "français" | "francais" => Ok(Language::French),
+            _ => Err(format!("Unknown language: {}", s.trim())),
+        }
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Language::English => write!(f, "en"),
+            Language::French => write!(f, "fr"),
+        }
+    }
+}
+
+impl Language {
+    /// Language of NIM_LANG, or else of the locale, English when neither is known
+    pub fn from_env() -> Language {
+        ["NIM_LANG", "LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| value.parse().ok())
+            .unwrap_or_default()
+    }
+}
+
+/// How messages are shown
+#[derive(PartialEq, Debug, Clone, Copy, Default)]
+pub struct Locale {
+    pub language: Language,
+    /// Plain ASCII glyphs instead of Nerd Font and emoji ones
+    pub ascii: bool,
+}
+
+/// Whether NIM_ASCII asks for plain ASCII output, any value but 0 doing so
+pub fn ascii_from_env() -> bool {
+    std::env::var("NIM_ASCII").is_ok_and(|value| !value.is_empty() && value != "0")
+}
+
+thread_local! {
+    static LOCALE: Cell<Locale> = Cell::new(Locale::default());
+}
+
+pub fn set_locale(locale: Locale) {
+    LOCALE.with(|current| current.set(locale));
+}
+
+pub fn locale() -> Locale {
+    LOCALE.with(Cell::get)
+}
+
+/// Symbols heading or drawing the output
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Glyph {
+    /// Heads the title of each screen
+    Title,
+    /// Heads the end of a game
+    Victory,
+    /// Heads a question to the player
+    Prompt,
+    /// Heads an error
+    Error,
+    /// Heads the sticks left
+    Sticks,
+    /// Heads the turn of a player
+    Player,
+    /// A stick already taken in the full screen display
+    Taken,
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl fmt::Display for Glyph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (fancy, plain) = match self {
+            Glyph::Title => ("󰊖", "#"),
+            Glyph::Victory => ("✌️", "!"),
+            Glyph::Prompt => ("\u{f420}", "?"),
+            Glyph::Error => ("\u{ea87}", "x"),
+            Glyph::Sticks => ("\u{f449}", "="),
+            Glyph::Player => ("\u{f415}", ">"),
+            Glyph::Taken => ("·", "."),
+            Glyph::Left => ("←", "<"),
+            Glyph::Right => ("→", ">"),
+            Glyph::Up => ("↑", "^"),
+            Glyph::Down => ("↓", "v"),
+        };
+        write!(f, "{}", if locale().ascii { plain } else { fancy })
+    }
+}
+
+/// Everything the game says, in the language of the current locale
+pub enum Message<'a> {
+    Welcome,
+    Error(&'a dyn fmt::Display),
+    Goodbye,
+    InputClosed,
+    InputQuit,
+    InputTimedOut,
+    // Setup
+    AskNumberPlayers,
+    TooFewPlayers,
+    NotANumber(&'a str),
+    AskPlayerType(usize),
+    AskPlayerName(usize),
+    UnknownPlayerType(&'a str),
+    AskTeams,
+    InvalidTeam(&'a str),
+    TeamPerPlayer(usize),
+    TwoTeams,
+    AskHeapSizes,
+    AskWythoffHeaps,
+    AskValidSticks,
+    WythoffTwoHeaps,
+    NoStick,
+    AskConvention,
+    UnknownConvention(&'a str),
+    FirstOutOfRange(usize),
+    TableMismatch(&'a dyn fmt::Display),
+    TurnOrder(&'a str),
+    GameResumed(&'a dyn fmt::Display),
+    // Turns
+    RemainingSticks,
+    HeapsTitle,
+    PlayerTurn {
+        number: usize,
+        name: &'a str,
+    },
+    PlayerTurnClock {
+        number: usize,
+        name: &'a str,
+        left: &'a str,
+    },
+    HowMany(&'a str),
+    MoveRange {
+        min: u64,
+        max: u64,
+    },
+    MoveSet(&'a str),
+    WhichHeap(usize),
+    WhichHeapOrBoth {
+        heaps: usize,
+        both: usize,
+    },
+    HowManyFromBoth,
+    HowManyFromHeap(&'a dyn fmt::Display),
+    Removes {
+        name: &'a str,
+        amount: u64,
+    },
+    RemovesFromBoth {
+        name: &'a str,
+        amount: u64,
+    },
+    RemovesFromHeap {
+        name: &'a str,
+        amount: u64,
+        heap: usize,
+    },
+    Won {
+        name: &'a str,
+        turns: u32,
+    },
+    TeamWon {
+        team: usize,
+        members: &'a str,
+        turns: u32,
+    },
+    // Sums of games
+    GamesTitle,
+    WhichGame(usize),
+    PossibleMoves(&'a dyn fmt::Display),
+    WhichMove(usize),
+    TurnsGameInto {
+        name: &'a str,
+        game: usize,
+        option: &'a str,
+    },
+    NoMoveInGames,
+    SubtractionHeap {
+        sticks: u8,
+        moves: &'a str,
+    },
+    KaylesRow(u8),
+    GrundyHeap(u8),
+    NothingLeft,
+    // Commands
+    GameSaved(&'a dyn fmt::Display),
+    GameLoaded(&'a dyn fmt::Display),
+    Undid {
+        name: &'a str,
+        amount: u64,
+        heap: usize,
+    },
+    NothingToUndo,
+    NothingToRedo,
+    ReplayOrQuit,
+    ReplayTitle,
+    PressEnter,
+    ReplayTurn {
+        turn: usize,
+        number: usize,
+        name: &'a str,
+    },
+    // Time controls
+    OutOfTimeForfeit(&'a str),
+    OutOfTimeRandom(&'a str),
+    Forfeits(&'a str),
+    // Network games
+    WaitingForPlayer(&'a dyn fmt::Display),
+    Joined {
+        name: &'a str,
+        address: &'a dyn fmt::Display,
+    },
+    NoNetworkCommands,
+    AskYourName,
+    Serving(&'a dyn fmt::Display),
+    // Refused moves
+    InvalidMove(&'a StickNumberError),
+    // Coach
+    Take(u64),
+    TakeFromBoth(u64),
+    TakeFromHeap {
+        amount: u64,
+        heap: usize,
+    },
+    MultipleOf(u128),
+    MoreThanMultiple {
+        offset: u128,
+        period: u128,
+    },
+    AnswerWith(u128),
+    TakingPerTurn {
+        allowed: &'a str,
+        losing: &'a str,
+    },
+    MisereNimReason,
+    MisereWythoffReason,
+    WythoffReason,
+    HintLeave {
+        take: u64,
+        left: u64,
+        position: &'a str,
+        reason: &'a str,
+    },
+    HintLeaveLosing {
+        take: u64,
+        left: u64,
+        reason: &'a str,
+    },
+    HintLeaveCold {
+        mv: &'a str,
+        small: u64,
+        large: u64,
+        reason: &'a str,
+    },
+    HintMove {
+        mv: &'a str,
+        reason: &'a str,
+    },
+    HintNimSum(&'a str),
+    NoWinningMove {
+        remaining: u64,
+        position: &'a str,
+        reason: &'a str,
+        take: u64,
+    },
+    NoWinningMoveLosing {
+        remaining: u64,
+        reason: &'a str,
+    },
+    NoWinningMoveCold {
+        small: u64,
+        large: u64,
+        reason: &'a str,
+    },
+    NoWinningMoveMisere(&'a str),
+    NoWinningMoveNimSum,
+    NimSumKeeps,
+    NimSumBreaks,
+    HeapLabel(usize),
+    NimSumLabel,
+    CoachGood,
+    CoachLastStick,
+    CoachMistake(&'a str),
+    CoachHopeless,
+    // Full screen display
+    PressAnyKey(&'a str),
+    Thinking {
+        number: usize,
+        name: &'a str,
+    },
+    ToMove {
+        number: usize,
+        name: &'a str,
+    },
+    BothHeaps,
+    MovesTitle,
+    // Reports
+    TranscriptWritten(&'a dyn fmt::Display),
+    TranscriptFailed(&'a dyn fmt::Display),
+    LeaderboardOf(&'a dyn fmt::Display),
+    NoGamesRecorded,
+    LastGames,
+    WonBy {
+        names: &'a str,
+        turns: u32,
+    },
+    TournamentOf {
+        strategies: usize,
+        rounds: u32,
+        seed: u64,
+    },
+    WinRateMatrix,
+    TrainingOn {
+        position: &'a str,
+        convention: &'a dyn fmt::Display,
+        episodes: u32,
+        seed: u64,
+    },
+    PositionsLearned {
+        positions: usize,
+        episodes: u32,
+        path: &'a dyn fmt::Display,
+    },
+    SolverAgreement {
+        share: f64,
+        positions: usize,
+    },
+    TooManyToCompare,
+    WonAgainstRandom {
+        wins: u32,
+        games: u32,
+    },
+    AnalysisOf {
+        position: &'a str,
+        convention: &'a dyn fmt::Display,
+    },
+    PositionsReachable {
+        positions: usize,
+        losing: usize,
+    },
+    FirstPlayerWins(u32),
+    FirstPlayerLoses(u32),
+    SolverAgrees,
+    SolverDisagrees(&'a str),
+    ColumnRank,
+    ColumnPlayer,
+    ColumnStrategy,
+    ColumnGames,
+    ColumnWins,
+    ColumnLosses,
+    ColumnWinRate,
+    ColumnAverageTurns,
+    ColumnStreak,
+    ColumnBest,
+    ColumnElo,
+    ColumnPosition,
+    ColumnResult,
+    ColumnLength,
+    ColumnWinningMoves,
+    ColumnWinningHeapMoves,
+    // Setup errors
+    MinAboveMax {
+        min: u64,
+        max: u64,
+    },
+    SticksWithoutStick,
+    WythoffSticks,
+    MovesSingleHeap,
+    TooFewNamedPlayers,
+    TooFewStrategies,
+    TournamentSetup,
+    NeedsSticks(&'a str),
+    OnlyHeapsAndRules(&'a str),
+    NoShuffleHosted,
+    HostedNewGames,
+    ServeSetup,
+    GamesLocalOnly,
+    PenaltyNeedsClock,
+    ClockLocalOnly,
+    TranscriptLocalOnly,
+    PositionNeeded,
+    TooManyPositions(usize),
+    TooManyMoves(u64),
+    UnknownStrategy {
+        name: &'a str,
+        expected: &'a str,
+    },
+    StrategyTwice(&'a str),
+    NoMoveToPlay,
+    // File errors
+    SaveAccess(&'a dyn fmt::Display),
+    SaveFormat(&'a dyn fmt::Display),
+    SaveInconsistent(&'a dyn fmt::Display),
+    SavedPlayersMismatch,
+    SavedUnknownPlayer,
+    SavedTeamsMismatch,
+    SavedInvalidHeap,
+    LearnedCannotPlay(&'a dyn fmt::Display),
+    ProfilesAccess(&'a dyn fmt::Display),
+    ProfilesFormat(&'a dyn fmt::Display),
+    TableAccess(&'a dyn fmt::Display),
+    TableFormat(&'a dyn fmt::Display),
+    // Transcript errors
+    TranscriptAccess(&'a dyn fmt::Display),
+    TranscriptLine {
+        line: usize,
+        reason: &'a dyn fmt::Display,
+    },
+    TranscriptMissing(&'a str),
+    TranscriptIllegalMove {
+        number: usize,
+        error: &'a dyn fmt::Display,
+    },
+    VariantNimOrWythoff,
+    RangeNotation,
+    TeamsFromOne,
+    FirstFromOne,
+    PlayersInOrder,
+    UnknownHeader(&'a str),
+    NoHeap,
+    WythoffAnyAmount,
+    RangeFromOne,
+    AmountsPositive,
+    MovesHeaderSingleHeap,
+    NoSuchPlayer,
+    MoveNotation,
+    MovesInOrder,
+    HeadersFirst,
+    HeaderOrMove,
+    ForfeitEnds,
+    OtherWinner,
+    // API errors
+    PathNotFound(&'a str),
+    GameNotFound(&'a str),
+    MethodNotAllowed,
+    InvalidJson(&'a dyn fmt::Display),
+    NoComputerMove(&'a str),
+    SticksToStart,
+    AtMostSticks(u64),
+    LearnedOnCommandLine,
+    AtMostIterations(u32),
+    AtMostPlayers(usize),
+    MalformedRequestLine,
+    MalformedLength,
+    BodyTooLarge,
+    BodyNotUtf8,
+    RequestTimedOut,
+    // Network errors
+    Disconnected,
+    UnexpectedLine(&'a str),
+}
+
+impl Message<'_> {
+    fn english(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Message::Welcome => write!(f, "Welcome to Nim Game!"),
+            Message::Error(e) => write!(f, "Error: {}", e),
+            Message::Goodbye => write!(f, "Goodbye!"),
+            Message::InputClosed => write!(f, "Input closed, goodbye!"),
+            Message::InputQuit => write!(f, "The game was quit"),
+            Message::InputTimedOut => write!(f, "No answer in time"),
+            Message::AskNumberPlayers => {
+                write!(f, "Please enter the number of players (2 by default):")
+            }
+            Message::TooFewPlayers => write!(f, "There must be at least two players"),
+            Message::NotANumber(value) => write!(f, "{} is not a valid number", value.trim()),
+            Message::AskPlayerType(number) => write!(
+                f,
+                "Please enter the type of player {} (human, easy, medium, hard, mcts, learned): ",
+                number
+            ),
+            Message::AskPlayerName(number) => {
+                write!(f, "Please enter the name of player {}: ", number)
+            }
+            Message::UnknownPlayerType(kind) => write!(f, "Unknown difficulty: {}", kind.trim()),
+            Message::AskTeams => write!(
+                f,
+                "Please enter the team of each player separated by spaces, or nothing to play without teams:"
+            ),
+            Message::InvalidTeam(team) => write!(f, "Invalid team number: {}", team),
+            Message::TeamPerPlayer(players) => {
+                write!(f, "there must be a team for each of the {} players", players)
+            }
+            Message::TwoTeams => write!(f, "there must be at least two teams"),
+            Message::AskHeapSizes => write!(
+                f,
+                "Please enter a number of total sticks, or several heap sizes separated by spaces:"
+            ),
+            Message::AskWythoffHeaps => write!(
+                f,
+                "Please enter the sizes of the two heaps separated by a space:"
+            ),
+            Message::AskValidSticks => write!(f, "Please enter a valid number of total sticks:"),
+            Message::WythoffTwoHeaps => write!(f, "Wythoff's game is played on two heaps"),
+            Message::NoStick => write!(f, "There must be at least one stick"),
+            Message::AskConvention => write!(
+                f,
+                "Please enter the play convention (normal: last stick wins, misere: last stick loses):"
+            ),
+            Message::UnknownConvention(convention) => {
+                write!(f, "Unknown play convention: {}", convention.trim())
+            }
+            Message::FirstOutOfRange(players) => {
+                write!(f, "--first must be between 1 and {}", players)
+            }
+            Message::TableMismatch(path) => write!(
+                f,
+                "the table {} was trained with other rules or moves",
+                path
+            ),
+            Message::TurnOrder(order) => write!(f, "Turn order: {}", order),
+            Message::GameResumed(path) => write!(f, "Game resumed from {}", path),
+            Message::RemainingSticks => write!(f, "Remaining sticks:"),
+            Message::HeapsTitle => write!(f, "Heaps:"),
+            Message::PlayerTurn { number, name } => write!(f, "Player {} - {}: ", number, name),
+            Message::PlayerTurnClock { number, name, left } => {
+                write!(f, "Player {} - {} ({} left): ", number, name, left)
+            }
+            Message::HowMany(moves) => write!(f, "How many to remove ({})", moves),
+            Message::MoveRange { min, max } => write!(f, "min={}, max={}", min, max),
+            Message::MoveSet(moves) => write!(f, "one of {}", moves),
+            Message::WhichHeap(heaps) => write!(f, "Which heap to take from (1-{})", heaps),
+            Message::WhichHeapOrBoth { heaps, both } => write!(
+                f,
+                "Which heap to take from (1-{}, or {} for both)",
+                heaps, both
+            ),
+            Message::HowManyFromBoth => write!(f, "How many to remove from both heaps"),
+            Message::HowManyFromHeap(heap) => write!(f, "How many to remove from heap {}", heap),
+            Message::Removes { name, amount } => write!(f, "{} removes {}", name, amount),
+            Message::RemovesFromBoth { name, amount } => {
+                write!(f, "{} removes {} from both heaps", name, amount)
+            }
+            Message::RemovesFromHeap { name, amount, heap } => {
+                write!(f, "{} removes {} from heap {}", name, amount, heap)
+            }
+            Message::Won { name, turns } => write!(f, "Game won by {} in {} turns", name, turns),
+            Message::TeamWon {
+                team,
+                members,
+                turns,
+            } => write!(
+                f,
+                "Game won by team {} ({}) in {} turns",
+                team, members, turns
+            ),
+            Message::GamesTitle => write!(f, "Games:"),
+            Message::WhichGame(games) => write!(f, "Which game to play in (1-{})", games),
+            Message::PossibleMoves(position) => write!(f, "Possible moves in {}:", position),
+            Message::WhichMove(options) => write!(f, "Which move (1-{})", options),
+            Message::TurnsGameInto { name, game, option } => {
+                write!(f, "{} turns game {} into {}", name, game, option)
+            }
+            Message::NoMoveInGames => write!(f, "There is no move to play in these games"),
+            Message::SubtractionHeap { sticks, moves } => {
+                write!(f, "{} sticks taking {}", sticks, moves)
+            }
+            Message::KaylesRow(pins) => write!(f, "Kayles row of {}", pins),
+            Message::GrundyHeap(sticks) => write!(f, "Grundy heap of {}", sticks),
+            Message::NothingLeft => write!(f, "nothing"),
+            Message::GameSaved(path) => write!(f, "Game saved to {}", path),
+            Message::GameLoaded(path) => write!(f, "Game loaded from {}", path),
+            Message::Undid { name, amount, heap } => {
+                write!(f, "Undid {} taking {} from heap {}", name, amount, heap)
+            }
+            Message::NothingToUndo => write!(f, "There is no move to undo"),
+            Message::NothingToRedo => write!(f, "There is no move to redo"),
+            Message::ReplayOrQuit => write!(
+                f,
+                "Type replay to step through the game, or press enter to quit:"
+            ),
+            Message::ReplayTitle => write!(f, "Replay"),
+            Message::PressEnter => write!(f, "Press enter for the next move"),
+            Message::ReplayTurn { turn, number, name } => {
+                write!(f, "Turn {} - Player {} - {}: ", turn, number, name)
+            }
+            Message::OutOfTimeForfeit(name) => {
+                write!(f, "{} ran out of time and forfeits the game", name)
+            }
+            Message::OutOfTimeRandom(name) => {
+                write!(f, "{} ran out of time, playing a random move", name)
+            }
+            Message::Forfeits(name) => write!(f, "{} forfeits the game", name),
+            Message::WaitingForPlayer(address) => {
+                write!(f, "Waiting for the other player on {}", address)
+            }
+            Message::Joined { name, address } => write!(f, "{} joined from {}", name, address),
+            Message::NoNetworkCommands => {
+                write!(f, "Commands are not available in network games")
+            }
+            Message::AskYourName => write!(f, "Please enter your name: "),
+            Message::Serving(address) => write!(f, "Serving games on http://{}", address),
+            Message::InvalidMove(error) => match error {
+                StickNumberError::RemainingNegative => {
+                    write!(f, "Taking that many sticks will lead to a negative number")
+                }
+                StickNumberError::TakeOverMax => write!(f, "Trying to take over the maximum limit"),
+                StickNumberError::TakeUnderMin => {
+                    write!(f, "Trying to take under the minimum limit")
+                }
+                StickNumberError::DisallowedAmount => {
+                    write!(f, "That amount is not one of the allowed moves")
+                }
+                StickNumberError::ParsingError => {
+                    write!(f, "Entered value is not a valid number of sticks")
+                }
+                StickNumberError::HeapOutOfRange => write!(f, "There is no heap with that number"),
+                StickNumberError::GameOver => write!(f, "The game is already over"),
+            },
+            Message::Take(amount) => write!(f, "take {}", amount),
+            Message::TakeFromBoth(amount) => write!(f, "take {} from both heaps", amount),
+            Message::TakeFromHeap { amount, heap } => {
+                write!(f, "take {} from heap {}", amount, heap)
+            }
+            Message::MultipleOf(period) => write!(f, "a multiple of {}", period),
+            Message::MoreThanMultiple { offset, period } => {
+                write!(f, "{} more than a multiple of {}", offset, period)
+            }
+            Message::AnswerWith(period) => write!(
+                f,
+                "whatever the other player takes, answering with {} minus it keeps it that way",
+                period
+            ),
+            Message::TakingPerTurn { allowed, losing } => write!(
+                f,
+                "taking {} per turn, the player to move loses with {} sticks",
+                allowed, losing
+            ),
+            Message::MisereNimReason => write!(
+                f,
+                "in misère play, once at most one heap holds more than one stick, \
+                 the winner leaves an odd number of single stick heaps"
+            ),
+            Message::MisereWythoffReason => write!(
+                f,
+                "in misère play the last cold positions are 0 and 1, then 2 and 2"
+            ),
+            Message::WythoffReason => write!(
+                f,
+                "the smaller heap is the difference times the golden ratio, rounded down"
+            ),
+            Message::HintLeave {
+                take,
+                left,
+                position,
+                reason,
+            } => write!(
+                f,
+                "Hint: take {} to leave {} sticks, {}: {}",
+                take, left, position, reason
+            ),
+            Message::HintLeaveLosing { take, left, reason } => write!(
+                f,
+                "Hint: take {} to leave {} sticks, a losing position: {}",
+                take, left, reason
+            ),
+            Message::HintLeaveCold {
+                mv,
+                small,
+                large,
+                reason,
+            } => write!(
+                f,
+                "Hint: {} to leave heaps of {} and {}, a cold position: {}",
+                mv, small, large, reason
+            ),
+            Message::HintMove { mv, reason } => write!(f, "Hint: {}: {}", mv, reason),
+            Message::HintNimSum(mv) => write!(
+                f,
+                "Hint: {} so that the nim-sum, the XOR of the heap sizes, becomes 0:",
+                mv
+            ),
+            Message::NoWinningMove {
+                remaining,
+                position,
+                reason,
+                take,
+            } => write!(
+                f,
+                "Hint: no winning move, {} sticks is already {}: {}. Take {} and wait for a mistake",
+                remaining, position, reason, take
+            ),
+            Message::NoWinningMoveLosing { remaining, reason } => write!(
+                f,
+                "Hint: no winning move, {} sticks is already a losing position: {}",
+                remaining, reason
+            ),
+            Message::NoWinningMoveCold {
+                small,
+                large,
+                reason,
+            } => write!(
+                f,
+                "Hint: no winning move, heaps of {} and {} are already a cold position: {}. \
+                 Take 1 and wait for a mistake",
+                small, large, reason
+            ),
+            Message::NoWinningMoveMisere(reason) => write!(
+                f,
+                "Hint: no winning move, {}. Take 1 and wait for a mistake",
+                reason
+            ),
+            Message::NoWinningMoveNimSum => write!(
+                f,
+                "Hint: no winning move, the nim-sum of the heaps is already 0:"
+            ),
+            Message::NimSumKeeps => write!(
+                f,
+                "Every move from a nim-sum of 0 makes it non-zero, and you can always bring it back to 0"
+            ),
+            Message::NimSumBreaks => write!(
+                f,
+                "Every move makes it non-zero for the other player. Take 1 and wait for a mistake"
+            ),
+            Message::HeapLabel(heap) => write!(f, "heap {}", heap),
+            Message::NimSumLabel => write!(f, "nim-sum"),
+            Message::CoachGood => write!(
+                f,
+                "Coach: good move, the other player is left without a winning move"
+            ),
+            Message::CoachLastStick => write!(f, "Coach: good move, that was the last stick"),
+            Message::CoachMistake(mv) => {
+                write!(f, "Coach: mistake, {} was winning. Type hint to see why", mv)
+            }
+            Message::CoachHopeless => write!(
+                f,
+                "Coach: no move could win from here against perfect play, keep the game long"
+            ),
+            Message::PressAnyKey(text) => write!(f, "{} - press any key to quit", text),
+            Message::Thinking { number, name } => {
+                write!(f, "Player {} - {} is thinking...", number, name)
+            }
+            Message::ToMove { number, name } => write!(
+                f,
+                "Player {} - {} to move: {}/{} amount, {}/{} heap, enter to play, q to quit",
+                number,
+                name,
+                Glyph::Left,
+                Glyph::Right,
+                Glyph::Up,
+                Glyph::Down
+            ),
+            Message::BothHeaps => write!(f, "both heaps"),
+            Message::MovesTitle => write!(f, "Moves"),
+            Message::TranscriptWritten(path) => write!(f, "Transcript written to {}", path),
+            Message::TranscriptFailed(e) => write!(f, "Could not write the transcript: {}", e),
+            Message::LeaderboardOf(path) => write!(f, "Leaderboard of {}", path),
+            Message::NoGamesRecorded => write!(f, "No games recorded yet"),
+            Message::LastGames => write!(f, "Last games"),
+            Message::WonBy { names, turns } => write!(f, "won by {} in {} turns", names, turns),
+            Message::TournamentOf {
+                strategies,
+                rounds,
+                seed,
+            } => write!(
+                f,
+                "Tournament of {} strategies, {} games per pair, seed {}",
+                strategies, rounds, seed
+            ),
+            Message::WinRateMatrix => write!(f, "Win rate of each row against each column"),
+            Message::TrainingOn {
+                position,
+                convention,
+                episodes,
+                seed,
+            } => write!(
+                f,
+                "Training on {}, {} play, {} games of self-play, seed {}",
+                position, convention, episodes, seed
+            ),
+            Message::PositionsLearned {
+                positions,
+                episodes,
+                path,
+            } => write!(
+                f,
+                "{} positions learned in {} games, table written to {}",
+                positions, episodes, path
+            ),
+            Message::SolverAgreement { share, positions } => write!(
+                f,
+                "Winning move found in {:.0}% of the {} winning positions",
+                100.0 * share,
+                positions
+            ),
+            Message::TooManyToCompare => {
+                write!(f, "Too many positions to compare with the solver")
+            }
+            Message::WonAgainstRandom { wins, games } => {
+                write!(f, "Won {} of {} games against a random player", wins, games)
+            }
+            Message::AnalysisOf {
+                position,
+                convention,
+            } => write!(f, "Analysis of {}, {} play", position, convention),
+            Message::PositionsReachable { positions, losing } => write!(
+                f,
+                "{} positions reachable, {} P-positions where the player to move loses",
+                positions, losing
+            ),
+            Message::FirstPlayerWins(length) => write!(
+                f,
+                "The first player wins, in {} moves at most against the best defence",
+                length
+            ),
+            Message::FirstPlayerLoses(length) => write!(
+                f,
+                "The first player loses against perfect play, holding on for {} moves at most",
+                length
+            ),
+            Message::SolverAgrees => write!(f, "The solver of the game agrees on every position"),
+            Message::SolverDisagrees(positions) => {
+                write!(f, "The solver of the game disagrees on {}", positions)
+            }
+            Message::ColumnRank => write!(f, "Rank"),
+            Message::ColumnPlayer => write!(f, "Player"),
+            Message::ColumnStrategy => write!(f, "Strategy"),
+            Message::ColumnGames => write!(f, "Games"),
+            Message::ColumnWins => write!(f, "Wins"),
+            Message::ColumnLosses => write!(f, "Losses"),
+            Message::ColumnWinRate => write!(f, "Win rate"),
+            Message::ColumnAverageTurns => write!(f, "Avg turns"),
+            Message::ColumnStreak => write!(f, "Streak"),
+            Message::ColumnBest => write!(f, "Best"),
+            Message::ColumnElo => write!(f, "Elo"),
+            Message::ColumnPosition => write!(f, "Position"),
+            Message::ColumnResult => write!(f, "Result"),
+            Message::ColumnLength => write!(f, "Length"),
+            Message::ColumnWinningMoves => write!(f, "Winning moves"),
+            Message::ColumnWinningHeapMoves => write!(f, "Winning moves (heap:amount)"),
+            Message::MinAboveMax { min, max } => {
+                write!(f, "--min ({}) must not be greater than --max ({})", min, max)
+            }
+            Message::SticksWithoutStick => write!(f, "--sticks must contain at least one stick"),
+            Message::WythoffSticks => write!(f, "--wythoff needs exactly two heaps in --sticks"),
+            Message::MovesSingleHeap => write!(f, "--moves only applies to a single heap game"),
+            Message::TooFewNamedPlayers => write!(f, "--players must name at least two players"),
+            Message::TooFewStrategies => {
+                write!(f, "--strategies must name at least two strategies")
+            }
+            Message::TournamentSetup => write!(
+                f,
+                "tournaments choose their own players, turn order and display"
+            ),
+            Message::NeedsSticks(command) => {
+                write!(f, "{} needs the starting position in --sticks", command)
+            }
+            Message::OnlyHeapsAndRules(command) => write!(
+                f,
+                "{} only needs the heaps and the rules, not players",
+                command
+            ),
+            Message::NoShuffleHosted => write!(f, "--shuffle is not available in hosted games"),
+            Message::HostedNewGames => write!(
+                f,
+                "hosted games are new games between the host and the joining player, \
+                 without --players or --resume"
+            ),
+            Message::ServeSetup => write!(
+                f,
+                "serve only takes the heaps, rules and players of new games"
+            ),
+            Message::GamesLocalOnly => write!(
+                f,
+                "--games is only available in new local games without --tui"
+            ),
+            Message::PenaltyNeedsClock => {
+                write!(f, "--timeout-penalty needs --move-time or --time-bank")
+            }
+            Message::ClockLocalOnly => write!(
+                f,
+                "time controls are only available in local games of Nim without --tui"
+            ),
+            Message::TranscriptLocalOnly => write!(
+                f,
+                "--transcript is only available in games of Nim played locally or hosted"
+            ),
+            Message::PositionNeeded => write!(f, "--sticks gives the starting position"),
+            Message::TooManyPositions(positions) => write!(
+                f,
+                "more than {} positions are reachable, try smaller heaps",
+                positions
+            ),
+            Message::TooManyMoves(moves) => write!(
+                f,
+                "more than {} moves from the starting position, try smaller heaps",
+                moves
+            ),
+            Message::UnknownStrategy { name, expected } => {
+                write!(f, "unknown strategy {}, expected one of {}", name, expected)
+            }
+            Message::StrategyTwice(name) => write!(f, "strategy {} is named twice", name),
+            Message::NoMoveToPlay => write!(f, "the heaps leave no move to play"),
+            Message::SaveAccess(e) => write!(f, "Could not access the save file: {}", e),
+            Message::SaveFormat(e) => write!(f, "The save file is not a valid game: {}", e),
+            Message::SaveInconsistent(reason) => {
+                write!(f, "The saved game is inconsistent: {}", reason)
+            }
+            Message::SavedPlayersMismatch => write!(f, "players do not match their names"),
+            Message::SavedUnknownPlayer => write!(f, "unknown player number"),
+            Message::SavedTeamsMismatch => write!(f, "teams do not match the players"),
+            Message::SavedInvalidHeap => write!(f, "invalid heap"),
+            Message::LearnedCannotPlay(e) => write!(f, "a learned player cannot play: {}", e),
+            Message::ProfilesAccess(e) => write!(f, "Could not access the profiles file: {}", e),
+            Message::ProfilesFormat(e) => write!(f, "The profiles file is not valid: {}", e),
+            Message::TableAccess(e) => write!(f, "Could not access the table file: {}", e),
+            Message::TableFormat(e) => write!(f, "The table file is not a valid table: {}", e),
+            Message::TranscriptAccess(e) => write!(f, "Could not access the transcript: {}", e),
+            Message::TranscriptLine { line, reason } => {
+                write!(f, "Line {} of the transcript: {}", line, reason)
+            }
+            Message::TranscriptMissing(header) => {
+                write!(f, "The transcript has no {} header", header)
+            }
+            Message::TranscriptIllegalMove { number, error } => {
+                write!(f, "Move {} of the transcript is illegal: {}", number, error)
+            }
+            Message::VariantNimOrWythoff => write!(f, "the variant is nim or wythoff"),
+            Message::RangeNotation => write!(f, "a range is written MIN-MAX"),
+            Message::TeamsFromOne => write!(f, "teams are counted from 1"),
+            Message::FirstFromOne => write!(f, "the first player is counted from 1"),
+            Message::PlayersInOrder => {
+                write!(f, "players are named in order, starting from Player 1")
+            }
+            Message::UnknownHeader(key) => write!(f, "unknown header {}", key),
+            Message::NoHeap => write!(f, "there must be at least one heap"),
+            Message::WythoffAnyAmount => write!(f, "Wythoff's game takes any amount"),
+            Message::RangeFromOne => write!(f, "the range must go from 1 or more up"),
+            Message::AmountsPositive => write!(f, "every amount must be positive"),
+            Message::MovesHeaderSingleHeap => write!(f, "moves only apply to a single heap game"),
+            Message::NoSuchPlayer => write!(f, "there is no such player"),
+            Message::MoveNotation => write!(f, "a move is written HEAP:AMOUNT"),
+            Message::MovesInOrder => write!(f, "moves are numbered in order from 1"),
+            Message::HeadersFirst => write!(f, "headers come before the moves"),
+            Message::HeaderOrMove => write!(f, "expected a header or a numbered move"),
+            Message::ForfeitEnds => write!(f, "a forfeit ends the game"),
+            Message::OtherWinner => write!(f, "the moves do not end with this winner"),
+            Message::PathNotFound(path) => write!(f, "path {} not found", path),
+            Message::GameNotFound(id) => write!(f, "game {} not found", id),
+            Message::MethodNotAllowed => write!(f, "method not allowed on this path"),
+            Message::InvalidJson(e) => write!(f, "invalid JSON body: {}", e),
+            Message::NoComputerMove(name) => write!(f, "{} did not choose a move", name),
+            Message::SticksToStart => write!(f, "sticks are needed to start a game"),
+            Message::AtMostSticks(sticks) => write!(f, "a game has at most {} sticks", sticks),
+            Message::LearnedOnCommandLine => {
+                write!(f, "learned players are only given on the command line")
+            }
+            Message::AtMostIterations(iterations) => {
+                write!(f, "tree search is limited to {} iterations", iterations)
+            }
+            Message::AtMostPlayers(players) => write!(f, "a game has at most {} players", players),
+            Message::MalformedRequestLine => write!(f, "malformed request line"),
+            Message::MalformedLength => write!(f, "malformed Content-Length"),
+            Message::BodyTooLarge => write!(f, "request body too large"),
+            Message::BodyNotUtf8 => write!(f, "request body is not UTF-8"),
+            Message::RequestTimedOut => write!(f, "the request was not received in time"),
+            Message::Disconnected => write!(f, "the other player disconnected"),
+            Message::UnexpectedLine(line) => write!(f, "unexpected message: {}", line),
+        }
+    }
+
+    fn french(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Message::Welcome => write!(f, "Bienvenue dans le jeu de Nim !"),
+            Message::Error(e) => write!(f, "Erreur : {}", e),
+            Message::Goodbye => write!(f, "Au revoir !"),
+            Message::InputClosed => write!(f, "Entrée fermée, au revoir !"),
+            Message::InputQuit => write!(f, "La partie a été quittée"),
+            Message::InputTimedOut => write!(f, "Pas de réponse à temps"),
+            Message::AskNumberPlayers => {
+                write!(f, "Veuillez entrer le nombre de joueurs (2 par défaut) :")
+            }
+            Message::TooFewPlayers => write!(f, "Il faut au moins deux joueurs"),
+            Message::NotANumber(value) => {
+                write!(f, "{} n'est pas un nombre valide", value.trim())
+            }
+            Message::AskPlayerType(number) => write!(
+                f,
+                "Veuillez entrer le type du joueur {} (human, easy, medium, hard, mcts, learned) : ",
+                number
+            ),
+            Message::AskPlayerName(number) => {
+                write!(f, "Veuillez entrer le nom du joueur {} : ", number)
+            }
+            Message::UnknownPlayerType(kind) => write!(f, "Niveau inconnu : {}", kind.trim()),
+            Message::AskTeams => write!(
+                f,
+                "Veuillez entrer l'équipe de chaque joueur séparées par des espaces, ou rien pour jouer sans équipes :"
+            ),
+            Message::InvalidTeam(team) => write!(f, "Numéro d'équipe invalide : {}", team),
+            Message::TeamPerPlayer(players) => {
+                write!(f, "il faut une équipe pour chacun des {} joueurs", players)
+            }
+            Message::TwoTeams => write!(f, "il faut au moins deux équipes"),
+            Message::AskHeapSizes => write!(
+                f,
+                "Veuillez entrer un nombre total de bâtonnets, ou la taille de plusieurs tas séparées par des espaces :"
+            ),
+            Message::AskWythoffHeaps => write!(
+                f,
+                "Veuillez entrer la taille des deux tas séparées par une espace :"
+            ),
+            Message::AskValidSticks => {
+                write!(f, "Veuillez entrer un nombre total de bâtonnets valide :")
+            }
+            Message::WythoffTwoHeaps => write!(f, "Le jeu de Wythoff se joue sur deux tas"),
+            Message::NoStick => write!(f, "Il faut au moins un bâtonnet"),
+            Message::AskConvention => write!(
+                f,
+                "Veuillez entrer la convention de jeu (normal : le dernier bâtonnet gagne, misere : le dernier bâtonnet perd) :"
+            ),
+            Message::UnknownConvention(convention) => {
+                write!(f, "Convention de jeu inconnue : {}", convention.trim())
+            }
+            Message::FirstOutOfRange(players) => {
+                write!(f, "--first doit être entre 1 et {}", players)
+            }
+            Message::TableMismatch(path) => write!(
+                f,
+                "la table {} a été apprise avec d'autres règles ou coups",
+                path
+            ),
+            Message::TurnOrder(order) => write!(f, "Ordre de jeu : {}", order),
+            Message::GameResumed(path) => write!(f, "Partie reprise depuis {}", path),
+            Message::RemainingSticks => write!(f, "Bâtonnets restants :"),
+            Message::HeapsTitle => write!(f, "Tas :"),
+            Message::PlayerTurn { number, name } => write!(f, "Joueur {} - {} : ", number, name),
+            Message::PlayerTurnClock { number, name, left } => {
+                write!(f, "Joueur {} - {} (reste {}) : ", number, name, left)
+            }
+            Message::HowMany(moves) => write!(f, "Combien en retirer ({})", moves),
+            Message::MoveRange { min, max } => write!(f, "min={}, max={}", min, max),
+            Message::MoveSet(moves) => write!(f, "parmi {}", moves),
+            Message::WhichHeap(heaps) => write!(f, "Dans quel tas prendre (1-{})", heaps),
+            Message::WhichHeapOrBoth { heaps, both } => write!(
+                f,
+                "Dans quel tas prendre (1-{}, ou {} pour les deux)",
+                heaps, both
+            ),
+            Message::HowManyFromBoth => write!(f, "Combien en retirer des deux tas"),
+            Message::HowManyFromHeap(heap) => write!(f, "Combien en retirer du tas {}", heap),
+            Message::Removes { name, amount } => write!(f, "{} retire {}", name, amount),
+            Message::RemovesFromBoth { name, amount } => {
+                write!(f, "{} retire {} des deux tas", name, amount)
+            }
+            Message::RemovesFromHeap { name, amount, heap } => {
+                write!(f, "{} retire {} du tas {}", name, amount, heap)
+            }
+            Message::Won { name, turns } => {
+                write!(f, "Partie gagnée par {} en {} tours", name, turns)
+            }
+            Message::TeamWon {
+                team,
+                members,
+                turns,
+            } => write!(
+                f,
+                "Partie gagnée par l'équipe {} ({}) en {} tours",
+                team, members, turns
+            ),
+            Message::GamesTitle => write!(f, "Jeux :"),
+            Message::WhichGame(games) => write!(f, "Dans quel jeu jouer (1-{})", games),
+            Message::PossibleMoves(position) => write!(f, "Coups possibles dans {} :", position),
+            Message::WhichMove(options) => write!(f, "Quel coup (1-{})", options),
+            Message::TurnsGameInto { name, game, option } => {
+                write!(f, "{} change le jeu {} en {}", name, game, option)
+            }
+            Message::NoMoveInGames => write!(f, "Il n'y a aucun coup à jouer dans ces jeux"),
+            Message::SubtractionHeap { sticks, moves } => {
+                write!(f, "{} bâtons en prenant {}", sticks, moves)
+            }
+            Message::KaylesRow(pins) => write!(f, "Rangée de Kayles de {}", pins),
+            Message::GrundyHeap(sticks) => write!(f, "Tas de Grundy de {}", sticks),
+            Message::NothingLeft => write!(f, "rien"),
+            Message::GameSaved(path) => write!(f, "Partie sauvegardée dans {}", path),
+            Message::GameLoaded(path) => write!(f, "Partie chargée depuis {}", path),
+            Message::Undid { name, amount, heap } => write!(
+                f,
+                "Coup annulé : {} prenait {} du tas {}",
+                name, amount, heap
+            ),
+            Message::NothingToUndo => write!(f, "Il n'y a aucun coup à annuler"),
+            Message::NothingToRedo => write!(f, "Il n'y a aucun coup à rejouer"),
+            Message::ReplayOrQuit => write!(
+                f,
+                "Tapez replay pour revoir la partie coup par coup, ou appuyez sur entrée pour quitter :"
+            ),
+            Message::ReplayTitle => write!(f, "Revue de la partie"),
+            Message::PressEnter => write!(f, "Appuyez sur entrée pour le coup suivant"),
+            Message::ReplayTurn { turn, number, name } => {
+                write!(f, "Tour {} - Joueur {} - {} : ", turn, number, name)
+            }
+            Message::OutOfTimeForfeit(name) => {
+                write!(f, "{} n'a plus de temps et perd la partie par forfait", name)
+            }
+            Message::OutOfTimeRandom(name) => {
+                write!(f, "{} n'a plus de temps, un coup est joué au hasard", name)
+            }
+            Message::Forfeits(name) => write!(f, "{} perd la partie par forfait", name),
+            Message::WaitingForPlayer(address) => {
+                write!(f, "En attente de l'autre joueur sur {}", address)
+            }
+            Message::Joined { name, address } => write!(f, "{} a rejoint depuis {}", name, address),
+            Message::NoNetworkCommands => {
+                write!(f, "Les commandes ne sont pas disponibles en réseau")
+            }
+            Message::AskYourName => write!(f, "Veuillez entrer votre nom : "),
+            Message::Serving(address) => write!(f, "Parties servies sur http://{}", address),
+            Message::InvalidMove(error) => match error {
+                StickNumberError::RemainingNegative => {
+                    write!(f, "Prendre autant de bâtonnets mènerait à un nombre négatif")
+                }
+                StickNumberError::TakeOverMax => {
+                    write!(f, "Tentative de prendre plus que la limite maximale")
+                }
+                StickNumberError::TakeUnderMin => {
+                    write!(f, "Tentative de prendre moins que la limite minimale")
+                }
+                StickNumberError::DisallowedAmount => {
+                    write!(f, "Ce nombre ne fait pas partie des coups autorisés")
+                }
+                StickNumberError::ParsingError => {
+                    write!(f, "La valeur entrée n'est pas un nombre de bâtonnets valide")
+                }
+                StickNumberError::HeapOutOfRange => write!(f, "Il n'y a pas de tas avec ce numéro"),
+                StickNumberError::GameOver => write!(f, "La partie est déjà terminée"),
+            },
+            Message::Take(amount) => write!(f, "prendre {}", amount),
+            Message::TakeFromBoth(amount) => write!(f, "prendre {} des deux tas", amount),
+            Message::TakeFromHeap { amount, heap } => {
+                write!(f, "prendre {} du tas {}", amount, heap)
+            }
+            Message::MultipleOf(period) => write!(f, "un multiple de {}", period),
+            Message::MoreThanMultiple { offset, period } => {
+                write!(f, "{} de plus qu'un multiple de {}", offset, period)
+            }
+            Message::AnswerWith(period) => write!(
+                f,
+                "quoi que prenne l'autre joueur, répondre par {} moins ce nombre maintient cette position",
+                period
+            ),
+            Message::TakingPerTurn { allowed, losing } => write!(
+                f,
+                "en prenant {} par tour, le joueur qui doit jouer perd avec {} bâtonnets",
+                allowed, losing
+            ),
+            Message::MisereNimReason => write!(
+                f,
+                "en misère, dès qu'au plus un tas contient plus d'un bâtonnet, \
+                 le gagnant laisse un nombre impair de tas d'un seul bâtonnet"
+            ),
+            Message::MisereWythoffReason => write!(
+                f,
+                "en misère les dernières positions froides sont 0 et 1, puis 2 et 2"
+            ),
+            Message::WythoffReason => write!(
+                f,
+                "le plus petit tas vaut la différence multipliée par le nombre d'or, arrondie à l'inférieur"
+            ),
+            Message::HintLeave {
+                take,
+                left,
+                position,
+                reason,
+            } => write!(
+                f,
+                "Conseil : prendre {} pour laisser {} bâtonnets, {} : {}",
+                take, left, position, reason
+            ),
+            Message::HintLeaveLosing { take, left, reason } => write!(
+                f,
+                "Conseil : prendre {} pour laisser {} bâtonnets, une position perdante : {}",
+                take, left, reason
+            ),
+            Message::HintLeaveCold {
+                mv,
+                small,
+                large,
+                reason,
+            } => write!(
+                f,
+                "Conseil : {} pour laisser des tas de {} et {}, une position froide : {}",
+                mv, small, large, reason
+            ),
+            Message::HintMove { mv, reason } => write!(f, "Conseil : {} : {}", mv, reason),
+            Message::HintNimSum(mv) => write!(
+                f,
+                "Conseil : {} pour que la nim-somme, le XOR des tailles des tas, devienne 0 :",
+                mv
+            ),
+            Message::NoWinningMove {
+                remaining,
+                position,
+                reason,
+                take,
+            } => write!(
+                f,
+                "Conseil : aucun coup gagnant, {} bâtonnets est déjà {} : {}. Prenez {} et attendez une erreur",
+                remaining, position, reason, take
+            ),
+            Message::NoWinningMoveLosing { remaining, reason } => write!(
+                f,
+                "Conseil : aucun coup gagnant, {} bâtonnets est déjà une position perdante : {}",
+                remaining, reason
+            ),
+            Message::NoWinningMoveCold {
+                small,
+                large,
+                reason,
+            } => write!(
+                f,
+                "Conseil : aucun coup gagnant, des tas de {} et {} sont déjà une position froide : {}. \
+                 Prenez 1 et attendez une erreur",
+                small, large, reason
+            ),
+            Message::NoWinningMoveMisere(reason) => write!(
+                f,
+                "Conseil : aucun coup gagnant, {}. Prenez 1 et attendez une erreur",
+                reason
+            ),
+            Message::NoWinningMoveNimSum => write!(
+                f,
+                "Conseil : aucun coup gagnant, la nim-somme des tas vaut déjà 0 :"
+            ),
+            Message::NimSumKeeps => write!(
+                f,
+                "Tout coup depuis une nim-somme de 0 la rend non nulle, et vous pouvez toujours la ramener à 0"
+            ),
+            Message::NimSumBreaks => write!(
+                f,
+                "Tout coup la rend non nulle pour l'autre joueur. Prenez 1 et attendez une erreur"
+            ),
+            Message::HeapLabel(heap) => write!(f, "tas {}", heap),
+            Message::NimSumLabel => write!(f, "nim-somme"),
+            Message::CoachGood => write!(
+                f,
+                "Coach : bon coup, l'autre joueur n'a plus de coup gagnant"
+            ),
+            Message::CoachLastStick => write!(f, "Coach : bon coup, c'était le dernier bâtonnet"),
+            Message::CoachMistake(mv) => write!(
+                f,
+                "Coach : erreur, {} était gagnant. Tapez hint pour savoir pourquoi",
+                mv
+            ),
+            Message::CoachHopeless => write!(
+                f,
+                "Coach : aucun coup ne gagne d'ici contre un jeu parfait, faites durer la partie"
+            ),
+            Message::PressAnyKey(text) => {
+                write!(f, "{} - appuyez sur une touche pour quitter", text)
+            }
+            Message::Thinking { number, name } => {
+                write!(f, "Joueur {} - {} réfléchit...", number, name)
+            }
+            Message::ToMove { number, name } => write!(
+                f,
+                "Joueur {} - {} à jouer : {}/{} nombre, {}/{} tas, entrée pour jouer, q pour quitter",
+                number,
+                name,
+                Glyph::Left,
+                Glyph::Right,
+                Glyph::Up,
+                Glyph::Down
+            ),
+            Message::BothHeaps => write!(f, "les deux tas"),
+            Message::MovesTitle => write!(f, "Coups"),
+            Message::TranscriptWritten(path) => write!(f, "Transcription écrite dans {}", path),
+            Message::TranscriptFailed(e) => {
+                write!(f, "Impossible d'écrire la transcription : {}", e)
+            }
+            Message::LeaderboardOf(path) => write!(f, "Classement de {}", path),
+            Message::NoGamesRecorded => write!(f, "Aucune partie enregistrée pour l'instant"),
+            Message::LastGames => write!(f, "Dernières parties"),
+            Message::WonBy { names, turns } => {
+                write!(f, "gagnée par {} en {} tours", names, turns)
+            }
+            Message::TournamentOf {
+                strategies,
+                rounds,
+                seed,
+            } => write!(
+                f,
+                "Tournoi de {} stratégies, {} parties par paire, graine {}",
+                strategies, rounds, seed
+            ),
+            Message::WinRateMatrix => write!(
+                f,
+                "Taux de victoire de chaque ligne contre chaque colonne"
+            ),
+            Message::TrainingOn {
+                position,
+                convention,
+                episodes,
+                seed,
+            } => write!(
+                f,
+                "Apprentissage sur {}, jeu {}, {} parties contre soi-même, graine {}",
+                position, convention, episodes, seed
+            ),
+            Message::PositionsLearned {
+                positions,
+                episodes,
+                path,
+            } => write!(
+                f,
+                "{} positions apprises en {} parties, table écrite dans {}",
+                positions, episodes, path
+            ),
+            Message::SolverAgreement { share, positions } => write!(
+                f,
+                "Coup gagnant trouvé dans {:.0} % des {} positions gagnantes",
+                100.0 * share,
+                positions
+            ),
+            Message::TooManyToCompare => {
+                write!(f, "Trop de positions pour comparer avec le solveur")
+            }
+            Message::WonAgainstRandom { wins, games } => write!(
+                f,
+                "{} parties gagnées sur {} contre un joueur aléatoire",
+                wins, games
+            ),
+            Message::AnalysisOf {
+                position,
+                convention,
+            } => write!(f, "Analyse de {}, jeu {}", position, convention),
+            Message::PositionsReachable { positions, losing } => write!(
+                f,
+                "{} positions accessibles, {} P-positions où le joueur au trait perd",
+                positions, losing
+            ),
+            Message::FirstPlayerWins(length) => write!(
+                f,
+                "Le premier joueur gagne, en {} coups au plus contre la meilleure défense",
+                length
+            ),
+            Message::FirstPlayerLoses(length) => write!(
+                f,
+                "Le premier joueur perd contre un jeu parfait, en tenant {} coups au plus",
+                length
+            ),
+            Message::SolverAgrees => {
+                write!(f, "Le solveur du jeu est d'accord sur chaque position")
+            }
+            Message::SolverDisagrees(positions) => {
+                write!(f, "Le solveur du jeu n'est pas d'accord sur {}", positions)
+            }
+            Message::ColumnRank => write!(f, "Rang"),
+            Message::ColumnPlayer => write!(f, "Joueur"),
+            Message::ColumnStrategy => write!(f, "Stratégie"),
+            Message::ColumnGames => write!(f, "Parties"),
+            Message::ColumnWins => write!(f, "Victoires"),
+            Message::ColumnLosses => write!(f, "Défaites"),
+            Message::ColumnWinRate => write!(f, "% victoires"),
+            Message::ColumnAverageTurns => write!(f, "Tours moy."),
+            Message::ColumnStreak => write!(f, "Série"),
+            Message::ColumnBest => write!(f, "Record"),
+            Message::ColumnElo => write!(f, "Elo"),
+            Message::ColumnPosition => write!(f, "Position"),
+            Message::ColumnResult => write!(f, "Résultat"),
+            Message::ColumnLength => write!(f, "Durée"),
+            Message::ColumnWinningMoves => write!(f, "Coups gagnants"),
+            Message::ColumnWinningHeapMoves => write!(f, "Coups gagnants (tas:nombre)"),
+            Message::MinAboveMax { min, max } => {
+                write!(f, "--min ({}) ne doit pas dépasser --max ({})", min, max)
+            }
+            Message::SticksWithoutStick => write!(f, "--sticks doit contenir au moins un bâton"),
+            Message::WythoffSticks => {
+                write!(f, "--wythoff demande exactement deux tas dans --sticks")
+            }
+            Message::MovesSingleHeap => {
+                write!(f, "--moves ne s'applique qu'aux parties à un seul tas")
+            }
+            Message::TooFewNamedPlayers => write!(f, "--players doit nommer au moins deux joueurs"),
+            Message::TooFewStrategies => {
+                write!(f, "--strategies doit nommer au moins deux stratégies")
+            }
+            Message::TournamentSetup => write!(
+                f,
+                "les tournois choisissent eux-mêmes les joueurs, l'ordre du jeu et l'affichage"
+            ),
+            Message::NeedsSticks(command) => {
+                write!(f, "{} demande la position de départ dans --sticks", command)
+            }
+            Message::OnlyHeapsAndRules(command) => write!(
+                f,
+                "{} ne demande que les tas et les règles, pas de joueurs",
+                command
+            ),
+            Message::NoShuffleHosted => write!(
+                f,
+                "--shuffle n'est pas disponible dans les parties hébergées"
+            ),
+            Message::HostedNewGames => write!(
+                f,
+                "les parties hébergées sont de nouvelles parties entre l'hôte et le joueur \
+                 qui le rejoint, sans --players ni --resume"
+            ),
+            Message::ServeSetup => write!(
+                f,
+                "serve ne prend que les tas, les règles et les joueurs des nouvelles parties"
+            ),
+            Message::GamesLocalOnly => write!(
+                f,
+                "--games n'est disponible que dans les nouvelles parties locales sans --tui"
+            ),
+            Message::PenaltyNeedsClock => {
+                write!(f, "--timeout-penalty demande --move-time ou --time-bank")
+            }
+            Message::ClockLocalOnly => write!(
+                f,
+                "les contrôles du temps ne sont disponibles que dans les parties locales de Nim \
+                 sans --tui"
+            ),
+            Message::TranscriptLocalOnly => write!(
+                f,
+                "--transcript n'est disponible que dans les parties de Nim locales ou hébergées"
+            ),
+            Message::PositionNeeded => write!(f, "--sticks donne la position de départ"),
+            Message::TooManyPositions(positions) => write!(
+                f,
+                "plus de {} positions sont accessibles, essayez des tas plus petits",
+                positions
+            ),
+            Message::TooManyMoves(moves) => write!(
+                f,
+                "plus de {} coups depuis la position de départ, essayez des tas plus petits",
+                moves
+            ),
+            Message::UnknownStrategy { name, expected } => {
+                write!(f, "stratégie inconnue {}, attendue parmi {}", name, expected)
+            }
+            Message::StrategyTwice(name) => {
+                write!(f, "la stratégie {} est nommée deux fois", name)
+            }
+            Message::NoMoveToPlay => write!(f, "les tas ne laissent aucun coup à jouer"),
+            Message::SaveAccess(e) => {
+                write!(f, "Impossible d'accéder au fichier de sauvegarde : {}", e)
+            }
+            Message::SaveFormat(e) => write!(
+                f,
+                "Le fichier de sauvegarde n'est pas une partie valide : {}",
+                e
+            ),
+            Message::SaveInconsistent(reason) => {
+                write!(f, "La partie sauvegardée est incohérente : {}", reason)
+            }
+            Message::SavedPlayersMismatch => {
+                write!(f, "les joueurs ne correspondent pas à leurs noms")
+            }
+            Message::SavedUnknownPlayer => write!(f, "numéro de joueur inconnu"),
+            Message::SavedTeamsMismatch => {
+                write!(f, "les équipes ne correspondent pas aux joueurs")
+            }
+            Message::SavedInvalidHeap => write!(f, "tas invalide"),
+            Message::LearnedCannotPlay(e) => {
+                write!(f, "un joueur entraîné ne peut pas jouer : {}", e)
+            }
+            Message::ProfilesAccess(e) => {
+                write!(f, "Impossible d'accéder au fichier des profils : {}", e)
+            }
+            Message::ProfilesFormat(e) => {
+                write!(f, "Le fichier des profils n'est pas valide : {}", e)
+            }
+            Message::TableAccess(e) => {
+                write!(f, "Impossible d'accéder au fichier de la table : {}", e)
+            }
+            Message::TableFormat(e) => {
+                write!(f, "Le fichier de la table n'est pas une table valide : {}", e)
+            }
+            Message::TranscriptAccess(e) => {
+                write!(f, "Impossible d'accéder à la transcription : {}", e)
+            }
+            Message::TranscriptLine { line, reason } => {
+                write!(f, "Ligne {} de la transcription : {}", line, reason)
+            }
+            Message::TranscriptMissing(header) => {
+                write!(f, "La transcription n'a pas d'en-tête {}", header)
+            }
+            Message::TranscriptIllegalMove { number, error } => write!(
+                f,
+                "Le coup {} de la transcription est illégal : {}",
+                number, error
+            ),
+            Message::VariantNimOrWythoff => write!(f, "la variante est nim ou wythoff"),
+            Message::RangeNotation => write!(f, "un intervalle s'écrit MIN-MAX"),
+            Message::TeamsFromOne => write!(f, "les équipes sont comptées à partir de 1"),
+            Message::FirstFromOne => write!(f, "le premier joueur est compté à partir de 1"),
+            Message::PlayersInOrder => write!(
+                f,
+                "les joueurs sont nommés dans l'ordre, à partir de Player 1"
+            ),
+            Message::UnknownHeader(key) => write!(f, "en-tête inconnu {}", key),
+            Message::NoHeap => write!(f, "il faut au moins un tas"),
+            Message::WythoffAnyAmount => write!(
+                f,
+                "le jeu de Wythoff permet de prendre n'importe quel nombre"
+            ),
+            Message::RangeFromOne => write!(f, "l'intervalle doit commencer à 1 ou plus"),
+            Message::AmountsPositive => write!(f, "chaque nombre doit être positif"),
+            Message::MovesHeaderSingleHeap => write!(
+                f,
+                "les coups permis ne s'appliquent qu'aux parties à un seul tas"
+            ),
+            Message::NoSuchPlayer => write!(f, "ce joueur n'existe pas"),
+            Message::MoveNotation => write!(f, "un coup s'écrit TAS:NOMBRE"),
+            Message::MovesInOrder => {
+                write!(f, "les coups sont numérotés dans l'ordre à partir de 1")
+            }
+            Message::HeadersFirst => write!(f, "les en-têtes viennent avant les coups"),
+            Message::HeaderOrMove => write!(f, "un en-tête ou un coup numéroté est attendu"),
+            Message::ForfeitEnds => write!(f, "un abandon termine la partie"),
+            Message::OtherWinner => write!(f, "les coups ne finissent pas sur ce vainqueur"),
+            Message::PathNotFound(path) => write!(f, "chemin {} introuvable", path),
+            Message::GameNotFound(id) => write!(f, "partie {} introuvable", id),
+            Message::MethodNotAllowed => write!(f, "méthode non permise sur ce chemin"),
+            Message::InvalidJson(e) => write!(f, "corps JSON invalide : {}", e),
+            Message::NoComputerMove(name) => write!(f, "{} n'a choisi aucun coup", name),
+            Message::SticksToStart => {
+                write!(f, "il faut des bâtons pour commencer une partie")
+            }
+            Message::AtMostSticks(sticks) => {
+                write!(f, "une partie a au plus {} bâtons", sticks)
+            }
+            Message::LearnedOnCommandLine => write!(
+                f,
+                "les joueurs entraînés ne se donnent qu'en ligne de commande"
+            ),
+            Message::AtMostIterations(iterations) => write!(
+                f,
+                "la recherche arborescente est limitée à {} itérations",
+                iterations
+            ),
+            Message::AtMostPlayers(players) => {
+                write!(f, "une partie a au plus {} joueurs", players)
+            }
+            Message::MalformedRequestLine => write!(f, "ligne de requête mal formée"),
+            Message::MalformedLength => write!(f, "Content-Length mal formé"),
+            Message::BodyTooLarge => write!(f, "corps de requête trop grand"),
+            Message::BodyNotUtf8 => write!(f, "le corps de requête n'est pas en UTF-8"),
+            Message::RequestTimedOut => write!(f, "la requête n'a pas été reçue à temps"),
+            Message::Disconnected => write!(f, "l'autre joueur s'est déconnecté"),
+            Message::UnexpectedLine(line) => write!(f, "message inattendu : {}", line),
+        }
+    }
+}
+
+impl fmt::Display for Message<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match locale().language {
+            Language::English => self.english(f),
+            Language::French => self.french(f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_languages() {
+        assert_eq!("fr".parse::<Language>(), Ok(Language::French));
+        assert_eq!("fr_FR.UTF-8".parse::<Language>(), Ok(Language::French));
+        assert_eq!("English".parse::<Language>(), Ok(Language::English));
+        assert_eq!("en-GB".parse::<Language>(), Ok(Language::English));
+        assert!("C.UTF-8".parse::<Language>().is_err());
+        assert_eq!(Language::French.to_string(), "fr");
+    }
+
+    #[test]
+    fn test_locale_of_the_thread() {
+        let won = Message::Won {
+            name: "alice",
+            turns: 3,
+        };
+        assert_eq!(won.to_string(), "Game won by alice in 3 turns");
+        assert_eq!(format!("{}", Glyph::Victory), "✌️");
+
+        set_locale(Locale {
+            language: Language::French,
+            ascii: true,
+        });
+        assert_eq!(won.to_string(), "Partie gagnée par alice en 3 tours");
+        assert_eq!(
+            format!("{}  | {}", Glyph::Title, Message::Welcome),
+            "#  | Bienvenue dans le jeu de Nim !"
+        );
+        assert_eq!(
+            StickNumberError::TakeOverMax.to_string(),
+            "Tentative de prendre plus que la limite maximale"
+        );
+        assert!(Message::ToMove {
+            number: 1,
+            name: "alice"
+        }
+        .to_string()
+        .contains("</> nombre, ^/v tas"));
+
+        // Other threads keep their own locale
+        let other = std::thread::spawn(|| StickNumberError::GameOver.to_string());
+        assert_eq!(other.join().unwrap(), "The game is already over");
+        set_locale(Locale::default());
+    }
+}
@@ -0,0 +1,330 @@
+/*
+Line based protocol between a hosting and a joining process.
+
+The joining process says `HELLO <name>` once connected, the host then sends:
+    MSG <text>                 a line of text to show
+    TURN <heaps> [<moves>]     ask for a move, with the allowed moves of a single heap game
+                               or `both` when a move may take from both heaps
+    ERROR <text>               the last move was refused, a new TURN follows
+    END                        the game is over
+and the joining process answers each TURN with `MOVE <heap> <amount>`, heaps
+counted from 1. The host owns the game and validates every move.
+*/
+
+use crate::cli;
+use crate::core::{Game, Move, StickNumberError};
+use crate::messages::{Glyph, Message};
+use crate::player::{HumanPlayer, Player, PlayerKind};
+use crate::terminal::{self, InputError, Terminal, TurnInput};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+
+pub const DEFAULT_PORT: u16 = 7878;
+
+struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Connection {
+    fn new(stream: TcpStream) -> io::Result<Connection> {
+        Ok(Connection {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+        })
+    }
+
+    fn send_line(&mut self, line: &str) -> io::Result<()> {
+        writeln!(self.writer, "{}", line)?;
+        self.writer.flush()
+    }
+
+    /// Send text to show on the other side, one MSG per line
+    fn send_text(&mut self, text: &str) -> io::Result<()> {
+        for line in text.lines() {
+            writeln!(self.writer, "MSG {}", line)?;
+        }
+        self.writer.flush()
+    }
+
+    fn receive(&mut self) -> io::Result<String> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                Message::Disconnected.to_string(),
+            ));
+        }
+        Ok(String::from(line.trim_end_matches(['\n', '\r'])))
+    }
+
+    /// Ask the joining player for a move and read it back
+    fn ask_move(&mut self, game: &Game) -> io::Result<Result<Move, StickNumberError>> {
+        let heaps = game.heaps();
+        match (heaps.len(), heaps.get_heap(0)) {
+            (1, Some(sticks)) => self.send_line(&format!("TURN 1 {}", sticks.describe_moves()))?,
+            (number, _) if heaps.both_heaps().is_some() => {
+                self.send_line(&format!("TURN {} both", number))?
+            }
+            (number, _) => self.send_line(&format!("TURN {}", number))?,
+        }
+        let line = self.receive()?;
+        Ok(parse_move(&line))
+    }
+}
+
+/// Read a `MOVE <heap> <amount>` answer
+fn parse_move(line: &str) -> Result<Move, StickNumberError> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    match words[..] {
+        ["MOVE", heap, amount] => {
+            let heap = heap
+                .parse::<usize>()
+                .map_err(|_| StickNumberError::ParsingError)?;
+            let amount = amount
+                .parse::<u64>()
+                .map_err(|_| StickNumberError::ParsingError)?;
+            match heap.checked_sub(1) {
+                Some(heap) => Ok(Move { heap, amount }),
+                None => Err(StickNumberError::HeapOutOfRange),
+            }
+        }
+        _ => Err(StickNumberError::ParsingError),
+    }
+}
+
+fn protocol_error(line: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        Message::UnexpectedLine(line).to_string(),
+    )
+}
+
+/// Host a game, the local player moving first unless told otherwise, and return
+/// it once over
+pub fn host<R: BufRead, W: Write>(
+    terminal: &mut Terminal<R, W>,
+    config: &cli::Config,
+    listener: TcpListener,
+) -> Result<Game, InputError> {
+    terminal.write_text(&format!(
+        "{}  | {}\n{}\n",
+        Glyph::Title,
+        Message::Welcome,
+        "-".repeat(80)
+    ))?;
+    let local = terminal.read_player(0)?;
+
+    let local_address = listener.local_addr()?;
+    terminal.write_text(&format!(
+        "  | {}\n",
+        Message::WaitingForPlayer(&local_address)
+    ))?;
+    let (stream, address) = listener.accept()?;
+    let mut connection = Connection::new(stream)?;
+    let line = connection.receive()?;
+    let name = match line.strip_prefix("HELLO ") {
+        Some(name) => String::from(name.trim()),
+        None => return Err(protocol_error(&line).into()),
+    };
+    let joined = Message::Joined {
+        name: &name,
+        address: &address,
+    };
+    terminal.write_text(&format!("  | {}\n", joined))?;
+
+    let players: Vec<Box<dyn Player>> = vec![local, Box::new(HumanPlayer::new(name))];
+    let (mut game, mut players) = terminal.setup_game(config, players)?;
+    let local_is_human = players[0].kind() == PlayerKind::Human;
+
+    let heaps_text = terminal::format_heaps(&game);
+    terminal.write_text(&heaps_text)?;
+    connection.send_text(&heaps_text)?;
+
+    while !game.is_over() {
+        let curr_player = game.current_player();
+        let turn = Message::PlayerTurn {
+            number: curr_player + 1,
+            name: &game.player_names()[curr_player],
+        };
+        let header = format!("{} | {}\n", Glyph::Player, turn);
+        terminal.write_text(&header)?;
+        connection.send_text(&header)?;
+
+        let mv = if curr_player == 0 {
+            match players[0].choose_move(&game) {
+                Some(mv) => Ok(mv),
+                None => match terminal.read_move(game.heaps())? {
+                    TurnInput::Move(mv) => mv,
+                    _ => {
+                        let error = Message::Error(&Message::NoNetworkCommands);
+                        terminal.write_text(&format!("{}  | {}\n", Glyph::Error, error))?;
+                        continue;
+                    }
+                },
+            }
+        } else {
+            connection.ask_move(&game)?
+        };
+
+        match mv.and_then(|mv| game.apply_move(mv)) {
+            Ok(events) => {
+                if let Some(turn) = game.history().last() {
+                    // Whoever typed the move does not need to see it again
+                    let turn_text = terminal::format_turn(&game, turn);
+                    if curr_player == 1 || !local_is_human {
+                        terminal.write_text(&turn_text)?;
+                    }
+                    if curr_player == 0 {
+                        connection.send_text(&turn_text)?;
+                    }
+                }
+                for event in events.iter() {
+                    let event_text = terminal::format_event(&game, event);
+                    terminal.write_text(&event_text)?;
+                    connection.send_text(&event_text)?;
+                }
+            }
+            Err(e) if curr_player == 0 => {
+                terminal.write_text(&format!("{}  | {}\n", Glyph::Error, Message::Error(&e)))?
+            }
+            Err(e) => connection.send_line(&format!("ERROR {}", e))?,
+        }
+    }
+    connection.send_line("END")?;
+    Ok(game)
+}
+
+/// Join a game hosted by another process, the host checking every move
+pub fn join<R: BufRead, W: Write>(
+    terminal: &mut Terminal<R, W>,
+    stream: TcpStream,
+) -> Result<(), InputError> {
+    let mut connection = Connection::new(stream)?;
+    terminal.write_text(&format!("{}  | {}\n", Glyph::Prompt, Message::AskYourName))?;
+    let name = terminal.read_string()?;
+    connection.send_line(&format!("HELLO {}", name))?;
+
+    loop {
+        let line = connection.receive()?;
+        let (kind, rest) = line.split_once(' ').unwrap_or((&line, ""));
+        match kind {
+            "MSG" => terminal.write_text(&format!("{}\n", rest))?,
+            "ERROR" => {
+                terminal.write_text(&format!("{}  | {}\n", Glyph::Error, Message::Error(&rest)))?
+            }
+            "END" => return Ok(()),
+            "TURN" => {
+                let (heap, amount) = match rest.split_once(' ') {
+                    Some(("1", moves)) => {
+                        terminal.write_text(&format!(
+                            "{}  | {}\n",
+                            Glyph::Prompt,
+                            Message::HowMany(moves)
+                        ))?;
+                        (String::from("1"), terminal.read_string()?)
+                    }
+                    Some((number, "both")) => {
+                        let heaps = number.parse::<usize>().map_err(|_| protocol_error(&line))?;
+                        let which = Message::WhichHeapOrBoth {
+                            heaps,
+                            both: heaps + 1,
+                        };
+                        terminal.write_text(&format!("{}  | {}\n", Glyph::Prompt, which))?;
+                        let heap = terminal.read_string()?;
+                        let how_many = if heap.trim() == (heaps + 1).to_string() {
+                            Message::HowManyFromBoth
+                        } else {
+                            Message::HowManyFromHeap(&heap.trim())
+                        };
+                        terminal.write_text(&format!("{}  | {}\n", Glyph::Prompt, how_many))?;
+                        (heap, terminal.read_string()?)
+                    }
+                    None if !rest.is_empty() => {
+                        let heaps = rest.parse::<usize>().map_err(|_| protocol_error(&line))?;
+                        terminal.write_text(&format!(
+                            "{}  | {}\n",
+                            Glyph::Prompt,
+                            Message::WhichHeap(heaps)
+                        ))?;
+                        let heap = terminal.read_string()?;
+                        let how_many = Message::HowManyFromHeap(&heap.trim());
+                        terminal.write_text(&format!("{}  | {}\n", Glyph::Prompt, how_many))?;
+                        (heap, terminal.read_string()?)
+                    }
+                    _ => return Err(protocol_error(&line).into()),
+                };
+                connection.send_line(&format!("MOVE {} {}", heap.trim(), amount.trim()))?;
+            }
+            _ => return Err(protocol_error(&line).into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_parse_move() {
+        assert_eq!(parse_move("MOVE 2 3"), Ok(Move { heap: 1, amount: 3 }));
+        assert_eq!(
+            parse_move("MOVE 0 3"),
+            Err(StickNumberError::HeapOutOfRange)
+        );
+        assert_eq!(
+            parse_move("MOVE 1 lots"),
+            Err(StickNumberError::ParsingError)
+        );
+        assert_eq!(parse_move("HELLO"), Err(StickNumberError::ParsingError));
+    }
+
+    #[test]
+    fn test_game_over_loopback() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let client = thread::spawn(move || {
+            let mut output = Vec::new();
+            let mut terminal = Terminal::new("bob\n4\n3\n".as_bytes(), &mut output);
+            let result = join(&mut terminal, TcpStream::connect(address).unwrap());
+            (result, String::from_utf8(output).unwrap())
+        });
+
+        let config = cli::Config {
+            sticks: Some(vec![5]),
+            min_remove: 1,
+            max_remove: 3,
+            moves: None,
+            players: None,
+            convention: Some(crate::core::Convention::Normal),
+            first: None,
+            resume: None,
+            teams: None,
+            shuffle: false,
+            tui: false,
+            coach: false,
+            wythoff: false,
+            games: None,
+            profiles: None,
+            time_control: None,
+            transcript: None,
+            mode: cli::Mode::Local,
+        };
+        let mut output = Vec::new();
+        let mut terminal = Terminal::new("human\nalice\n2\n".as_bytes(), &mut output);
+        let result = host(&mut terminal, &config, listener);
+        let host_output = String::from_utf8(output).unwrap();
+        let (client_result, client_output) = client.join().unwrap();
+
+        assert!(result.is_ok());
+        assert!(client_result.is_ok());
+        assert!(host_output.contains("  | bob joined from 127.0.0.1"));
+        assert!(host_output.contains("  | bob removes 3"));
+        assert!(host_output.ends_with("✌️  | Game won by bob in 2 turns\n"));
+        assert!(client_output.contains("  | alice removes 2"));
+        assert!(client_output.contains("  | Error: Trying to take over the maximum limit"));
+        assert!(client_output.ends_with("✌️  | Game won by bob in 2 turns\n"));
+    }
+}
@@ -0,0 +1,399 @@
+use crate::core::{Game, Heaps, Move, StickNumber};
+use crate::grundy::{GameSum, GrundyCache, ImpartialGame, Position};
+use crate::learn::{LearnError, LearnedPlayer};
+use crate::mcts::MctsPlayer;
+use crate::messages::Message;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::PathBuf;
+
+/// Probability that an easy computer plays the optimal move instead of a random one
+const EASY_OPTIMAL_PROBABILITY: f64 = 0.25;
+/// Probability that a medium computer plays a greedy move instead of the optimal one
+const MEDIUM_GREEDY_PROBABILITY: f64 = 0.35;
+
+/// What a player is, enough to rebuild it from a saved game
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub enum PlayerKind {
+    Human,
+    Computer(Difficulty),
+    /// Tree search player, with its number of iterations per move
+    Mcts(u32),
+    /// Player of a table learned by self-play, with the file of the table
+    Learned(PathBuf),
+}
+
+pub trait Player {
+    fn name(&self) -> &str;
+
+    fn kind(&self) -> PlayerKind;
+
+    /// Choose the next move, human players return `None` to let the front-end ask for it
+    fn choose_move(&mut self, game: &Game) -> Option<Move>;
+
+    /// Choose a component of a sum of games and one of its options, like `choose_move`
+    fn choose_sum_move(
+        &mut self,
+        sum: &GameSum<Position>,
+        cache: &mut GrundyCache<Position>,
+    ) -> Option<(usize, usize)>;
+
+    /// Why the player cannot play `game`, any game being fine by default
+    fn check_game(&self, _game: &Game) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+pub struct HumanPlayer {
+    name: String,
+}
+
+impl HumanPlayer {
+    pub fn new(name: String) -> HumanPlayer {
+        HumanPlayer { name }
+    }
+}
+
+impl Player for HumanPlayer {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn kind(&self) -> PlayerKind {
+        PlayerKind::Human
+    }
+
+    fn choose_move(&mut self, _: &Game) -> Option<Move> {
+        None
+    }
+
+    fn choose_sum_move(
+        &mut self,
+        _: &GameSum<Position>,
+        _: &mut GrundyCache<Position>,
+    ) -> Option<(usize, usize)> {
+        None
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl std::str::FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "easy" => Ok(Difficulty::Easy),
+            "medium" => Ok(Difficulty::Medium),
+            "hard" => Ok(Difficulty::Hard),
+            other => Err(Message::UnknownPlayerType(other).to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Difficulty::Easy => write!(f, "easy"),
+            Difficulty::Medium => write!(f, "medium"),
+            Difficulty::Hard => write!(f, "hard"),
+        }
+    }
+}
+
+pub struct ComputerPlayer {
+    name: String,
+    difficulty: Difficulty,
+    rng: StdRng,
+}
+
+impl ComputerPlayer {
+    pub fn new(difficulty: Difficulty) -> ComputerPlayer {
+        ComputerPlayer::with_rng(difficulty, StdRng::from_entropy())
+    }
+
+    pub fn with_seed(difficulty: Difficulty, seed: u64) -> ComputerPlayer {
+        ComputerPlayer::with_rng(difficulty, StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(difficulty: Difficulty, rng: StdRng) -> ComputerPlayer {
+        ComputerPlayer {
+            name: format!("Computer ({})", difficulty),
+            difficulty,
+            rng,
+        }
+    }
+}
+
+/// Rebuild a player, the name only being used by human players
+pub fn from_kind(name: &str, kind: &PlayerKind) -> Result<Box<dyn Player>, LearnError> {
+    Ok(match kind {
+        PlayerKind::Human => Box::new(HumanPlayer::new(String::from(name))),
+        PlayerKind::Computer(difficulty) => Box::new(ComputerPlayer::new(*difficulty)),
+        PlayerKind::Mcts(iterations) => Box::new(MctsPlayer::new(*iterations)),
+        PlayerKind::Learned(path) => Box::new(LearnedPlayer::load(path)?),
+    })
+}
+
+/// Any legal removal from a heap
+pub fn random_amount<G: Rng>(rng: &mut G, sticks: &StickNumber) -> u64 {
+    if sticks.get_moves().is_some() {
+        let moves = sticks.legal_moves();
+        if !moves.is_empty() {
+            return moves[rng.gen_range(0..moves.len())];
+        }
+    }
+    match (sticks.next_move(0), sticks.largest_move()) {
+        (Some(smallest), Some(largest)) => rng.gen_range(smallest..=largest),
+        _ => sticks.get_min_remove(),
+    }
+}
+
+/// Any legal move, picking the heap first
+pub fn random_move<G: Rng>(rng: &mut G, heaps: &Heaps) -> (usize, u64) {
+    let candidates: Vec<(usize, &StickNumber)> = heaps.playable().collect();
+    if candidates.is_empty() {
+        return (0, 1);
+    }
+    // Taking from both heaps counts as one more choice of heap
+    if let Some(both) = heaps.both_heaps() {
+        if candidates.len() > 1 && rng.gen_range(0..=candidates.len()) == 0 {
+            let most = candidates
+                .iter()
+                .map(|(_, heap)| heap.get_remaining())
+                .min()
+                .unwrap_or(1);
+            return (both, rng.gen_range(1..=most));
+        }
+    }
+    let (index, heap) = candidates[rng.gen_range(0..candidates.len())];
+    (index, random_amount(rng, heap))
+}
+
+/// Any option of any component of a sum that is not over yet
+pub fn random_sum_move<G: Rng>(rng: &mut G, sum: &GameSum<Position>) -> (usize, usize) {
+    let candidates: Vec<(usize, usize)> = sum
+        .components()
+        .iter()
+        .enumerate()
+        .map(|(index, component)| (index, component.options().len()))
+        .filter(|&(_, options)| options > 0)
+        .collect();
+    if candidates.is_empty() {
+        return (0, 0);
+    }
+    let (index, options) = candidates[rng.gen_range(0..candidates.len())];
+    (index, rng.gen_range(0..options))
+}
+
+/// Largest legal removal from a heap
+pub fn greedy_amount(sticks: &StickNumber) -> u64 {
+    sticks.largest_move().unwrap_or(sticks.get_min_remove())
+}
+
+/// Take as much as possible from the largest heap
+pub fn greedy_move(heaps: &Heaps) -> (usize, u64) {
+    heaps
+        .playable()
+        .max_by_key(|(_, heap)| heap.get_remaining())
+        .map(|(index, heap)| (index, greedy_amount(heap)))
+        .unwrap_or((0, 1))
+}
+
+impl Player for ComputerPlayer {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn kind(&self) -> PlayerKind {
+        PlayerKind::Computer(self.difficulty)
+    }
+
+    fn choose_move(&mut self, game: &Game) -> Option<Move> {
+        let heaps = game.heaps();
+        let optimal = heaps.winning_move(game.rules());
+        let (heap, amount) = match self.difficulty {
+            Difficulty::Easy => match optimal {
+                Some(mv) if self.rng.gen_bool(EASY_OPTIMAL_PROBABILITY) => mv,
+                _ => random_move(&mut self.rng, heaps),
+            },
+            Difficulty::Medium => match optimal {
+                Some(mv) if !self.rng.gen_bool(MEDIUM_GREEDY_PROBABILITY) => mv,
+                _ => greedy_move(heaps),
+            },
+            Difficulty::Hard => match optimal {
+                Some(mv) => mv,
+                None => random_move(&mut self.rng, heaps),
+            },
+        };
+        Some(Move { heap, amount })
+    }
+
+    /// Sums have no greedy move, a medium computer plays randomly instead
+    fn choose_sum_move(
+        &mut self,
+        sum: &GameSum<Position>,
+        cache: &mut GrundyCache<Position>,
+    ) -> Option<(usize, usize)> {
+        let optimal = sum.winning_move(cache);
+        let mv = match (self.difficulty, optimal) {
+            (Difficulty::Easy, Some(mv)) if self.rng.gen_bool(EASY_OPTIMAL_PROBABILITY) => mv,
+            (Difficulty::Medium, Some(mv)) if !self.rng.gen_bool(MEDIUM_GREEDY_PROBABILITY) => mv,
+            (Difficulty::Hard, Some(mv)) => mv,
+            _ => random_sum_move(&mut self.rng, sum),
+        };
+        Some(mv)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{Convention, Rules};
+
+    const MISERE: Rules = Rules {
+        convention: Convention::Misere,
+    };
+    const NORMAL: Rules = Rules {
+        convention: Convention::Normal,
+    };
+
+    fn new_game(heaps: Heaps, rules: Rules) -> Game {
+        let names = vec![String::from("one"), String::from("two")];
+        Game::new(heaps, rules, names, 0)
+    }
+
+    #[test]
+    fn test_greedy_amount_bounded_by_remaining() {
+        let sticks = StickNumber::new(2, 1, 3);
+        assert_eq!(greedy_amount(&sticks), 2);
+        let sticks = StickNumber::new(20, 1, 3);
+        assert_eq!(greedy_amount(&sticks), 3);
+        let sticks = StickNumber::with_moves(3, &[1, 4]);
+        assert_eq!(greedy_amount(&sticks), 1);
+    }
+
+    #[test]
+    fn test_human_leaves_move_to_front_end() {
+        let mut human = HumanPlayer::new(String::from("alice"));
+        let game = new_game(Heaps::new(&[3, 4]), NORMAL);
+        assert_eq!(human.choose_move(&game), None);
+        assert_eq!(human.name(), "alice");
+    }
+
+    #[test]
+    fn test_computer_moves_are_legal() {
+        for difficulty in [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard] {
+            let mut computer = ComputerPlayer::with_seed(difficulty, 42);
+            let mut game = new_game(Heaps::single(StickNumber::new(30, 1, 3)), MISERE);
+            while !game.is_over() {
+                let mv = computer.choose_move(&game).unwrap();
+                assert!(game.apply_move(mv).is_ok());
+            }
+            let mut game = new_game(
+                Heaps::single(StickNumber::with_moves(30, &[1, 3, 4])),
+                NORMAL,
+            );
+            while !game.is_over() {
+                let mv = computer.choose_move(&game).unwrap();
+                assert!(game.apply_move(mv).is_ok());
+            }
+            let mut game = new_game(Heaps::new(&[3, 5, 7]), NORMAL);
+            while !game.is_over() {
+                let mv = computer.choose_move(&game).unwrap();
+                assert!(game.apply_move(mv).is_ok());
+            }
+            let mut game = new_game(Heaps::wythoff(12, 20), MISERE);
+            while !game.is_over() {
+                let mv = computer.choose_move(&game).unwrap();
+                assert!(game.apply_move(mv).is_ok());
+            }
+        }
+    }
+
+    #[test]
+    fn test_hard_computer_wins_from_winning_position() {
+        for (rules, heaps) in [
+            (MISERE, Heaps::single(StickNumber::new(20, 1, 3))),
+            (NORMAL, Heaps::single(StickNumber::new(21, 1, 3))),
+            (
+                NORMAL,
+                Heaps::single(StickNumber::with_moves(20, &[1, 3, 4])),
+            ),
+            (
+                MISERE,
+                Heaps::single(StickNumber::with_moves(20, &[2, 3, 7])),
+            ),
+            (NORMAL, Heaps::new(&[3, 4, 5])),
+            (MISERE, Heaps::new(&[3, 4, 5])),
+            (NORMAL, Heaps::wythoff(9, 14)),
+            (MISERE, Heaps::wythoff(9, 14)),
+        ] {
+            let mut hard = ComputerPlayer::with_seed(Difficulty::Hard, 0);
+            let mut easy = ComputerPlayer::with_seed(Difficulty::Easy, 0);
+            let mut game = new_game(heaps, rules);
+            while !game.is_over() {
+                let mv = if game.current_player() == 0 {
+                    hard.choose_move(&game)
+                } else {
+                    easy.choose_move(&game)
+                };
+                game.apply_move(mv.unwrap()).unwrap();
+            }
+            assert_eq!(game.winner(), Some(0));
+        }
+    }
+
+    #[test]
+    fn test_greedy_move_empties_largest_heap() {
+        assert_eq!(greedy_move(&Heaps::new(&[1, 4, 5])), (2, 5));
+        assert_eq!(
+            greedy_move(&Heaps::single(StickNumber::new(9, 1, 3))),
+            (0, 3)
+        );
+    }
+
+    #[test]
+    fn test_hard_computer_wins_sum_of_games() {
+        let mut cache = GrundyCache::new();
+        let mut hard = ComputerPlayer::with_seed(Difficulty::Hard, 0);
+        let mut easy = ComputerPlayer::with_seed(Difficulty::Easy, 0);
+        let mut sum = GameSum::new(vec![
+            Position::Kayles(5),
+            Position::Grundy(7),
+            Position::Subtraction {
+                sticks: 6,
+                moves: vec![1, 3, 4],
+            },
+        ]);
+        let mut curr_player = 0;
+        while !sum.is_over() {
+            let player: &mut ComputerPlayer = if curr_player == 0 {
+                &mut hard
+            } else {
+                &mut easy
+            };
+            let (component, option) = player.choose_sum_move(&sum, &mut cache).unwrap();
+            sum.play(component, option).unwrap();
+            curr_player = (curr_player + 1) % 2;
+        }
+        // The player left without a move loses
+        assert_eq!(curr_player, 1);
+        assert_eq!(
+            HumanPlayer::new(String::from("alice")).choose_sum_move(&sum, &mut cache),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_difficulty() {
+        assert_eq!("Hard".parse::<Difficulty>(), Ok(Difficulty::Hard));
+        assert!("impossible".parse::<Difficulty>().is_err());
+    }
+}
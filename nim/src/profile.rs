@@ -0,0 +1,423 @@
+use crate::cli;
+use crate::core::Game;
+use crate::messages::{Glyph, Message};
+use crate::terminal;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{fmt, fs};
+
+/// Name of the data file kept in the home directory
+const DATA_FILE: &str = ".nim_profiles.json";
+/// Games listed under the leaderboard unless told otherwise
+pub const DEFAULT_RECENT: usize = 5;
+
+/// Results of a player over every recorded game
+#[derive(PartialEq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    pub wins: u32,
+    pub losses: u32,
+    /// Turns of all the games played, for the average
+    pub turns: u32,
+    /// Games won in a row up to the last one
+    pub streak: u32,
+    pub best_streak: u32,
+}
+
+impl Profile {
+    fn new(name: &str) -> Profile {
+        Profile {
+            name: String::from(name),
+            ..Profile::default()
+        }
+    }
+
+    pub fn games(&self) -> u32 {
+        self.wins + self.losses
+    }
+
+    pub fn win_rate(&self) -> f64 {
+        match self.games() {
+            0 => 0.0,
+            games => self.wins as f64 / games as f64,
+        }
+    }
+
+    pub fn average_turns(&self) -> f64 {
+        match self.games() {
+            0 => 0.0,
+            games => self.turns as f64 / games as f64,
+        }
+    }
+}
+
+/// A finished game, kept for the history
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub struct GameRecord {
+    /// Seconds since the Unix epoch
+    pub played_at: u64,
+    pub players: Vec<String>,
+    pub winners: Vec<String>,
+    pub turns: u32,
+}
+
+/// Content of the data file, written as JSON
+#[derive(PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct Profiles {
+    #[serde(default)]
+    profiles: Vec<Profile>,
+    #[serde(default)]
+    games: Vec<GameRecord>,
+}
+
+impl Profiles {
+    #[cfg(test)]
+    pub fn get(&self, name: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|profile| profile.name == name)
+    }
+
+    fn get_mut(&mut self, name: &str) -> &mut Profile {
+        match self
+            .profiles
+            .iter()
+            .position(|profile| profile.name == name)
+        {
+            Some(index) => &mut self.profiles[index],
+            None => {
+                self.profiles.push(Profile::new(name));
+                self.profiles.last_mut().unwrap()
+            }
+        }
+    }
+
+    /// Add a finished game, every player of the winning team winning it.
+    /// Returns `false` for a game that is not over.
+    pub fn record(&mut self, game: &Game, played_at: u64) -> bool {
+        let winner = match game.winner() {
+            Some(winner) => winner,
+            None => return false,
+        };
+        let teams = game.teams();
+        let turns = game.history().len() as u32;
+        let mut players: Vec<String> = Vec::new();
+        let mut winners: Vec<String> = Vec::new();
+        for (index, name) in game.player_names().iter().enumerate() {
+            if teams[index] == teams[winner] && !winners.contains(name) {
+                winners.push(name.clone());
+            }
+            if !players.contains(name) {
+                players.push(name.clone());
+            }
+        }
+
+        // A name seated twice counts once, as a win if any of its seats won
+        for name in players.iter() {
+            let profile = self.get_mut(name);
+            profile.turns += turns;
+            if winners.contains(name) {
+                profile.wins += 1;
+                profile.streak += 1;
+                profile.best_streak = profile.best_streak.max(profile.streak);
+            } else {
+                profile.losses += 1;
+                profile.streak = 0;
+            }
+        }
+        self.games.push(GameRecord {
+            played_at,
+            players,
+            winners,
+            turns,
+        });
+        true
+    }
+
+    /// Most wins first, then the best win rate
+    pub fn leaderboard(&self) -> Vec<&Profile> {
+        let mut leaderboard: Vec<&Profile> = self.profiles.iter().collect();
+        leaderboard.sort_by(|a, b| {
+            b.wins
+                .cmp(&a.wins)
+                .then(b.win_rate().total_cmp(&a.win_rate()))
+                .then(a.name.cmp(&b.name))
+        });
+        leaderboard
+    }
+
+    /// The last games recorded, oldest first
+    pub fn recent(&self, number: usize) -> &[GameRecord] {
+        &self.games[self.games.len().saturating_sub(number)..]
+    }
+}
+
+#[derive(Debug)]
+pub enum ProfileError {
+    Io(io::Error),
+    Format(serde_json::Error),
+}
+
+impl fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProfileError::Io(e) => write!(f, "{}", Message::ProfilesAccess(e)),
+            ProfileError::Format(e) => write!(f, "{}", Message::ProfilesFormat(e)),
+        }
+    }
+}
+
+impl From<io::Error> for ProfileError {
+    fn from(e: io::Error) -> Self {
+        ProfileError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for ProfileError {
+    fn from(e: serde_json::Error) -> Self {
+        ProfileError::Format(e)
+    }
+}
+
+/// The data file in the home directory, or the current one without a home
+pub fn default_path() -> PathBuf {
+    std::env::var_os("HOME")
+        .map(PathBuf::from)
+        .unwrap_or_default()
+        .join(DATA_FILE)
+}
+
+/// Read the profiles, none having been recorded before the file exists
+pub fn load(path: &Path) -> Result<Profiles, ProfileError> {
+    match fs::read_to_string(path) {
+        Ok(text) => Ok(serde_json::from_str(&text)?),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Profiles::default()),
+        Err(e) => Err(ProfileError::Io(e)),
+    }
+}
+
+pub fn save(path: &Path, profiles: &Profiles) -> Result<(), ProfileError> {
+    fs::write(path, serde_json::to_string_pretty(profiles)?)?;
+    Ok(())
+}
+
+/// Add a finished game to the profiles stored at `path`
+pub fn record_game(path: &Path, game: &Game) -> Result<(), ProfileError> {
+    let mut profiles = load(path)?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    if profiles.record(game, now) {
+        save(path, &profiles)?;
+    }
+    Ok(())
+}
+
+/// Date of a Unix timestamp as YYYY-MM-DD, in UTC
+fn format_date(seconds: u64) -> String {
+    // Days to civil date, counting in 400 year eras starting in March
+    let days = (seconds / 86400) as i64 + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Leaderboard followed by the last games
+pub fn format_stats(profiles: &Profiles, recent: usize) -> String {
+    let leaderboard = profiles.leaderboard();
+    if leaderboard.is_empty() {
+        return format!("  | {}\n", Message::NoGamesRecorded);
+    }
+    let headers = [
+        Message::ColumnRank,
+        Message::ColumnPlayer,
+        Message::ColumnGames,
+        Message::ColumnWins,
+        Message::ColumnLosses,
+        Message::ColumnWinRate,
+        Message::ColumnAverageTurns,
+        Message::ColumnStreak,
+        Message::ColumnBest,
+    ];
+    let mut rows = vec![headers.iter().map(|header| header.to_string()).collect()];
+    for (rank, profile) in leaderboard.iter().enumerate() {
+        rows.push(vec![
+            (rank + 1).to_string(),
+            profile.name.clone(),
+            profile.games().to_string(),
+            profile.wins.to_string(),
+            profile.losses.to_string(),
+            format!("{:.1}%", 100.0 * profile.win_rate()),
+            format!("{:.1}", profile.average_turns()),
+            profile.streak.to_string(),
+            profile.best_streak.to_string(),
+        ]);
+    }
+    let mut text = terminal::format_table(&rows, &[4, 0, 5, 5, 6, 8, 9, 6, 4], 1);
+    let games = profiles.recent(recent);
+    if !games.is_empty() {
+        text.push_str(&format!("  |\n  | {}\n", Message::LastGames));
+        for record in games.iter().rev() {
+            let won = Message::WonBy {
+                names: &record.winners.join(", "),
+                turns: record.turns,
+            };
+            text.push_str(&format!(
+                "  | {}  {} - {}\n",
+                format_date(record.played_at),
+                record.players.join(", "),
+                won
+            ));
+        }
+    }
+    text
+}
+
+/// Print the leaderboard of the profiles file given on the command line
+pub fn show_stats<W: Write>(config: &cli::Config, output: &mut W) -> io::Result<()> {
+    let recent = match config.mode {
+        cli::Mode::Stats { recent } => recent,
+        _ => DEFAULT_RECENT,
+    };
+    let path = config.profiles.clone().unwrap_or_else(default_path);
+    let profiles =
+        load(&path).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+    writeln!(
+        output,
+        "{}  | {}\n{}",
+        Glyph::Title,
+        Message::LeaderboardOf(&path.display()),
+        "-".repeat(80)
+    )?;
+    write!(output, "{}", format_stats(&profiles, recent))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{Heaps, Move, Rules, StickNumber};
+    use crate::messages;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("nim_{}_{}.json", name, std::process::id()))
+    }
+
+    /// A one move game on a single stick won by `winner`
+    fn won_game(names: &[&str], winner: usize) -> Game {
+        let names = names.iter().map(|name| String::from(*name)).collect();
+        let mut game = Game::new(
+            Heaps::single(StickNumber::new(1, 1, 1)),
+            Rules::default(),
+            names,
+            winner,
+        );
+        game.apply_move(Move { heap: 0, amount: 1 }).unwrap();
+        game
+    }
+
+    #[test]
+    fn test_record_wins_losses_and_streaks() {
+        let mut profiles = Profiles::default();
+        assert!(profiles.record(&won_game(&["alice", "bob"], 0), 10));
+        assert!(profiles.record(&won_game(&["alice", "bob"], 0), 20));
+        assert!(profiles.record(&won_game(&["bob", "alice"], 0), 30));
+        assert!(profiles.record(&won_game(&["alice", "carol"], 0), 40));
+
+        let alice = profiles.get("alice").unwrap();
+        assert_eq!((alice.wins, alice.losses), (3, 1));
+        assert_eq!((alice.streak, alice.best_streak), (1, 2));
+        assert_eq!(alice.average_turns(), 1.0);
+        let bob = profiles.get("bob").unwrap();
+        assert_eq!((bob.wins, bob.losses, bob.streak), (1, 2, 1));
+
+        let names: Vec<&str> = profiles
+            .leaderboard()
+            .iter()
+            .map(|profile| profile.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["alice", "bob", "carol"]);
+        assert_eq!(profiles.recent(2)[0].played_at, 30);
+        assert_eq!(profiles.recent(10).len(), 4);
+    }
+
+    #[test]
+    fn test_record_teams_and_unfinished_games() {
+        let mut profiles = Profiles::default();
+        let game = Game::new(
+            Heaps::new(&[3]),
+            Rules::default(),
+            vec![String::from("alice"), String::from("bob")],
+            0,
+        );
+        assert!(!profiles.record(&game, 0));
+        assert_eq!(profiles, Profiles::default());
+
+        let game = won_game(&["alice", "bob", "carol", "dave"], 2).with_teams(vec![0, 1, 0, 1]);
+        assert!(profiles.record(&game, 0));
+        assert_eq!(profiles.recent(1)[0].winners, vec!["alice", "carol"]);
+        assert_eq!(profiles.get("dave").unwrap().losses, 1);
+    }
+
+    #[test]
+    fn test_record_game_to_file() {
+        let path = temp_path("profiles");
+        let _ = fs::remove_file(&path);
+        assert_eq!(load(&path).unwrap(), Profiles::default());
+        record_game(&path, &won_game(&["alice", "bob"], 1)).unwrap();
+        record_game(&path, &won_game(&["alice", "bob"], 1)).unwrap();
+        let profiles = load(&path).unwrap();
+        assert_eq!(profiles.get("bob").unwrap().best_streak, 2);
+
+        fs::write(&path, "not json").unwrap();
+        assert!(matches!(load(&path), Err(ProfileError::Format(_))));
+        messages::set_locale(messages::Locale {
+            language: messages::Language::French,
+            ascii: false,
+        });
+        let error = load(&path).unwrap_err().to_string();
+        messages::set_locale(messages::Locale::default());
+        assert!(error.starts_with("Le fichier des profils n'est pas valide : "));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_format_stats() {
+        let mut profiles = Profiles::default();
+        assert_eq!(format_stats(&profiles, 5), "  | No games recorded yet\n");
+        profiles.record(&won_game(&["alice", "bob"], 1), 1_760_659_200);
+        let text = format_stats(&profiles, 5);
+        assert!(text.contains("  |    1  bob         1      1       0    100.0%"));
+        assert!(text.contains("  | 2025-10-17  alice, bob - won by bob in 1 turns\n"));
+        assert!(!format_stats(&profiles, 0).contains("Last games"));
+
+        // Columns widen to fit the translated headers
+        messages::set_locale(messages::Locale {
+            language: messages::Language::French,
+            ascii: false,
+        });
+        let text = format_stats(&profiles, 5);
+        messages::set_locale(messages::Locale::default());
+        assert!(text.contains("  | Rang  Joueur  Parties  Victoires  Défaites  % victoires"));
+        assert!(text.contains("  |    1  bob           1          1         0       100.0%"));
+        assert!(text.contains("  | Dernières parties\n"));
+        assert!(text.contains("alice, bob - gagnée par bob en 1 tours\n"));
+    }
+
+    #[test]
+    fn test_format_date() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(951_782_400), "2000-02-29");
+        assert_eq!(format_date(1_709_251_199), "2024-02-29");
+    }
+}
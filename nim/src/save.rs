@@ -0,0 +1,172 @@
+use crate::core::Game;
+use crate::messages::Message;
+use crate::player::{self, Player, PlayerKind};
+use serde::{Deserialize, Serialize};
+use std::{fmt, fs, io, path::Path};
+
+/// Content of a save file, written as JSON
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+struct SavedGame {
+    game: Game,
+    players: Vec<PlayerKind>,
+}
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(io::Error),
+    Format(serde_json::Error),
+    Invalid(String),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::Io(e) => write!(f, "{}", Message::SaveAccess(e)),
+            SaveError::Format(e) => write!(f, "{}", Message::SaveFormat(e)),
+            SaveError::Invalid(reason) => write!(f, "{}", Message::SaveInconsistent(reason)),
+        }
+    }
+}
+
+impl From<io::Error> for SaveError {
+    fn from(e: io::Error) -> Self {
+        SaveError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for SaveError {
+    fn from(e: serde_json::Error) -> Self {
+        SaveError::Format(e)
+    }
+}
+
+/// Check what the engine relies on but the file format cannot enforce
+fn check(saved: &SavedGame) -> Result<(), SaveError> {
+    let game = &saved.game;
+    let number_players = game.player_names().len();
+    if number_players == 0 || saved.players.len() != number_players {
+        return Err(SaveError::Invalid(
+            Message::SavedPlayersMismatch.to_string(),
+        ));
+    }
+    if game.current_player() >= number_players
+        || game
+            .history()
+            .iter()
+            .any(|turn| turn.player >= number_players)
+    {
+        return Err(SaveError::Invalid(Message::SavedUnknownPlayer.to_string()));
+    }
+    if game.teams().len() != number_players {
+        return Err(SaveError::Invalid(Message::SavedTeamsMismatch.to_string()));
+    }
+    let heaps = game.heaps();
+    let heaps_valid = (0..heaps.len())
+        .filter_map(|index| heaps.get_heap(index))
+        .all(|heap| {
+            heap.get_remaining() <= heap.get_total()
+                && heap.get_min_remove() <= heap.get_max_remove()
+                && heap.get_moves().is_none_or(|moves| !moves.is_empty())
+        });
+    let wythoff_valid = heaps.both_heaps().is_none_or(|_| heaps.len() == 2);
+    if heaps.len() == 0 || !heaps_valid || !wythoff_valid {
+        return Err(SaveError::Invalid(Message::SavedInvalidHeap.to_string()));
+    }
+    Ok(())
+}
+
+pub fn save(path: &Path, game: &Game, players: &[Box<dyn Player>]) -> Result<(), SaveError> {
+    let saved = SavedGame {
+        game: game.clone(),
+        players: players.iter().map(|player| player.kind()).collect(),
+    };
+    fs::write(path, serde_json::to_string_pretty(&saved)?)?;
+    Ok(())
+}
+
+pub fn load(path: &Path) -> Result<(Game, Vec<Box<dyn Player>>), SaveError> {
+    let saved: SavedGame = serde_json::from_str(&fs::read_to_string(path)?)?;
+    check(&saved)?;
+    let players: Vec<Box<dyn Player>> = saved
+        .game
+        .player_names()
+        .iter()
+        .zip(saved.players.iter())
+        .map(|(name, kind)| player::from_kind(name, kind))
+        .collect::<Result<_, _>>()
+        .map_err(|e| SaveError::Invalid(Message::LearnedCannotPlay(&e).to_string()))?;
+    for player in players.iter() {
+        player.check_game(&saved.game).map_err(SaveError::Invalid)?;
+    }
+    Ok((saved.game, players))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{Heaps, Move, Rules, StickNumber};
+    use crate::messages;
+    use crate::player::{ComputerPlayer, Difficulty, HumanPlayer};
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("nim_{}_{}.json", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let players: Vec<Box<dyn Player>> = vec![
+            Box::new(HumanPlayer::new(String::from("alice"))),
+            Box::new(ComputerPlayer::new(Difficulty::Medium)),
+        ];
+        let names = players.iter().map(|p| String::from(p.name())).collect();
+        let mut game = Game::new(
+            Heaps::single(StickNumber::new(10, 1, 3)),
+            Rules::default(),
+            names,
+            0,
+        );
+        game.apply_move(Move { heap: 0, amount: 2 }).unwrap();
+
+        let path = temp_path("round_trip");
+        save(&path, &game, &players).unwrap();
+        let (loaded, loaded_players) = load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded, game);
+        assert_eq!(loaded.current_player(), 1);
+        assert_eq!(loaded_players[0].name(), "alice");
+        assert_eq!(
+            loaded_players[1].kind(),
+            PlayerKind::Computer(Difficulty::Medium)
+        );
+    }
+
+    #[test]
+    fn test_load_rejects_bad_files() {
+        assert!(matches!(load(&temp_path("missing")), Err(SaveError::Io(_))));
+
+        let path = temp_path("garbage");
+        fs::write(&path, "not a game").unwrap();
+        assert!(matches!(load(&path), Err(SaveError::Format(_))));
+
+        let game = Game::new(Heaps::new(&[3]), Rules::default(), vec![], 0);
+        let saved = SavedGame {
+            game,
+            players: vec![],
+        };
+        fs::write(&path, serde_json::to_string(&saved).unwrap()).unwrap();
+        assert!(matches!(load(&path), Err(SaveError::Invalid(_))));
+
+        messages::set_locale(messages::Locale {
+            language: messages::Language::French,
+            ascii: false,
+        });
+        let error = load(&path).err().unwrap().to_string();
+        messages::set_locale(messages::Locale::default());
+        assert_eq!(
+            error,
+            "La partie sauvegardée est incohérente : les joueurs ne correspondent pas à leurs noms"
+        );
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,821 @@
+/*
+HTTP JSON API over the game engine, for tools embedding the game.
+
+    POST /games               create a game, the body optionally giving its position
+                              and players: {"sticks": [3, 4, 5], "variant": "misere",
+                              "players": ["alice", "hard"]}
+    GET  /games/<id>          state of a game
+    POST /games/<id>/moves    play {"heap": 1, "amount": 2} for the player to move
+    GET  /games/<id>/hint     winning move of the player to move, if there is one
+
+Heaps and players are counted from 1 as in the prompts. Whatever a new game leaves
+out comes from the command line. Computer players move as soon as it is their turn,
+so every answer waits for a human player. A refused move is answered with 422 and
+the `StickNumberError` variant in `error.kind`.
+
+Games are kept in memory, those idle for `GAME_EXPIRY` being dropped and the least
+recently played one making room once `MAX_GAMES` are in play. New games are
+limited to `MAX_STICKS` sticks and `MAX_PLAYERS` players, tree search to
+`MAX_ITERATIONS` and learned tables to those of the command line, as clients should
+not read the files of the server. Requests are handled one at a time, each on its
+own connection, a client not done sending its request within `CONNECTION_TIMEOUT`
+of connecting being answered with 408.
+*/
+
+use crate::cli;
+use crate::coach;
+use crate::core::{Convention, Game, Move, Rules, StickNumberError};
+use crate::learn;
+use crate::mcts;
+use crate::messages::{Glyph, Message};
+use crate::player::{Player, PlayerKind};
+use crate::terminal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, TcpListener, TcpStream};
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_PORT: u16 = 8080;
+/// Only local clients can connect unless told otherwise
+pub const DEFAULT_ADDRESS: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+/// Largest request body accepted, far above any real request
+const MAX_BODY: usize = 64 * 1024;
+/// Largest request line and headers accepted
+const MAX_HEADERS: u64 = 16 * 1024;
+/// Longest a client may take from connecting to the end of its request, and to take
+/// each write of the answer
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(5);
+/// Games kept at most
+const MAX_GAMES: usize = 1000;
+/// Games without any request for that long are dropped
+const GAME_EXPIRY: Duration = Duration::from_secs(60 * 60);
+/// Sticks of all the heaps of a new game at most
+const MAX_STICKS: u64 = 10_000;
+/// Players of a new game at most
+const MAX_PLAYERS: usize = 8;
+/// Iterations of tree search players at most
+const MAX_ITERATIONS: u32 = 10_000;
+
+/// Body of `POST /games`, missing fields coming from the command line
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct NewGame {
+    sticks: Option<Vec<u64>>,
+    variant: Option<String>,
+    /// Human names or computer levels, as in `--players`
+    players: Option<Vec<String>>,
+}
+
+/// Body of `POST /games/<id>/moves`
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MoveRequest {
+    heap: usize,
+    amount: u64,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct TurnState {
+    player: usize,
+    heap: usize,
+    amount: u64,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct GameState {
+    id: u64,
+    heaps: Vec<u64>,
+    variant: String,
+    players: Vec<String>,
+    /// `None` once the game is over
+    current_player: Option<usize>,
+    history: Vec<TurnState>,
+    winner: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Hint {
+    /// The winning move, `None` when every move loses against perfect play
+    heap: Option<usize>,
+    amount: Option<u64>,
+    explanation: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct ErrorBody {
+    error: ErrorDetail,
+}
+
+#[derive(Serialize)]
+struct ErrorDetail {
+    kind: String,
+    message: String,
+}
+
+/// Why a request could not be served
+#[derive(Debug)]
+enum ApiError {
+    NotFound(String),
+    MethodNotAllowed,
+    BadRequest(String),
+    /// The engine refused the move
+    Move(StickNumberError),
+    /// The server could not go on with the game
+    Internal(String),
+    /// The request was not over in time
+    TimedOut,
+}
+
+impl ApiError {
+    fn status(&self) -> u16 {
+        match self {
+            ApiError::NotFound(_) => 404,
+            ApiError::MethodNotAllowed => 405,
+            ApiError::BadRequest(_) => 400,
+            ApiError::Move(_) => 422,
+            ApiError::Internal(_) => 500,
+            ApiError::TimedOut => 408,
+        }
+    }
+
+    fn detail(&self) -> ErrorDetail {
+        let (kind, message) = match self {
+            ApiError::NotFound(what) => (String::from("NotFound"), what.clone()),
+            ApiError::MethodNotAllowed => (
+                String::from("MethodNotAllowed"),
+                Message::MethodNotAllowed.to_string(),
+            ),
+            ApiError::BadRequest(reason) => (String::from("BadRequest"), reason.clone()),
+            // Variant names are part of the API
+            ApiError::Move(e) => (format!("{:?}", e), e.to_string()),
+            ApiError::Internal(reason) => (String::from("Internal"), reason.clone()),
+            ApiError::TimedOut => (
+                String::from("TimedOut"),
+                Message::RequestTimedOut.to_string(),
+            ),
+        };
+        ErrorDetail { kind, message }
+    }
+}
+
+impl From<StickNumberError> for ApiError {
+    fn from(e: StickNumberError) -> Self {
+        ApiError::Move(e)
+    }
+}
+
+impl From<serde_json::Error> for ApiError {
+    fn from(e: serde_json::Error) -> Self {
+        ApiError::BadRequest(Message::InvalidJson(&e).to_string())
+    }
+}
+
+/// Status and JSON body of an answer
+#[derive(PartialEq, Debug)]
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+impl Response {
+    fn json<T: Serialize>(status: u16, value: &T) -> Response {
+        Response {
+            status,
+            body: serde_json::to_string(value).unwrap_or_default(),
+        }
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        408 => "Request Timeout",
+        422 => "Unprocessable Entity",
+        _ => "Internal Server Error",
+    }
+}
+
+struct Session {
+    game: Game,
+    players: Vec<Box<dyn Player>>,
+    /// Time of the last request about the game
+    touched: Instant,
+}
+
+impl Session {
+    /// Let the computer players move until a human player is to move
+    fn play_computers(&mut self) -> Result<(), ApiError> {
+        while !self.game.is_over() {
+            let player = &mut self.players[self.game.current_player()];
+            if player.kind() == PlayerKind::Human {
+                break;
+            }
+            match player.choose_move(&self.game) {
+                Some(mv) => self.game.apply_move(mv)?,
+                None => {
+                    return Err(ApiError::Internal(
+                        Message::NoComputerMove(player.name()).to_string(),
+                    ))
+                }
+            };
+        }
+        Ok(())
+    }
+
+    fn state(&self, id: u64) -> GameState {
+        let game = &self.game;
+        GameState {
+            id,
+            heaps: game.heaps().sizes(),
+            variant: game.rules().convention.to_string(),
+            players: game.player_names().to_vec(),
+            current_player: (!game.is_over()).then_some(game.current_player() + 1),
+            history: game
+                .history()
+                .iter()
+                .map(|turn| TurnState {
+                    player: turn.player + 1,
+                    heap: turn.heap + 1,
+                    amount: turn.amount,
+                })
+                .collect(),
+            winner: game.winner().map(|winner| winner + 1),
+        }
+    }
+
+    fn hint(&self) -> Hint {
+        let game = &self.game;
+        let winning = if game.is_over() {
+            None
+        } else {
+            game.heaps().winning_move(game.rules())
+        };
+        let explanation = if game.is_over() {
+            vec![StickNumberError::GameOver.to_string()]
+        } else {
+            coach::hint(game)
+                .lines()
+                .map(|line| String::from(line.trim_start_matches("  | ")))
+                .collect()
+        };
+        Hint {
+            heap: winning.map(|(heap, _)| heap + 1),
+            amount: winning.map(|(_, amount)| amount),
+            explanation,
+        }
+    }
+}
+
+/// Games in play, answering the requests of the API
+pub struct Server<'a> {
+    config: &'a cli::Config,
+    games: HashMap<u64, Session>,
+    next_id: u64,
+}
+
+impl<'a> Server<'a> {
+    pub fn new(config: &'a cli::Config) -> Server<'a> {
+        Server {
+            config,
+            games: HashMap::new(),
+            next_id: 1,
+        }
+    }
+
+    /// Answer a request, `path` possibly ending with a query string
+    pub fn handle(&mut self, method: &str, path: &str, body: &str) -> Response {
+        self.games
+            .retain(|_, session| session.touched.elapsed() < GAME_EXPIRY);
+        let path = path.split('?').next().unwrap_or_default();
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        let result = match (method, &segments[..]) {
+            ("POST", ["games"]) => self.create(body).map(|state| Response::json(201, &state)),
+            ("GET", ["games", id]) => self
+                .session(id)
+                .map(|(id, session)| Response::json(200, &session.state(id))),
+            ("POST", ["games", id, "moves"]) => {
+                self.play(id, body).map(|state| Response::json(200, &state))
+            }
+            ("GET", ["games", id, "hint"]) => self
+                .session(id)
+                .map(|(_, session)| Response::json(200, &session.hint())),
+            (_, ["games"] | ["games", _] | ["games", _, "moves" | "hint"]) => {
+                Err(ApiError::MethodNotAllowed)
+            }
+            _ => Err(ApiError::NotFound(Message::PathNotFound(path).to_string())),
+        };
+        result.unwrap_or_else(|e| Response::json(e.status(), &ErrorBody { error: e.detail() }))
+    }
+
+    fn id(id: &str) -> Result<u64, ApiError> {
+        id.parse::<u64>()
+            .map_err(|_| ApiError::NotFound(Message::GameNotFound(id).to_string()))
+    }
+
+    /// A game in play, kept for longer now that it was asked about
+    fn session(&mut self, id: &str) -> Result<(u64, &mut Session), ApiError> {
+        let id = Server::id(id)?;
+        match self.games.get_mut(&id) {
+            Some(session) => {
+                session.touched = Instant::now();
+                Ok((id, session))
+            }
+            None => Err(ApiError::NotFound(
+                Message::GameNotFound(&id.to_string()).to_string(),
+            )),
+        }
+    }
+
+    fn create(&mut self, body: &str) -> Result<GameState, ApiError> {
+        let request: NewGame = if body.trim().is_empty() {
+            NewGame::default()
+        } else {
+            serde_json::from_str(body)?
+        };
+        let sizes = request
+            .sticks
+            .or_else(|| self.config.sticks.clone())
+            .ok_or_else(|| ApiError::BadRequest(Message::SticksToStart.to_string()))?;
+        if sizes.iter().all(|&size| size == 0) {
+            return Err(ApiError::BadRequest(Message::NoStick.to_string()));
+        }
+        let total = sizes
+            .iter()
+            .try_fold(0u64, |total, &size| total.checked_add(size));
+        if total.is_none_or(|total| total > MAX_STICKS) {
+            return Err(ApiError::BadRequest(
+                Message::AtMostSticks(MAX_STICKS).to_string(),
+            ));
+        }
+        let heaps = self.config.heaps(&sizes).map_err(ApiError::BadRequest)?;
+        let convention = match &request.variant {
+            Some(variant) => variant
+                .parse::<Convention>()
+                .map_err(ApiError::BadRequest)?,
+            None => self.config.convention.unwrap_or(Convention::Normal),
+        };
+        if let Some(specs) = &request.players {
+            for spec in specs.iter() {
+                if learn::parse_spec(spec).is_some() {
+                    return Err(ApiError::BadRequest(
+                        Message::LearnedOnCommandLine.to_string(),
+                    ));
+                }
+                if mcts::parse_spec(spec).is_some_and(|iterations| iterations > MAX_ITERATIONS) {
+                    return Err(ApiError::BadRequest(
+                        Message::AtMostIterations(MAX_ITERATIONS).to_string(),
+                    ));
+                }
+            }
+        }
+        let specs = request
+            .players
+            .or_else(|| self.config.players.clone())
+            .unwrap_or_else(|| vec![String::from("Player 1"), String::from("Player 2")]);
+        if specs.len() < 2 {
+            return Err(ApiError::BadRequest(Message::TooFewPlayers.to_string()));
+        }
+        if specs.len() > MAX_PLAYERS {
+            return Err(ApiError::BadRequest(
+                Message::AtMostPlayers(MAX_PLAYERS).to_string(),
+            ));
+        }
+        let players = specs
+            .iter()
+            .map(|spec| terminal::player_from_spec(spec))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+        let names = players
+            .iter()
+            .map(|player| String::from(player.name()))
+            .collect();
+        let game = Game::new(heaps, Rules::new(convention), names, 0);
+        for player in players.iter() {
+            player.check_game(&game).map_err(ApiError::BadRequest)?;
+        }
+        let mut session = Session {
+            game,
+            players,
+            touched: Instant::now(),
+        };
+        session.play_computers()?;
+
+        if self.games.len() >= MAX_GAMES {
+            let oldest = self
+                .games
+                .iter()
+                .min_by_key(|(_, session)| session.touched)
+                .map(|(&id, _)| id);
+            if let Some(oldest) = oldest {
+                self.games.remove(&oldest);
+            }
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        let state = session.state(id);
+        self.games.insert(id, session);
+        Ok(state)
+    }
+
+    fn play(&mut self, id: &str, body: &str) -> Result<GameState, ApiError> {
+        let (id, session) = self.session(id)?;
+        let request: MoveRequest = serde_json::from_str(body)?;
+        let heap = request
+            .heap
+            .checked_sub(1)
+            .ok_or(StickNumberError::HeapOutOfRange)?;
+        session.game.apply_move(Move {
+            heap,
+            amount: request.amount,
+        })?;
+        session.play_computers()?;
+        Ok(session.state(id))
+    }
+}
+
+/// Method, path and body of a request
+fn read_request(reader: &mut impl BufRead) -> io::Result<(String, String, String)> {
+    let invalid = |reason: Message| io::Error::new(io::ErrorKind::InvalidData, reason.to_string());
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut words = line.split_whitespace();
+    let (Some(method), Some(path)) = (words.next(), words.next()) else {
+        return Err(invalid(Message::MalformedRequestLine));
+    };
+    let (method, path) = (method.to_uppercase(), String::from(path));
+
+    let mut length = 0;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                length = value
+                    .trim()
+                    .parse::<usize>()
+                    .map_err(|_| invalid(Message::MalformedLength))?;
+            }
+        }
+    }
+    if length > MAX_BODY {
+        return Err(invalid(Message::BodyTooLarge));
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    let body = String::from_utf8(body).map_err(|_| invalid(Message::BodyNotUtf8))?;
+    Ok((method, path, body))
+}
+
+fn write_response(writer: &mut impl Write, response: &Response) -> io::Result<()> {
+    write!(
+        writer,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        reason(response.status),
+        response.body.len(),
+        response.body
+    )?;
+    writer.flush()
+}
+
+/// Reads of a connection that fail once its deadline has passed, however slowly the
+/// client sends
+struct Deadline {
+    stream: TcpStream,
+    deadline: Instant,
+}
+
+impl Read for Deadline {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let left = self.deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                Message::RequestTimedOut.to_string(),
+            ));
+        }
+        self.stream.set_read_timeout(Some(left))?;
+        self.stream.read(buf)
+    }
+}
+
+/// Answer the request of a single connection, returning its request line and status
+fn serve_connection(
+    server: &mut Server,
+    stream: TcpStream,
+    timeout: Duration,
+) -> io::Result<String> {
+    stream.set_write_timeout(Some(timeout))?;
+    let deadline = Deadline {
+        stream: stream.try_clone()?,
+        deadline: Instant::now() + timeout,
+    };
+    let limit = MAX_HEADERS + MAX_BODY as u64;
+    let mut reader = BufReader::new(deadline.take(limit));
+    let mut writer = stream;
+    let error = match read_request(&mut reader) {
+        Ok((method, path, body)) => {
+            let response = server.handle(&method, &path, &body);
+            let summary = format!("{} {} {}", method, path, response.status);
+            write_response(&mut writer, &response)?;
+            return Ok(summary);
+        }
+        // A blocked read ends with either kind depending on the platform
+        Err(e)
+            if matches!(
+                e.kind(),
+                io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock
+            ) =>
+        {
+            ApiError::TimedOut
+        }
+        Err(e) => ApiError::BadRequest(e.to_string()),
+    };
+    let response = Response::json(
+        error.status(),
+        &ErrorBody {
+            error: error.detail(),
+        },
+    );
+    write_response(&mut writer, &response)?;
+    Ok(format!("invalid request {}", response.status))
+}
+
+/// Serve the API until the process is stopped, logging each request
+pub fn serve(config: &cli::Config, listener: TcpListener, log: &mut impl Write) -> io::Result<()> {
+    writeln!(
+        log,
+        "{}  | {}\n{}",
+        Glyph::Title,
+        Message::Serving(&listener.local_addr()?),
+        "-".repeat(80)
+    )?;
+    let mut server = Server::new(config);
+    for stream in listener.incoming() {
+        // A client going away must not stop the other games
+        match stream.and_then(|stream| serve_connection(&mut server, stream, CONNECTION_TIMEOUT)) {
+            Ok(summary) => writeln!(log, "  | {}", summary)?,
+            Err(e) => writeln!(log, "  | {}", Message::Error(&e))?,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messages;
+    use std::thread;
+
+    fn empty_config() -> cli::Config {
+        cli::config_from_matches(&cli::command().get_matches_from(["nim", "serve"])).unwrap()
+    }
+
+    fn error_kind(response: &Response) -> String {
+        let body: serde_json::Value = serde_json::from_str(&response.body).unwrap();
+        String::from(body["error"]["kind"].as_str().unwrap())
+    }
+
+    #[test]
+    fn test_game_against_computer() {
+        let config = empty_config();
+        let mut server = Server::new(&config);
+        let created = server.handle(
+            "POST",
+            "/games",
+            r#"{"sticks": [1, 2], "players": ["alice", "hard"]}"#,
+        );
+        assert_eq!(created.status, 201);
+        let state: GameState = serde_json::from_str(&created.body).unwrap();
+        assert_eq!(state.id, 1);
+        assert_eq!(state.heaps, vec![1, 2]);
+        assert_eq!(state.players, vec!["alice", "Computer (hard)"]);
+        assert_eq!(state.current_player, Some(1));
+
+        let hint: Hint =
+            serde_json::from_str(&server.handle("GET", "/games/1/hint", "").body).unwrap();
+        assert_eq!((hint.heap, hint.amount), (Some(2), Some(1)));
+        assert!(hint.explanation[0].starts_with("Hint: take 1 from heap 2"));
+
+        // Alice empties the second heap, the computer then takes the last stick
+        let played = server.handle("POST", "/games/1/moves", r#"{"heap": 2, "amount": 2}"#);
+        assert_eq!(played.status, 200);
+        let state: GameState = serde_json::from_str(&played.body).unwrap();
+        assert_eq!(
+            state.history,
+            vec![
+                TurnState {
+                    player: 1,
+                    heap: 2,
+                    amount: 2
+                },
+                TurnState {
+                    player: 2,
+                    heap: 1,
+                    amount: 1
+                }
+            ]
+        );
+        assert_eq!((state.current_player, state.winner), (None, Some(2)));
+        let got: GameState =
+            serde_json::from_str(&server.handle("GET", "/games/1", "").body).unwrap();
+        assert_eq!(got, state);
+    }
+
+    #[test]
+    fn test_errors() {
+        let config = cli::config_from_matches(
+            &cli::command().get_matches_from(["nim", "serve", "--sticks", "5", "--max", "2"]),
+        )
+        .unwrap();
+        let mut server = Server::new(&config);
+        let created = server.handle("POST", "/games", "");
+        assert_eq!(created.status, 201);
+        let state: GameState = serde_json::from_str(&created.body).unwrap();
+        assert_eq!(state.players, vec!["Player 1", "Player 2"]);
+
+        let mv = |server: &mut Server, body: &str| server.handle("POST", "/games/1/moves", body);
+        let refused = mv(&mut server, r#"{"heap": 1, "amount": 3}"#);
+        assert_eq!(refused.status, 422);
+        assert_eq!(error_kind(&refused), "TakeOverMax");
+        assert!(refused
+            .body
+            .contains("Trying to take over the maximum limit"));
+        assert_eq!(
+            error_kind(&mv(&mut server, r#"{"heap": 2, "amount": 1}"#)),
+            "HeapOutOfRange"
+        );
+        assert_eq!(
+            error_kind(&mv(&mut server, r#"{"heap": 0, "amount": 1}"#)),
+            "HeapOutOfRange"
+        );
+        assert_eq!(error_kind(&mv(&mut server, "{\"heap\": 1}")), "BadRequest");
+        for amount in [2, 2, 1] {
+            let body = format!(r#"{{"heap": 1, "amount": {}}}"#, amount);
+            assert_eq!(mv(&mut server, &body).status, 200);
+        }
+        assert_eq!(
+            error_kind(&mv(&mut server, r#"{"heap": 1, "amount": 1}"#)),
+            "GameOver"
+        );
+
+        assert_eq!(server.handle("GET", "/games/7", "").status, 404);
+        assert_eq!(server.handle("GET", "/games/seven/hint", "").status, 404);
+        assert_eq!(server.handle("GET", "/players", "").status, 404);
+        assert_eq!(server.handle("DELETE", "/games/1", "").status, 405);
+        let invalid = [
+            r#"{"sticks": [0, 0]}"#,
+            r#"{"variant": "sideways"}"#,
+            r#"{"players": ["alice"]}"#,
+            r#"{"colour": "red"}"#,
+            r#"{"sticks": [10000, 1]}"#,
+            r#"{"sticks": [18446744073709551615, 1]}"#,
+            r#"{"players": ["a", "b", "c", "d", "e", "f", "g", "h", "i"]}"#,
+            r#"{"players": ["alice", "learned:/etc/passwd"]}"#,
+            r#"{"players": ["alice", "mcts:1000000"]}"#,
+        ];
+        for body in invalid {
+            assert_eq!(
+                server.handle("POST", "/games", body).status,
+                400,
+                "{}",
+                body
+            );
+        }
+
+        messages::set_locale(messages::Locale {
+            language: messages::Language::French,
+            ascii: false,
+        });
+        let missing = server.handle("GET", "/games/7", "");
+        let too_many = server.handle("POST", "/games", r#"{"sticks": [10001]}"#);
+        messages::set_locale(messages::Locale::default());
+        assert!(missing.body.contains("partie 7 introuvable"));
+        assert!(too_many.body.contains("une partie a au plus 10000 bâtons"));
+    }
+
+    #[test]
+    fn test_oldest_game_dropped() {
+        let config = empty_config();
+        let mut server = Server::new(&config);
+        for _ in 0..MAX_GAMES {
+            let created = server.handle("POST", "/games", r#"{"sticks": [3]}"#);
+            assert_eq!(created.status, 201);
+        }
+        assert_eq!(server.handle("GET", "/games/1", "").status, 200);
+        assert_eq!(
+            server.handle("POST", "/games", r#"{"sticks": [3]}"#).status,
+            201
+        );
+        assert_eq!(server.games.len(), MAX_GAMES);
+        assert_eq!(server.handle("GET", "/games/1", "").status, 200);
+        assert_eq!(server.handle("GET", "/games/2", "").status, 404);
+    }
+
+    #[test]
+    fn test_computer_without_move() {
+        struct Idle;
+        impl Player for Idle {
+            fn name(&self) -> &str {
+                "idle"
+            }
+            fn kind(&self) -> PlayerKind {
+                PlayerKind::Mcts(1)
+            }
+            fn choose_move(&mut self, _: &Game) -> Option<Move> {
+                None
+            }
+            fn choose_sum_move(
+                &mut self,
+                _: &crate::grundy::GameSum<crate::grundy::Position>,
+                _: &mut crate::grundy::GrundyCache<crate::grundy::Position>,
+            ) -> Option<(usize, usize)> {
+                None
+            }
+        }
+        let names = vec![String::from("idle"), String::from("alice")];
+        let mut session = Session {
+            game: Game::new(crate::core::Heaps::new(&[3]), Rules::default(), names, 0),
+            players: vec![Box::new(Idle), Box::new(Idle)],
+            touched: Instant::now(),
+        };
+        let error = session.play_computers().unwrap_err();
+        assert_eq!(error.status(), 500);
+        assert_eq!(error.detail().kind, "Internal");
+    }
+
+    /// Send a request the way any HTTP client would, returning the status and body
+    fn request(address: &str, method: &str, path: &str, body: &str) -> (u16, serde_json::Value) {
+        let mut stream = TcpStream::connect(address).unwrap();
+        write!(
+            stream,
+            "{} {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+            method,
+            path,
+            address,
+            body.len(),
+            body
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+        (status, serde_json::from_str(body).unwrap())
+    }
+
+    #[test]
+    fn test_trickling_client_timed_out() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        // One byte every 100 ms never waits long enough for a single read to time out
+        let client = thread::spawn(move || {
+            let mut stream = TcpStream::connect(address).unwrap();
+            for byte in "GET /games/1 HTTP/1.1\r\nHost: localhost".bytes() {
+                if stream.write_all(&[byte]).is_err() {
+                    break;
+                }
+                thread::sleep(Duration::from_millis(100));
+            }
+        });
+        let config = empty_config();
+        let mut server = Server::new(&config);
+        let (stream, _) = listener.accept().unwrap();
+        let start = Instant::now();
+        let summary = serve_connection(&mut server, stream, Duration::from_millis(500)).unwrap();
+        assert_eq!(summary, "invalid request 408");
+        assert!(start.elapsed() < Duration::from_secs(2));
+        client.join().unwrap();
+    }
+
+    #[test]
+    fn test_local_client() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        thread::spawn(move || serve(&empty_config(), listener, &mut io::sink()));
+        // A client sending nothing only holds the others up until it times out
+        let _silent = TcpStream::connect(&address).unwrap();
+
+        let (status, state) = request(&address, "POST", "/games", r#"{"sticks": [3, 4, 5]}"#);
+        assert_eq!(status, 201);
+        assert_eq!(state["heaps"], serde_json::json!([3, 4, 5]));
+        let path = format!("/games/{}/moves", state["id"]);
+        let (status, state) = request(&address, "POST", &path, r#"{"heap": 1, "amount": 2}"#);
+        assert_eq!(status, 200);
+        assert_eq!(state["heaps"], serde_json::json!([1, 4, 5]));
+        assert_eq!(state["current_player"], 2);
+        let (status, error) = request(&address, "POST", &path, r#"{"heap": 1, "amount": 2}"#);
+        assert_eq!(status, 422);
+        assert_eq!(error["error"]["kind"], "RemainingNegative");
+        let (status, _) = request(&address, "GET", "/games/99", "");
+        assert_eq!(status, 404);
+    }
+}
@@ -0,0 +1,1430 @@
+use crate::cli;
+use crate::clock::{self, Clock, Penalty, TimeControl};
+use crate::coach;
+use crate::core::{Convention, Event, Game, Heaps, Move, Rules, StickNumberError, Turn};
+use crate::grundy::{self, GameSum, GrundyCache, ImpartialGame, Position};
+use crate::learn::{self, LearnedPlayer};
+use crate::mcts::{self, MctsPlayer};
+use crate::messages::{Glyph, Message};
+use crate::player::{self, ComputerPlayer, Difficulty, HumanPlayer, Player, PlayerKind};
+use crate::save;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// File used by the `save` and `load` commands when none is given
+const DEFAULT_SAVE_FILE: &str = "nim_save.json";
+
+/// Why the players could not be asked for what the game needs
+#[derive(Debug)]
+pub enum InputError {
+    /// Nothing more will be typed, as when a pipe is drained
+    Closed,
+    /// A player typed `quit`
+    Quit,
+    /// The clock ran out before a line was typed
+    TimedOut,
+    Io(io::Error),
+    /// A setup that cannot be played, with the reason
+    Invalid(String),
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::Closed => write!(f, "{}", Message::InputClosed),
+            InputError::Quit => write!(f, "{}", Message::InputQuit),
+            InputError::TimedOut => write!(f, "{}", Message::InputTimedOut),
+            InputError::Io(e) => write!(f, "{}", e),
+            InputError::Invalid(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+impl From<io::Error> for InputError {
+    fn from(e: io::Error) -> Self {
+        InputError::Io(e)
+    }
+}
+
+/// What a player typed at the turn prompt
+#[derive(PartialEq, Debug)]
+pub enum TurnInput {
+    Move(Result<Move, StickNumberError>),
+    Save(PathBuf),
+    Load(PathBuf),
+    Undo,
+    Redo,
+    Hint,
+}
+
+/// Recognise the `save [file]`, `load [file]`, `undo`, `redo` and `hint` commands
+fn parse_command(line: &str) -> Option<TurnInput> {
+    let mut words = line.split_whitespace();
+    let command = words.next()?.to_lowercase();
+    let path = PathBuf::from(words.next().unwrap_or(DEFAULT_SAVE_FILE));
+    match command.as_str() {
+        "save" => Some(TurnInput::Save(path)),
+        "load" => Some(TurnInput::Load(path)),
+        "undo" => Some(TurnInput::Undo),
+        "redo" => Some(TurnInput::Redo),
+        "hint" => Some(TurnInput::Hint),
+        _ => None,
+    }
+}
+
+/// Text front-end playing the game over any input and output streams
+pub struct Terminal<R: BufRead, W: Write> {
+    input: R,
+    output: W,
+    /// Comment on the quality of each human move
+    coach: bool,
+    /// Time limits of the human players in `play`
+    time_control: Option<TimeControl>,
+    /// Time spent since the given instant, replaced in tests
+    elapsed: fn(Instant) -> Duration,
+    /// Lines read on their own thread, so that a clock can run out while waiting for one
+    lines: Option<Receiver<io::Result<String>>>,
+    /// When the line being waited for must have been typed
+    deadline: Option<Instant>,
+}
+
+/// Read the lines of `input` on a new thread, until it is closed
+fn spawn_reader<T: BufRead + Send + 'static>(input: T) -> Receiver<io::Result<String>> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for line in input.lines() {
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    receiver
+}
+
+/// A computer level given on the command line makes a computer player, anything else a human name
+pub fn player_from_spec(spec: &str) -> Result<Box<dyn Player>, InputError> {
+    if let Some(iterations) = mcts::parse_spec(spec) {
+        return Ok(Box::new(MctsPlayer::new(iterations)));
+    }
+    if let Some(path) = learn::parse_spec(spec) {
+        let learned = LearnedPlayer::load(&path).map_err(|e| InputError::Invalid(e.to_string()))?;
+        return Ok(Box::new(learned));
+    }
+    match spec.parse::<Difficulty>() {
+        Ok(difficulty) => Ok(Box::new(ComputerPlayer::new(difficulty))),
+        Err(_) => Ok(Box::new(HumanPlayer::new(String::from(spec)))),
+    }
+}
+
+pub fn format_turn(game: &Game, turn: &Turn) -> String {
+    let name = &game.player_names()[turn.player];
+    if game.heaps().len() == 1 {
+        let amount = turn.amount;
+        format!("  | {}\n", Message::Removes { name, amount })
+    } else if Some(turn.heap) == game.heaps().both_heaps() {
+        let amount = turn.amount;
+        format!("  | {}\n", Message::RemovesFromBoth { name, amount })
+    } else {
+        let removes = Message::RemovesFromHeap {
+            name,
+            amount: turn.amount,
+            heap: turn.heap + 1,
+        };
+        format!("  | {}\n", removes)
+    }
+}
+
+pub fn format_heaps(game: &Game) -> String {
+    if game.heaps().len() == 1 {
+        format!(
+            "{}  | {}\n{}\n",
+            Glyph::Sticks,
+            Message::RemainingSticks,
+            game.heaps()
+        )
+    } else {
+        format!(
+            "{}  | {}\n{}\n",
+            Glyph::Sticks,
+            Message::HeapsTitle,
+            game.heaps()
+        )
+    }
+}
+
+/// Lines of a table, columns widening from `widths` to fit their longest cell,
+/// translated headers included, and the column at `left` being aligned left
+pub fn format_table(rows: &[Vec<String>], widths: &[usize], left: usize) -> String {
+    let mut widths = widths.to_vec();
+    for row in rows.iter() {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let mut text = String::new();
+    for row in rows.iter() {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(widths.iter())
+            .enumerate()
+            .map(|(column, (cell, &width))| {
+                if column == left {
+                    format!("{:<width$}", cell)
+                } else {
+                    format!("{:>width$}", cell)
+                }
+            })
+            .collect();
+        text.push_str(&format!("  | {}\n", cells.join("  ")));
+    }
+    text
+}
+
+pub fn format_event(game: &Game, event: &Event) -> String {
+    match event {
+        Event::MovePlayed { .. } => {
+            let prefix = if game.heaps().len() == 1 {
+                format!("{}  | ", Glyph::Sticks)
+            } else {
+                String::new()
+            };
+            format!("{}{}\n\n{}\n", prefix, game.heaps(), "-".repeat(50))
+        }
+        Event::TurnStarted { .. } => String::new(),
+        Event::GameOver { winner, turns } if game.has_teams() => {
+            let team = game.teams()[*winner];
+            let members: Vec<&str> = game
+                .team_members(team)
+                .iter()
+                .map(|&player| game.player_names()[player].as_str())
+                .collect();
+            let won = Message::TeamWon {
+                team: team + 1,
+                members: &members.join(", "),
+                turns: *turns,
+            };
+            format!("{}  | {}\n", Glyph::Victory, won)
+        }
+        Event::GameOver { winner, turns } => {
+            let won = Message::Won {
+                name: &game.player_names()[*winner],
+                turns: *turns,
+            };
+            format!("{}  | {}\n", Glyph::Victory, won)
+        }
+    }
+}
+
+impl<R: BufRead, W: Write> Terminal<R, W> {
+    pub fn new(input: R, output: W) -> Terminal<R, W> {
+        Terminal {
+            input,
+            output,
+            coach: false,
+            time_control: None,
+            elapsed: |start| start.elapsed(),
+            lines: None,
+            deadline: None,
+        }
+    }
+
+    pub fn with_coach(self, coach: bool) -> Terminal<R, W> {
+        Terminal { coach, ..self }
+    }
+
+    pub fn with_time_control(self, time_control: Option<TimeControl>) -> Terminal<R, W> {
+        Terminal {
+            time_control,
+            ..self
+        }
+    }
+
+    /// Read from `input` on its own thread instead, for the clock to stop waiting
+    /// for a move once the time is up
+    pub fn with_input_thread<T: BufRead + Send + 'static>(self, input: T) -> Terminal<R, W> {
+        Terminal {
+            lines: Some(spawn_reader(input)),
+            ..self
+        }
+    }
+
+    /// Write text as it is, lines being terminated by the caller
+    pub fn write_text(&mut self, text: &str) -> io::Result<()> {
+        write!(self.output, "{}", text)
+    }
+
+    /// Read a line without its end, typing `quit` at any prompt leaving the game
+    pub fn read_string(&mut self) -> Result<String, InputError> {
+        let mut input_string = String::new();
+        self.output.flush()?;
+        match (&self.lines, self.deadline) {
+            (Some(lines), Some(deadline)) => {
+                let timeout = deadline.saturating_duration_since(Instant::now());
+                match lines.recv_timeout(timeout) {
+                    Ok(line) => input_string = line?,
+                    Err(RecvTimeoutError::Timeout) => return Err(InputError::TimedOut),
+                    Err(RecvTimeoutError::Disconnected) => return Err(InputError::Closed),
+                }
+            }
+            (Some(lines), None) => match lines.recv() {
+                Ok(line) => input_string = line?,
+                Err(_) => return Err(InputError::Closed),
+            },
+            (None, _) => {
+                if self.input.read_line(&mut input_string)? == 0 {
+                    return Err(InputError::Closed);
+                }
+            }
+        }
+        let line = input_string.trim_end_matches(['\n', '\r']);
+        if line.trim().eq_ignore_ascii_case("quit") {
+            return Err(InputError::Quit);
+        }
+        Ok(String::from(line))
+    }
+
+    pub fn read_player(&mut self, index: usize) -> Result<Box<dyn Player>, InputError> {
+        loop {
+            writeln!(
+                self.output,
+                "{}  | {}",
+                Glyph::Prompt,
+                Message::AskPlayerType(index + 1)
+            )?;
+            let kind = self.read_string()?;
+            if kind.trim().is_empty() || kind.trim().eq_ignore_ascii_case("human") {
+                writeln!(
+                    self.output,
+                    "{}  | {}",
+                    Glyph::Prompt,
+                    Message::AskPlayerName(index + 1)
+                )?;
+                let name = self.read_string()?;
+                return Ok(Box::new(HumanPlayer::new(name)));
+            }
+            if let Some(iterations) = mcts::parse_spec(&kind) {
+                return Ok(Box::new(MctsPlayer::new(iterations)));
+            }
+            if let Some(path) = learn::parse_spec(&kind) {
+                match LearnedPlayer::load(&path) {
+                    Ok(learned) => return Ok(Box::new(learned)),
+                    Err(e) => {
+                        writeln!(self.output, "{}  | {}", Glyph::Error, Message::Error(&e))?;
+                        continue;
+                    }
+                }
+            }
+            match kind.parse::<Difficulty>() {
+                Ok(difficulty) => return Ok(Box::new(ComputerPlayer::new(difficulty))),
+                Err(_) => {
+                    let unknown = Message::UnknownPlayerType(&kind);
+                    writeln!(
+                        self.output,
+                        "{}  | {}",
+                        Glyph::Error,
+                        Message::Error(&unknown)
+                    )?
+                }
+            }
+        }
+    }
+
+    fn read_number_players(&mut self) -> Result<usize, InputError> {
+        loop {
+            writeln!(
+                self.output,
+                "{}  | {}",
+                Glyph::Prompt,
+                Message::AskNumberPlayers
+            )?;
+            let number = self.read_string()?;
+            if number.trim().is_empty() {
+                return Ok(2);
+            }
+            match number.trim().parse::<usize>() {
+                Ok(number) if number >= 2 => return Ok(number),
+                Ok(_) => writeln!(
+                    self.output,
+                    "{}  | {}",
+                    Glyph::Error,
+                    Message::Error(&Message::TooFewPlayers)
+                )?,
+                Err(_) => {
+                    let invalid = Message::NotANumber(&number);
+                    writeln!(
+                        self.output,
+                        "{}  | {}",
+                        Glyph::Error,
+                        Message::Error(&invalid)
+                    )?
+                }
+            }
+        }
+    }
+
+    /// Ask for the team of each player, `None` when playing without teams
+    fn read_teams(&mut self, number_players: usize) -> Result<Option<Vec<usize>>, InputError> {
+        loop {
+            writeln!(self.output, "{}  | {}", Glyph::Prompt, Message::AskTeams)?;
+            let line = self.read_string()?;
+            if line.trim().is_empty() {
+                return Ok(None);
+            }
+            let teams: Result<Vec<usize>, _> = line
+                .split_whitespace()
+                .map(|team| match team.parse::<usize>() {
+                    Ok(team) if team >= 1 => Ok(team - 1),
+                    _ => Err(Message::InvalidTeam(team).to_string()),
+                })
+                .collect();
+            match teams
+                .and_then(|teams| cli::check_teams(&teams, Some(number_players)).map(|_| teams))
+            {
+                Ok(teams) => return Ok(Some(teams)),
+                Err(e) => writeln!(self.output, "{}  | {}", Glyph::Error, Message::Error(&e))?,
+            }
+        }
+    }
+
+    /// Ask for the heaps, exactly two of them in Wythoff's game
+    fn read_heap_sizes(&mut self, wythoff: bool) -> Result<Vec<u64>, InputError> {
+        if wythoff {
+            writeln!(
+                self.output,
+                "{}  | {}",
+                Glyph::Prompt,
+                Message::AskWythoffHeaps
+            )?;
+        } else {
+            writeln!(
+                self.output,
+                "{}  | {}",
+                Glyph::Prompt,
+                Message::AskHeapSizes
+            )?;
+        }
+        loop {
+            let line = self.read_string()?;
+            let sizes: Result<Vec<u64>, _> = line
+                .split_whitespace()
+                .map(|size| size.parse::<u64>().map_err(|_| size))
+                .collect();
+            let error = match sizes {
+                Ok(sizes) if wythoff && sizes.len() != 2 => Message::WythoffTwoHeaps,
+                Ok(sizes) if sizes.iter().any(|&size| size > 0) => return Ok(sizes),
+                Ok(_) => Message::NoStick,
+                Err(size) => Message::NotANumber(size),
+            };
+            writeln!(
+                self.output,
+                "{}  | {}",
+                Glyph::Error,
+                Message::Error(&error)
+            )?;
+            writeln!(
+                self.output,
+                "{}  | {}",
+                Glyph::Prompt,
+                Message::AskValidSticks
+            )?;
+        }
+    }
+
+    fn read_rules(&mut self) -> Result<Rules, InputError> {
+        loop {
+            writeln!(
+                self.output,
+                "{}  | {}",
+                Glyph::Prompt,
+                Message::AskConvention
+            )?;
+            let convention = self.read_string()?;
+            if convention.trim().is_empty() {
+                return Ok(Rules::default());
+            }
+            match convention.parse::<Convention>() {
+                Ok(convention) => return Ok(Rules::new(convention)),
+                Err(_) => {
+                    let unknown = Message::UnknownConvention(&convention);
+                    writeln!(
+                        self.output,
+                        "{}  | {}",
+                        Glyph::Error,
+                        Message::Error(&unknown)
+                    )?
+                }
+            }
+        }
+    }
+
+    /// Build the game from the command line, asking for whatever is missing
+    pub fn setup(
+        &mut self,
+        config: &cli::Config,
+    ) -> Result<(Game, Vec<Box<dyn Player>>), InputError> {
+        writeln!(self.output, "{}  | {}", Glyph::Title, Message::Welcome)?;
+        writeln!(self.output, "{}", "-".repeat(80))?;
+
+        if let Some(path) = &config.resume {
+            let resumed = save::load(path).map_err(|e| InputError::Invalid(e.to_string()))?;
+            let resumed_from = path.display();
+            writeln!(self.output, "  | {}", Message::GameResumed(&resumed_from))?;
+            return Ok(resumed);
+        }
+
+        let players = self.read_players(config)?;
+        self.setup_game(config, players)
+    }
+
+    fn read_players(&mut self, config: &cli::Config) -> Result<Vec<Box<dyn Player>>, InputError> {
+        match &config.players {
+            Some(specs) => specs.iter().map(|spec| player_from_spec(spec)).collect(),
+            None => {
+                let number_players = self.read_number_players()?;
+                (0..number_players)
+                    .map(|index| self.read_player(index))
+                    .collect()
+            }
+        }
+    }
+
+    /// Build the game for the given players, asking for the heaps and rules if missing
+    pub fn setup_game(
+        &mut self,
+        config: &cli::Config,
+        players: Vec<Box<dyn Player>>,
+    ) -> Result<(Game, Vec<Box<dyn Player>>), InputError> {
+        let mut players = players;
+        let mut teams = match &config.teams {
+            Some(teams) => {
+                cli::check_teams(teams, Some(players.len())).map_err(InputError::Invalid)?;
+                Some(teams.clone())
+            }
+            None if players.len() > 2 => self.read_teams(players.len())?,
+            None => None,
+        };
+        if config.shuffle {
+            // Teams stay with their players
+            let mut seats: Vec<usize> = (0..players.len()).collect();
+            seats.shuffle(&mut rand::thread_rng());
+            let mut unseated: Vec<Option<Box<dyn Player>>> =
+                players.into_iter().map(Some).collect();
+            players = seats
+                .iter()
+                .filter_map(|&seat| unseated[seat].take())
+                .collect();
+            teams = teams.map(|teams| seats.iter().map(|&seat| teams[seat]).collect());
+            let order: Vec<&str> = players.iter().map(|player| player.name()).collect();
+            writeln!(self.output, "  | {}", Message::TurnOrder(&order.join(", ")))?;
+        }
+
+        let sizes = match &config.sticks {
+            Some(sizes) => sizes.clone(),
+            None => self.read_heap_sizes(config.wythoff)?,
+        };
+        let rules = match config.convention {
+            Some(convention) => Rules::new(convention),
+            None => self.read_rules()?,
+        };
+        let first = match config.first {
+            Some(cli::First::Player(index)) if index < players.len() => index,
+            Some(cli::First::Player(_)) => {
+                return Err(InputError::Invalid(
+                    Message::FirstOutOfRange(players.len()).to_string(),
+                ))
+            }
+            Some(cli::First::Random) => rand::thread_rng().gen_range(0..players.len()),
+            None => 0,
+        };
+        writeln!(self.output, "{}", "-".repeat(80))?;
+
+        let heaps = config.heaps(&sizes).map_err(InputError::Invalid)?;
+        let player_names = players
+            .iter()
+            .map(|player| String::from(player.name()))
+            .collect();
+        let game = Game::new(heaps, rules, player_names, first);
+        for player in players.iter() {
+            player.check_game(&game).map_err(InputError::Invalid)?;
+        }
+        match teams {
+            Some(teams) => Ok((game.with_teams(teams), players)),
+            None => Ok((game, players)),
+        }
+    }
+
+    /// Ask a human player for a move, or a command to run instead
+    pub fn read_move(&mut self, heaps: &Heaps) -> Result<TurnInput, InputError> {
+        let heap = if let (1, Some(sticks)) = (heaps.len(), heaps.get_heap(0)) {
+            let moves = sticks.describe_moves();
+            writeln!(
+                self.output,
+                "{}  | {}",
+                Glyph::Prompt,
+                Message::HowMany(&moves)
+            )?;
+            0
+        } else {
+            let both = heaps.both_heaps();
+            let heap = loop {
+                match both {
+                    Some(both) => {
+                        let which = Message::WhichHeapOrBoth {
+                            heaps: heaps.len(),
+                            both: both + 1,
+                        };
+                        writeln!(self.output, "{}  | {}", Glyph::Prompt, which)?
+                    }
+                    None => writeln!(
+                        self.output,
+                        "{}  | {}",
+                        Glyph::Prompt,
+                        Message::WhichHeap(heaps.len())
+                    )?,
+                }
+                let line = self.read_string()?;
+                if let Some(command) = parse_command(&line) {
+                    return Ok(command);
+                }
+                match line.trim().parse::<usize>() {
+                    Ok(val) if val >= 1 && Some(val - 1) == both => break val - 1,
+                    Ok(val) if val >= 1 && val <= heaps.len() => break val - 1,
+                    Ok(_) => writeln!(
+                        self.output,
+                        "{}  | {}",
+                        Glyph::Error,
+                        Message::Error(&StickNumberError::HeapOutOfRange)
+                    )?,
+                    Err(_) => {
+                        let invalid = Message::NotANumber(&line);
+                        writeln!(
+                            self.output,
+                            "{}  | {}",
+                            Glyph::Error,
+                            Message::Error(&invalid)
+                        )?
+                    }
+                }
+            };
+            if Some(heap) == both {
+                writeln!(
+                    self.output,
+                    "{}  | {}",
+                    Glyph::Prompt,
+                    Message::HowManyFromBoth
+                )?;
+            } else {
+                writeln!(
+                    self.output,
+                    "{}  | {}",
+                    Glyph::Prompt,
+                    Message::HowManyFromHeap(&(heap + 1))
+                )?;
+            }
+            heap
+        };
+        let line = self.read_string()?;
+        if let Some(command) = parse_command(&line) {
+            return Ok(command);
+        }
+        Ok(TurnInput::Move(
+            line.trim()
+                .parse::<u64>()
+                .map(|amount| Move { heap, amount })
+                .map_err(|_| StickNumberError::ParsingError),
+        ))
+    }
+
+    /// Ask a human player for a game of the sum, then for one of its moves
+    fn read_sum_move(&mut self, sum: &GameSum<Position>) -> Result<(usize, usize), InputError> {
+        let components = sum.components();
+        let component = if components.len() == 1 {
+            0
+        } else {
+            writeln!(
+                self.output,
+                "{}  | {}",
+                Glyph::Prompt,
+                Message::WhichGame(components.len())
+            )?;
+            match self.read_string()?.trim().parse::<usize>() {
+                Ok(val) if val >= 1 => val - 1,
+                _ => return Ok((components.len(), 0)),
+            }
+        };
+        let Some(position) = components.get(component) else {
+            return Ok((component, 0));
+        };
+        writeln!(self.output, "  | {}", Message::PossibleMoves(position))?;
+        let options = position.options();
+        for (index, option) in options.iter().enumerate() {
+            writeln!(
+                self.output,
+                "  | {}: {}",
+                index + 1,
+                grundy::describe_option(option)
+            )?;
+        }
+        writeln!(
+            self.output,
+            "{}  | {}",
+            Glyph::Prompt,
+            Message::WhichMove(options.len())
+        )?;
+        let option = match self.read_string()?.trim().parse::<usize>() {
+            Ok(val) if val >= 1 => val - 1,
+            _ => options.len(),
+        };
+        Ok((component, option))
+    }
+
+    /// Play a sum of impartial games, whoever is left without a move losing
+    pub fn play_sum(&mut self, config: &cli::Config) -> Result<(), InputError> {
+        writeln!(self.output, "{}  | {}", Glyph::Title, Message::Welcome)?;
+        writeln!(self.output, "{}", "-".repeat(80))?;
+        let mut players = self.read_players(config)?;
+        let mut sum = GameSum::new(config.games.clone().unwrap_or_default());
+        let mut cache = GrundyCache::new();
+        let mut curr_player = match config.first {
+            Some(cli::First::Player(index)) if index < players.len() => index,
+            Some(cli::First::Player(_)) => {
+                return Err(InputError::Invalid(
+                    Message::FirstOutOfRange(players.len()).to_string(),
+                ))
+            }
+            Some(cli::First::Random) => rand::thread_rng().gen_range(0..players.len()),
+            None => 0,
+        };
+        writeln!(self.output, "{}", "-".repeat(80))?;
+        writeln!(self.output, "  | {}\n{}\n", Message::GamesTitle, sum)?;
+        if sum.is_over() {
+            writeln!(self.output, "  | {}", Message::NoMoveInGames)?;
+            return Ok(self.output.flush()?);
+        }
+
+        let mut turns = 0;
+        while !sum.is_over() {
+            let player = &mut players[curr_player];
+            let turn = Message::PlayerTurn {
+                number: curr_player + 1,
+                name: player.name(),
+            };
+            writeln!(self.output, "{} | {}", Glyph::Player, turn)?;
+            let (component, option) = match player.choose_sum_move(&sum, &mut cache) {
+                Some(mv) => mv,
+                None => self.read_sum_move(&sum)?,
+            };
+            let played = sum
+                .components()
+                .get(component)
+                .and_then(|position| position.options().get(option).cloned());
+            match sum.play(component, option) {
+                Ok(()) => {
+                    let turned = Message::TurnsGameInto {
+                        name: players[curr_player].name(),
+                        game: component + 1,
+                        option: &grundy::describe_option(&played.unwrap_or_default()),
+                    };
+                    writeln!(self.output, "  | {}", turned)?;
+                    writeln!(self.output, "{}\n\n{}", sum, "-".repeat(50))?;
+                    turns += 1;
+                    curr_player = (curr_player + 1) % players.len();
+                }
+                Err(e) => writeln!(self.output, "{}  | {}", Glyph::Error, Message::Error(&e))?,
+            }
+        }
+
+        // Under normal play the last player to move wins
+        let winner = (curr_player + players.len() - 1) % players.len();
+        let won = Message::Won {
+            name: players[winner].name(),
+            turns,
+        };
+        writeln!(self.output, "{}  | {}", Glyph::Victory, won)?;
+        Ok(self.output.flush()?)
+    }
+
+    fn write_turn(&mut self, game: &Game, turn: &Turn) -> io::Result<()> {
+        self.write_text(&format_turn(game, turn))
+    }
+
+    pub fn write_heaps(&mut self, game: &Game) -> io::Result<()> {
+        self.write_text(&format_heaps(game))
+    }
+
+    pub fn write_event(&mut self, game: &Game, event: &Event) -> io::Result<()> {
+        self.write_text(&format_event(game, event))
+    }
+
+    /// Play the game until it is over
+    pub fn play(
+        &mut self,
+        game: &mut Game,
+        players: &mut Vec<Box<dyn Player>>,
+    ) -> Result<(), InputError> {
+        self.write_heaps(game)?;
+        let mut clock = self
+            .time_control
+            .map(|control| Clock::new(control, players.len()));
+        // When the player to move started thinking
+        let mut turn_started: Option<Instant> = None;
+
+        while !game.is_over() {
+            let curr_player = game.current_player();
+            // Asking again after a command or a refused move counts the time spent so far
+            let thinking = turn_started.map_or(Duration::ZERO, self.elapsed);
+            let started = *turn_started.get_or_insert_with(Instant::now);
+            let time_left = clock
+                .as_ref()
+                .filter(|_| players[curr_player].kind() == PlayerKind::Human)
+                .and_then(|clock| clock.time_left(curr_player));
+            let number = curr_player + 1;
+            let name = players[curr_player].name();
+            match time_left {
+                Some(left) => {
+                    let left = clock::format_duration(left.saturating_sub(thinking));
+                    let turn = Message::PlayerTurnClock {
+                        number,
+                        name,
+                        left: &left,
+                    };
+                    writeln!(self.output, "{} | {}", Glyph::Player, turn)?
+                }
+                None => writeln!(
+                    self.output,
+                    "{} | {}",
+                    Glyph::Player,
+                    Message::PlayerTurn { number, name }
+                )?,
+            }
+
+            let input = match players[curr_player].choose_move(game) {
+                Some(mv) => {
+                    let turn = Turn {
+                        player: curr_player,
+                        heap: mv.heap,
+                        amount: mv.amount,
+                    };
+                    self.write_turn(game, &turn)?;
+                    TurnInput::Move(Ok(mv))
+                }
+                None => {
+                    self.deadline =
+                        time_left.map(|left| Instant::now() + left.saturating_sub(thinking));
+                    let input = self.read_move(game.heaps());
+                    self.deadline = None;
+                    let input = match (input, &mut clock) {
+                        (Err(InputError::TimedOut), Some(clock)) => {
+                            clock.charge(curr_player, (self.elapsed)(started));
+                            turn_started = None;
+                            self.time_out(game, clock.penalty())?;
+                            continue;
+                        }
+                        (input, _) => input?,
+                    };
+                    if let Some(clock) = &mut clock {
+                        let elapsed = (self.elapsed)(started);
+                        if clock.is_out_of_time(curr_player, elapsed) {
+                            clock.charge(curr_player, elapsed);
+                            turn_started = None;
+                            self.time_out(game, clock.penalty())?;
+                            continue;
+                        }
+                    }
+                    if let (true, TurnInput::Move(Ok(mv))) = (self.coach, &input) {
+                        write!(self.output, "{}", coach::review(game, *mv))?;
+                    }
+                    input
+                }
+            };
+            match input {
+                TurnInput::Move(mv) => match mv.and_then(|mv| game.apply_move(mv)) {
+                    Ok(events) => {
+                        if let Some(clock) = &mut clock {
+                            clock.charge(curr_player, (self.elapsed)(started));
+                        }
+                        turn_started = None;
+                        for event in events.iter() {
+                            self.write_event(game, event)?;
+                        }
+                    }
+                    Err(e) => writeln!(self.output, "{}  | {}", Glyph::Error, Message::Error(&e))?,
+                },
+                TurnInput::Save(path) => match save::save(&path, game, players) {
+                    Ok(()) => writeln!(self.output, "  | {}", Message::GameSaved(&path.display()))?,
+                    Err(e) => writeln!(self.output, "{}  | {}", Glyph::Error, Message::Error(&e))?,
+                },
+                TurnInput::Load(path) => match save::load(&path) {
+                    Ok((loaded_game, loaded_players)) => {
+                        *game = loaded_game;
+                        *players = loaded_players;
+                        clock = self
+                            .time_control
+                            .map(|control| Clock::new(control, players.len()));
+                        turn_started = None;
+                        writeln!(self.output, "  | {}", Message::GameLoaded(&path.display()))?;
+                        self.write_heaps(game)?;
+                    }
+                    Err(e) => writeln!(self.output, "{}  | {}", Glyph::Error, Message::Error(&e))?,
+                },
+                TurnInput::Undo => {
+                    self.undo(game, players)?;
+                    turn_started = None;
+                }
+                TurnInput::Redo => {
+                    self.redo(game, players)?;
+                    turn_started = None;
+                }
+                TurnInput::Hint => write!(self.output, "{}", coach::hint(game))?,
+            }
+        }
+
+        writeln!(
+            self.output,
+            "{}  | {}",
+            Glyph::Prompt,
+            Message::ReplayOrQuit
+        )?;
+        match self.read_string() {
+            Ok(line) if line.trim().eq_ignore_ascii_case("replay") => self.replay(game)?,
+            Ok(_) | Err(InputError::Closed) | Err(InputError::Quit) => {}
+            Err(e) => return Err(e),
+        }
+        Ok(self.output.flush()?)
+    }
+
+    /// Apply the penalty of the time control to the player to move
+    fn time_out(&mut self, game: &mut Game, penalty: Penalty) -> io::Result<()> {
+        let player = game.current_player();
+        let name = game.player_names()[player].clone();
+        let events = match penalty {
+            Penalty::Forfeit => {
+                writeln!(self.output, "  | {}", Message::OutOfTimeForfeit(&name))?;
+                game.forfeit()
+            }
+            Penalty::Random => {
+                writeln!(self.output, "  | {}", Message::OutOfTimeRandom(&name))?;
+                let (heap, amount) = player::random_move(&mut rand::thread_rng(), game.heaps());
+                self.write_turn(
+                    game,
+                    &Turn {
+                        player,
+                        heap,
+                        amount,
+                    },
+                )?;
+                match game.apply_move(Move { heap, amount }) {
+                    Ok(events) => events,
+                    Err(e) => {
+                        return writeln!(self.output, "{}  | {}", Glyph::Error, Message::Error(&e))
+                    }
+                }
+            }
+        };
+        for event in events.iter() {
+            self.write_event(game, event)?;
+        }
+        Ok(())
+    }
+
+    /// Take back moves until a human player is to move again
+    fn undo(&mut self, game: &mut Game, players: &[Box<dyn Player>]) -> io::Result<()> {
+        let mut undone = false;
+        while let Some(turn) = game.undo() {
+            undone = true;
+            let undid = Message::Undid {
+                name: &game.player_names()[turn.player],
+                amount: turn.amount,
+                heap: turn.heap + 1,
+            };
+            writeln!(self.output, "  | {}", undid)?;
+            if players[game.current_player()].kind() == PlayerKind::Human {
+                break;
+            }
+        }
+        if !undone {
+            writeln!(
+                self.output,
+                "{}  | {}",
+                Glyph::Error,
+                Message::Error(&Message::NothingToUndo)
+            )?;
+            return Ok(());
+        }
+        self.write_heaps(game)
+    }
+
+    /// Play again undone moves until a human player is to move again
+    fn redo(&mut self, game: &mut Game, players: &[Box<dyn Player>]) -> io::Result<()> {
+        if !game.can_redo() {
+            return writeln!(
+                self.output,
+                "{}  | {}",
+                Glyph::Error,
+                Message::Error(&Message::NothingToRedo)
+            );
+        }
+        while let Some(events) = game.redo() {
+            if let Some(turn) = game.history().last().copied() {
+                self.write_turn(game, &turn)?;
+            }
+            for event in events.iter() {
+                self.write_event(game, event)?;
+            }
+            if game.is_over() || players[game.current_player()].kind() == PlayerKind::Human {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Step through a game from its first move, waiting for enter between moves
+    pub fn replay(&mut self, game: &Game) -> Result<(), InputError> {
+        let mut replayed = game.initial();
+        writeln!(self.output, "{}", "-".repeat(80))?;
+        writeln!(self.output, "  | {}", Message::ReplayTitle)?;
+        self.write_heaps(&replayed)?;
+        let mut waiting = true;
+        for turn in game.history() {
+            if waiting {
+                writeln!(self.output, "{}  | {}", Glyph::Prompt, Message::PressEnter)?;
+                // Once the input is closed the rest of the game is shown at once
+                waiting = match self.read_string() {
+                    Ok(_) => true,
+                    Err(InputError::Closed) => false,
+                    Err(InputError::Quit) => return Ok(()),
+                    Err(e) => return Err(e),
+                };
+            }
+            let replayed_turn = Message::ReplayTurn {
+                turn: replayed.history().len() + 1,
+                number: turn.player + 1,
+                name: &replayed.player_names()[turn.player],
+            };
+            writeln!(self.output, "{} | {}", Glyph::Player, replayed_turn)?;
+            self.write_turn(&replayed, turn)?;
+            let events = replayed
+                .apply_move(Move {
+                    heap: turn.heap,
+                    amount: turn.amount,
+                })
+                .map_err(|e| InputError::Invalid(e.to_string()))?;
+            for event in events.iter() {
+                self.write_event(&replayed, event)?;
+            }
+        }
+        if let Some(player) = game.forfeited() {
+            let name = &replayed.player_names()[player];
+            writeln!(self.output, "  | {}", Message::Forfeits(name))?;
+            for event in replayed.forfeit().iter() {
+                self.write_event(&replayed, event)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(config: &cli::Config, script: &str) -> (Result<(), InputError>, String) {
+        let mut output = Vec::new();
+        let mut terminal = Terminal::new(script.as_bytes(), &mut output)
+            .with_coach(config.coach)
+            .with_time_control(config.time_control);
+        // Every human move takes 20 seconds
+        terminal.elapsed = |_| Duration::from_secs(20);
+        let result = match config.games {
+            Some(_) => terminal.play_sum(config),
+            None => terminal
+                .setup(config)
+                .and_then(|(mut game, mut players)| terminal.play(&mut game, &mut players)),
+        };
+        (result, String::from_utf8(output).unwrap())
+    }
+
+    fn empty_config() -> cli::Config {
+        cli::Config {
+            sticks: None,
+            min_remove: 1,
+            max_remove: 3,
+            moves: None,
+            players: None,
+            convention: None,
+            first: None,
+            resume: None,
+            teams: None,
+            shuffle: false,
+            tui: false,
+            coach: false,
+            wythoff: false,
+            games: None,
+            profiles: None,
+            time_control: None,
+            transcript: None,
+            mode: cli::Mode::Local,
+        }
+    }
+
+    #[test]
+    fn test_scripted_single_heap_game() {
+        let script = "\nhuman\nalice\n\nbob\n5\nnormal\n3\n4\nx\n2\n";
+        let (result, output) = run(&empty_config(), script);
+        assert!(result.is_ok());
+        assert!(output.contains(" | Player 1 - alice: "));
+        assert!(output.contains("  | 2/5[##   ]"));
+        assert!(output.contains("  | Error: Trying to take over the maximum limit"));
+        assert!(output.contains("  | Error: Entered value is not a valid number of sticks"));
+        assert!(output.contains("✌️  | Game won by bob in 2 turns\n"));
+    }
+
+    #[test]
+    fn test_scripted_team_game() {
+        let script = "3\nhuman\nalice\nhard\nhuman\ncarol\n1 1\n1 2 1\n4\nmisere\n1\n1\n";
+        let (result, output) = run(&empty_config(), script);
+        assert!(result.is_ok());
+        assert!(output.contains(" | Player 3 - carol: "));
+        assert!(output.contains("  | Error: there must be a team for each of the 3 players"));
+        assert!(output.contains("  | Computer (hard) removes 2"));
+        assert!(output.contains("✌️  | Game won by team 2 (Computer (hard)) in 3 turns\n"));
+    }
+
+    #[test]
+    fn test_shuffled_teams_stay_with_their_players() {
+        let config = cli::Config {
+            sticks: Some(vec![2]),
+            players: Some(["alice", "bob", "carol", "dave"].map(String::from).to_vec()),
+            convention: Some(Convention::Normal),
+            teams: Some(vec![0, 1, 0, 1]),
+            shuffle: true,
+            ..empty_config()
+        };
+        let mut output = Vec::new();
+        let mut terminal = Terminal::new("".as_bytes(), &mut output);
+        let players: Vec<Box<dyn Player>> = config
+            .players
+            .iter()
+            .flatten()
+            .map(|spec| player_from_spec(spec).unwrap())
+            .collect();
+        let (game, _) = terminal.setup_game(&config, players).unwrap();
+        for (name, team) in game.player_names().iter().zip(game.teams()) {
+            let expected = if name == "alice" || name == "carol" {
+                0
+            } else {
+                1
+            };
+            assert_eq!(team, expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_hint_and_coach() {
+        let config = cli::Config {
+            sticks: Some(vec![1, 2]),
+            players: Some(vec![String::from("alice"), String::from("bob")]),
+            convention: Some(Convention::Normal),
+            coach: true,
+            ..empty_config()
+        };
+        // Alice asks for a hint, then empties the second heap anyway
+        let (result, output) = run(&config, "hint\n2\n2\n1\n1\n");
+        assert!(result.is_ok());
+        assert!(output.contains("  | Hint: take 1 from heap 2 so that the nim-sum"));
+        assert!(output.contains("  | Coach: mistake, take 1 from heap 2 was winning"));
+        assert!(output.contains("  | Coach: good move, that was the last stick\n"));
+        assert!(output.contains("✌️  | Game won by bob in 2 turns\n"));
+    }
+
+    #[test]
+    fn test_french_ascii_game() {
+        let config = cli::Config {
+            sticks: Some(vec![1, 2]),
+            players: Some(vec![String::from("alice"), String::from("bob")]),
+            convention: Some(Convention::Normal),
+            ..empty_config()
+        };
+        crate::messages::set_locale(crate::messages::Locale {
+            language: crate::messages::Language::French,
+            ascii: true,
+        });
+        let (result, output) = run(&config, "3\n2\n9\n2\n2\n1\n1\n");
+        crate::messages::set_locale(crate::messages::Locale::default());
+        assert!(result.is_ok());
+        assert!(output.starts_with("#  | Bienvenue dans le jeu de Nim !\n"));
+        assert!(output.contains("  | Erreur : Il n'y a pas de tas avec ce numéro\n"));
+        assert!(output.contains("  | Erreur : Tentative de prendre plus que la limite maximale\n"));
+        assert!(output.contains("  | Combien en retirer du tas 2\n"));
+        assert!(output.contains("!  | Partie gagnée par bob en 2 tours\n"));
+        assert!(!output.contains('✌') && !output.contains('󰊖'));
+    }
+
+    #[test]
+    fn test_scripted_heaps_game_against_computer() {
+        let config = cli::Config {
+            sticks: Some(vec![1, 2]),
+            players: Some(vec![String::from("alice"), String::from("hard")]),
+            convention: Some(Convention::Normal),
+            ..empty_config()
+        };
+        // Alice empties the second heap, the computer then takes the last stick
+        let (result, output) = run(&config, "3\n2\n2\n");
+        assert!(result.is_ok());
+        assert!(output.contains("  | Error: There is no heap with that number"));
+        assert!(output.contains("  | Computer (hard) removes 1 from heap 1"));
+        assert!(output.contains("✌️  | Game won by Computer (hard) in 2 turns\n"));
+    }
+
+    #[test]
+    fn test_scripted_subtraction_set_game() {
+        let config = cli::Config {
+            sticks: Some(vec![5]),
+            moves: Some(vec![1, 3, 4]),
+            players: Some(vec![String::from("alice"), String::from("bob")]),
+            convention: Some(Convention::Normal),
+            ..empty_config()
+        };
+        let (result, output) = run(&config, "2\n4\n1\n");
+        assert!(result.is_ok());
+        assert!(output.contains("  | How many to remove (one of 1, 3, 4)"));
+        assert!(output.contains("  | Error: That amount is not one of the allowed moves"));
+        assert!(output.contains("✌️  | Game won by bob in 2 turns\n"));
+    }
+
+    #[test]
+    fn test_scripted_sum_of_games() {
+        let config = cli::Config {
+            players: Some(vec![String::from("alice"), String::from("bob")]),
+            games: Some(vec![Position::Grundy(4), Position::Kayles(1)]),
+            ..empty_config()
+        };
+        // Grundy's heap of 4 can only be split into 1 and 3
+        let script = "1\n1\n1\n1\n3\n1\n2\n1\n";
+        let (result, output) = run(&config, script);
+        assert!(result.is_ok());
+        assert!(output.contains("  | 1: Grundy heap of 1 [#] + Grundy heap of 3 [###]"));
+        assert!(output
+            .contains("  | alice turns game 1 into Grundy heap of 1 [#] + Grundy heap of 3 [###]"));
+        assert!(output.contains("  | Error: That amount is not one of the allowed moves"));
+        assert!(output.contains("  | bob turns game 3 into nothing"));
+        assert!(output.ends_with("✌️  | Game won by alice in 3 turns\n"));
+    }
+
+    #[test]
+    fn test_sum_over_from_the_start() {
+        let config = cli::Config {
+            players: Some(vec![String::from("alice"), String::from("bob")]),
+            games: Some(vec![Position::Grundy(2), Position::Kayles(0)]),
+            ..empty_config()
+        };
+        let (result, output) = run(&config, "");
+        assert!(result.is_ok());
+        assert!(output.ends_with("  | There is no move to play in these games\n"));
+        assert!(!output.contains("Game won"));
+    }
+
+    #[test]
+    fn test_scripted_wythoff_game() {
+        let config = cli::Config {
+            players: Some(vec![String::from("alice"), String::from("bob")]),
+            convention: Some(Convention::Normal),
+            wythoff: true,
+            ..empty_config()
+        };
+        let (result, output) = run(&config, "3 4 5\n1 2\n3\n1\n2\n1\n");
+        assert!(result.is_ok());
+        assert!(output.contains("  | Error: Wythoff's game is played on two heaps"));
+        assert!(output.contains("  | Which heap to take from (1-2, or 3 for both)"));
+        assert!(output.contains("  | How many to remove from both heaps"));
+        assert!(output.contains("1: 0/1[ ]\n2: 1/2[# ]"));
+        assert!(output.contains("✌️  | Game won by bob in 2 turns\n"));
+    }
+
+    #[test]
+    fn test_closed_input_stops_the_game() {
+        let config = cli::Config {
+            sticks: Some(vec![5]),
+            players: Some(vec![String::from("alice"), String::from("bob")]),
+            convention: Some(Convention::Misere),
+            ..empty_config()
+        };
+        let (result, _) = run(&config, "1\n");
+        assert!(matches!(result, Err(InputError::Closed)));
+    }
+
+    #[test]
+    fn test_quit_leaves_the_game() {
+        let (result, output) = run(&empty_config(), "\nhuman\nquit\n");
+        assert!(matches!(result, Err(InputError::Quit)));
+        assert!(!output.contains("Remaining sticks"));
+
+        let config = cli::Config {
+            sticks: Some(vec![5]),
+            players: Some(vec![String::from("alice"), String::from("bob")]),
+            convention: Some(Convention::Normal),
+            ..empty_config()
+        };
+        let (result, _) = run(&config, "2\n Quit \n");
+        assert!(matches!(result, Err(InputError::Quit)));
+        // Quitting once the game is over only skips the replay
+        let (result, output) = run(&config, "2\n3\nquit\n");
+        assert!(result.is_ok());
+        assert!(output.contains("✌️  | Game won by bob in 2 turns\n"));
+    }
+
+    #[test]
+    fn test_time_control() {
+        let config = cli::Config {
+            sticks: Some(vec![10]),
+            players: Some(vec![String::from("alice"), String::from("bob")]),
+            convention: Some(Convention::Normal),
+            time_control: Some(TimeControl {
+                per_move: Some(Duration::from_secs(30)),
+                bank: Some(Duration::from_secs(50)),
+                penalty: Penalty::Forfeit,
+            }),
+            ..empty_config()
+        };
+        // The third move of alice comes after 40 of her 50 seconds
+        let (result, output) = run(&config, "1\n1\nhint\n1\n1\n1\n\n");
+        assert!(result.is_ok());
+        assert!(output.contains(" | Player 1 - alice (0:30 left): "));
+        assert!(output.contains(" | Player 2 - bob (0:30 left): "));
+        assert!(output.contains(" | Player 1 - alice (0:10 left): "));
+        assert!(output.contains("  | alice ran out of time and forfeits the game\n"));
+        assert!(output.contains("✌️  | Game won by bob in 4 turns\n"));
+
+        let config = cli::Config {
+            time_control: Some(TimeControl {
+                per_move: Some(Duration::from_secs(10)),
+                bank: None,
+                penalty: Penalty::Random,
+            }),
+            ..config
+        };
+        let (result, output) = run(&config, &"1\n".repeat(10));
+        assert!(result.is_ok());
+        assert!(output.contains("  | alice ran out of time, playing a random move\n"));
+        assert!(output.contains("  | bob ran out of time, playing a random move\n"));
+        assert!(output.contains("✌️  | Game won by "));
+    }
+
+    #[test]
+    fn test_time_out_while_waiting() {
+        let config = cli::Config {
+            sticks: Some(vec![10]),
+            players: Some(vec![String::from("alice"), String::from("bob")]),
+            convention: Some(Convention::Normal),
+            time_control: Some(TimeControl {
+                per_move: Some(Duration::from_millis(200)),
+                bank: None,
+                penalty: Penalty::Forfeit,
+            }),
+            ..empty_config()
+        };
+        // Nothing is typed until long after the time is up
+        let (reader, writer) = io::pipe().unwrap();
+        let typist = thread::spawn(move || {
+            thread::sleep(Duration::from_secs(2));
+            drop(writer);
+        });
+        let mut output = Vec::new();
+        let mut terminal = Terminal::new("".as_bytes(), &mut output)
+            .with_time_control(config.time_control)
+            .with_input_thread(io::BufReader::new(reader));
+        let result = terminal
+            .setup(&config)
+            .and_then(|(mut game, mut players)| terminal.play(&mut game, &mut players));
+        typist.join().unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(result.is_ok());
+        assert!(output.contains("  | alice ran out of time and forfeits the game\n"));
+        assert!(output.contains("✌️  | Game won by bob in 0 turns\n"));
+    }
+
+    #[test]
+    fn test_parse_command() {
+        assert_eq!(
+            parse_command("save game.json"),
+            Some(TurnInput::Save(PathBuf::from("game.json")))
+        );
+        assert_eq!(
+            parse_command(" LOAD "),
+            Some(TurnInput::Load(PathBuf::from(DEFAULT_SAVE_FILE)))
+        );
+        assert_eq!(parse_command("Hint"), Some(TurnInput::Hint));
+        assert_eq!(parse_command("3"), None);
+        assert_eq!(parse_command(""), None);
+    }
+
+    #[test]
+    fn test_save_during_play_then_resume() {
+        let path = std::env::temp_dir().join(format!("nim_terminal_{}.json", std::process::id()));
+        let config = cli::Config {
+            sticks: Some(vec![3, 4]),
+            players: Some(vec![String::from("alice"), String::from("bob")]),
+            convention: Some(Convention::Normal),
+            ..empty_config()
+        };
+        let script = format!("2\n4\nsave {}\n", path.display());
+        let (result, output) = run(&config, &script);
+        assert!(result.is_err());
+        assert!(output.contains("  | Game saved to"));
+
+        let config = cli::Config {
+            resume: Some(path.clone()),
+            ..empty_config()
+        };
+        let (result, output) = run(&config, "1\n3\n");
+        std::fs::remove_file(&path).unwrap();
+        assert!(result.is_ok());
+        assert!(output.contains("  | Game resumed from"));
+        assert!(output.contains(" | Player 2 - bob: "));
+        assert!(output.contains("✌️  | Game won by bob in 2 turns\n"));
+    }
+
+    #[test]
+    fn test_undo_redo_and_replay() {
+        let config = cli::Config {
+            sticks: Some(vec![5]),
+            players: Some(vec![String::from("alice"), String::from("bob")]),
+            convention: Some(Convention::Normal),
+            ..empty_config()
+        };
+        let script = "2\nundo\nredo\nundo\n1\n3\n1\nreplay\n\n\n\n";
+        let (result, output) = run(&config, script);
+        assert!(result.is_ok());
+        assert!(output.contains("  | Undid alice taking 2 from heap 1"));
+        assert!(output.contains("  | alice removes 2"));
+        assert!(output.contains("✌️  | Game won by alice in 3 turns"));
+        let replay = &output[output.find("  | Replay").unwrap()..];
+        assert!(replay.contains(" | Turn 1 - Player 1 - alice: \n  | alice removes 1"));
+        assert!(replay.contains(" | Turn 3 - Player 1 - alice: \n  | alice removes 1"));
+        assert!(replay.ends_with("✌️  | Game won by alice in 3 turns\n"));
+    }
+
+    #[test]
+    fn test_undo_skips_computer_moves() {
+        let config = cli::Config {
+            sticks: Some(vec![3, 4, 5]),
+            players: Some(vec![String::from("alice"), String::from("hard")]),
+            convention: Some(Convention::Normal),
+            ..empty_config()
+        };
+        let (result, output) = run(&config, "undo\n1\n1\nundo\n");
+        assert!(result.is_err());
+        assert!(output.contains("  | Error: There is no move to undo"));
+        let undo = &output[output.find("  | Undid").unwrap()..];
+        assert!(undo.starts_with("  | Undid Computer (hard) taking"));
+        assert!(
+            undo.contains("  | Undid alice taking 1 from heap 1\n\u{f449}  | Heaps:\n1: 3/3[###]")
+        );
+    }
+}
@@ -0,0 +1,514 @@
+/*
+Round-robin tournaments between computer strategies, played without any front-end.
+
+Every pair of strategies plays the same number of games on the configured heaps,
+taking turns at moving first. Each player gets its own seed, drawn from the
+tournament seed in a fixed order, so a tournament is replayed exactly from its seed.
+*/
+
+use crate::cli;
+use crate::core::{Convention, Game, Heaps, Move, Rules};
+use crate::grundy::{GameSum, GrundyCache, Position};
+use crate::mcts::{self, MctsPlayer};
+use crate::messages::{Glyph, Message};
+use crate::player::{self, ComputerPlayer, Difficulty, Player, PlayerKind};
+use crate::terminal;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::fmt;
+use std::io::{self, Write};
+
+/// Heaps played when none are given
+const DEFAULT_STICKS: &[u64] = &[21];
+const DEFAULT_WYTHOFF: &[u64] = &[10, 15];
+/// Rating of every strategy before its first game
+pub const INITIAL_ELO: f64 = 1500.0;
+/// Largest rating change of a single game
+const ELO_K: f64 = 16.0;
+
+/// Build a strategy's player, all its random choices coming from the seed
+pub type Factory = fn(u64) -> Box<dyn Player>;
+
+/// Strategies a tournament can pick from, plugins being any other `Player`
+pub struct Registry {
+    strategies: Vec<(String, Factory)>,
+}
+
+impl Registry {
+    pub fn new() -> Registry {
+        Registry {
+            strategies: Vec::new(),
+        }
+    }
+
+    /// The simple strategies, the computer levels and the tree search
+    pub fn with_builtins() -> Registry {
+        let mut registry = Registry::new();
+        registry.register("random", |seed| {
+            Box::new(StrategyPlayer::new(Strategy::Random, seed))
+        });
+        registry.register("greedy", |seed| {
+            Box::new(StrategyPlayer::new(Strategy::Greedy, seed))
+        });
+        registry.register("optimal", |seed| {
+            Box::new(StrategyPlayer::new(Strategy::Optimal, seed))
+        });
+        registry.register("easy", |seed| {
+            Box::new(ComputerPlayer::with_seed(Difficulty::Easy, seed))
+        });
+        registry.register("medium", |seed| {
+            Box::new(ComputerPlayer::with_seed(Difficulty::Medium, seed))
+        });
+        registry.register("hard", |seed| {
+            Box::new(ComputerPlayer::with_seed(Difficulty::Hard, seed))
+        });
+        registry.register("mcts", |seed| {
+            Box::new(MctsPlayer::with_seed(mcts::DEFAULT_ITERATIONS, seed))
+        });
+        registry
+    }
+
+    /// Add a strategy, replacing any other one of the same name
+    pub fn register(&mut self, name: &str, factory: Factory) {
+        let name = name.trim().to_lowercase();
+        self.strategies.retain(|(other, _)| *other != name);
+        self.strategies.push((name, factory));
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.strategies
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    pub fn create(&self, name: &str, seed: u64) -> Option<Box<dyn Player>> {
+        let name = name.trim().to_lowercase();
+        self.strategies
+            .iter()
+            .find(|(other, _)| *other == name)
+            .map(|(_, factory)| factory(seed))
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Registry::new()
+    }
+}
+
+/// Single minded strategies, unlike the computer levels that mix them
+#[derive(PartialEq, Debug, Clone, Copy)]
+enum Strategy {
+    Random,
+    Greedy,
+    Optimal,
+}
+
+struct StrategyPlayer {
+    strategy: Strategy,
+    rng: StdRng,
+}
+
+impl StrategyPlayer {
+    fn new(strategy: Strategy, seed: u64) -> StrategyPlayer {
+        StrategyPlayer {
+            strategy,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Player for StrategyPlayer {
+    fn name(&self) -> &str {
+        match self.strategy {
+            Strategy::Random => "random",
+            Strategy::Greedy => "greedy",
+            Strategy::Optimal => "optimal",
+        }
+    }
+
+    /// Tournament players are never saved, the closest computer level will do
+    fn kind(&self) -> PlayerKind {
+        match self.strategy {
+            Strategy::Random => PlayerKind::Computer(Difficulty::Easy),
+            Strategy::Greedy => PlayerKind::Computer(Difficulty::Medium),
+            Strategy::Optimal => PlayerKind::Computer(Difficulty::Hard),
+        }
+    }
+
+    fn choose_move(&mut self, game: &Game) -> Option<Move> {
+        let heaps = game.heaps();
+        let (heap, amount) = match self.strategy {
+            Strategy::Random => player::random_move(&mut self.rng, heaps),
+            Strategy::Greedy => player::greedy_move(heaps),
+            Strategy::Optimal => match heaps.winning_move(game.rules()) {
+                Some(mv) => mv,
+                None => player::random_move(&mut self.rng, heaps),
+            },
+        };
+        Some(Move { heap, amount })
+    }
+
+    /// Sums have no greedy move, the greedy strategy plays randomly instead
+    fn choose_sum_move(
+        &mut self,
+        sum: &GameSum<Position>,
+        cache: &mut GrundyCache<Position>,
+    ) -> Option<(usize, usize)> {
+        let optimal = match self.strategy {
+            Strategy::Optimal => sum.winning_move(cache),
+            _ => None,
+        };
+        Some(optimal.unwrap_or_else(|| player::random_sum_move(&mut self.rng, sum)))
+    }
+}
+
+/// Play a game to the end, a player with no move or an illegal one losing it
+fn play_game(game: &mut Game, players: &mut [Box<dyn Player>]) -> usize {
+    while !game.is_over() {
+        let current = game.current_player();
+        let played = players[current]
+            .choose_move(game)
+            .map(|mv| game.apply_move(mv).is_ok());
+        if played != Some(true) {
+            return (current + 1) % players.len();
+        }
+    }
+    game.winner().unwrap_or(0)
+}
+
+/// Expected score of a player against another one from their ratings
+fn expected_score(rating: f64, other: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((other - rating) / 400.0))
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct Results {
+    pub names: Vec<String>,
+    /// Games won by each strategy against each other one
+    pub wins: Vec<Vec<u32>>,
+    /// Games played by each pair of strategies
+    pub rounds: u32,
+    /// Ratings after every game, updated in the order they were played
+    pub elo: Vec<f64>,
+}
+
+impl Results {
+    pub fn total_wins(&self, strategy: usize) -> u32 {
+        self.wins[strategy].iter().sum()
+    }
+
+    pub fn total_games(&self) -> u32 {
+        self.rounds * (self.names.len() as u32 - 1)
+    }
+
+    /// Strategies from the best rating to the worst
+    pub fn ranking(&self) -> Vec<usize> {
+        let mut ranking: Vec<usize> = (0..self.names.len()).collect();
+        ranking.sort_by(|&a, &b| self.elo[b].total_cmp(&self.elo[a]));
+        ranking
+    }
+}
+
+impl fmt::Display for Results {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let headers = [
+            Message::ColumnRank,
+            Message::ColumnStrategy,
+            Message::ColumnWins,
+            Message::ColumnGames,
+            Message::ColumnWinRate,
+            Message::ColumnElo,
+        ];
+        let mut rows = vec![headers.iter().map(|header| header.to_string()).collect()];
+        let ranking = self.ranking();
+        for (rank, &strategy) in ranking.iter().enumerate() {
+            let wins = self.total_wins(strategy);
+            rows.push(vec![
+                (rank + 1).to_string(),
+                self.names[strategy].clone(),
+                wins.to_string(),
+                self.total_games().to_string(),
+                format!("{:.1}%", 100.0 * wins as f64 / self.total_games() as f64),
+                format!("{:.0}", self.elo[strategy]),
+            ]);
+        }
+        write!(
+            f,
+            "{}",
+            terminal::format_table(&rows, &[4, 0, 6, 6, 8, 6], 1)
+        )?;
+        let width = rows
+            .iter()
+            .map(|row| row[1].chars().count())
+            .max()
+            .unwrap_or(0);
+
+        writeln!(f, "  |\n  | {}", Message::WinRateMatrix)?;
+        write!(f, "  | {:<width$}", "")?;
+        for &strategy in ranking.iter() {
+            write!(f, "  {:>8}", self.names[strategy])?;
+        }
+        writeln!(f)?;
+        for &row in ranking.iter() {
+            write!(f, "  | {:<width$}", self.names[row])?;
+            for &column in ranking.iter() {
+                if row == column {
+                    write!(f, "  {:>8}", "-")?;
+                } else {
+                    let rate = 100.0 * self.wins[row][column] as f64 / self.rounds as f64;
+                    write!(f, "  {:>7.1}%", rate)?;
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Play `rounds` games between each pair of strategies, in turn moving first
+pub fn run(
+    registry: &Registry,
+    names: &[String],
+    heaps: &Heaps,
+    rules: Rules,
+    rounds: u32,
+    seed: u64,
+) -> Result<Results, String> {
+    let names: Vec<String> = names
+        .iter()
+        .map(|name| name.trim().to_lowercase())
+        .collect();
+    if names.len() < 2 {
+        return Err(Message::TooFewStrategies.to_string());
+    }
+    for (index, name) in names.iter().enumerate() {
+        if registry.create(name, 0).is_none() {
+            return Err(Message::UnknownStrategy {
+                name,
+                expected: &registry.names().join(", "),
+            }
+            .to_string());
+        }
+        if names[..index].contains(name) {
+            return Err(Message::StrategyTwice(name).to_string());
+        }
+    }
+    if heaps.is_over() {
+        return Err(Message::NoMoveToPlay.to_string());
+    }
+
+    let mut seeds = StdRng::seed_from_u64(seed);
+    let mut wins = vec![vec![0; names.len()]; names.len()];
+    let mut elo = vec![INITIAL_ELO; names.len()];
+    for a in 0..names.len() {
+        for b in a + 1..names.len() {
+            for round in 0..rounds {
+                let pair = [a, b];
+                let mut players: Vec<Box<dyn Player>> = pair
+                    .iter()
+                    .filter_map(|&strategy| registry.create(&names[strategy], seeds.gen()))
+                    .collect();
+                let player_names = pair
+                    .iter()
+                    .map(|&strategy| names[strategy].clone())
+                    .collect();
+                let mut game = Game::new(heaps.clone(), rules, player_names, (round % 2) as usize);
+                let (winner, loser) = match play_game(&mut game, &mut players) {
+                    0 => (a, b),
+                    _ => (b, a),
+                };
+                wins[winner][loser] += 1;
+                let change = ELO_K * (1.0 - expected_score(elo[winner], elo[loser]));
+                elo[winner] += change;
+                elo[loser] -= change;
+            }
+        }
+    }
+    Ok(Results {
+        names,
+        wins,
+        rounds,
+        elo,
+    })
+}
+
+/// Run the tournament described on the command line and print its results
+pub fn play<W: Write>(config: &cli::Config, output: &mut W) -> io::Result<()> {
+    let (strategies, rounds, seed) = match &config.mode {
+        cli::Mode::Tournament {
+            strategies,
+            rounds,
+            seed,
+        } => (strategies, *rounds, *seed),
+        _ => return Ok(()),
+    };
+    let registry = Registry::with_builtins();
+    let names = strategies.clone().unwrap_or_else(|| registry.names());
+    let invalid = |e: String| io::Error::new(io::ErrorKind::InvalidInput, e);
+    let sizes = match &config.sticks {
+        Some(sizes) => sizes.as_slice(),
+        None if config.wythoff => DEFAULT_WYTHOFF,
+        None => DEFAULT_STICKS,
+    };
+    let heaps = config.heaps(sizes).map_err(invalid)?;
+    let rules = Rules::new(config.convention.unwrap_or(Convention::Normal));
+    let results = run(&registry, &names, &heaps, rules, rounds, seed).map_err(invalid)?;
+
+    writeln!(
+        output,
+        "{}  | {}\n{}",
+        Glyph::Title,
+        Message::TournamentOf {
+            strategies: results.names.len(),
+            rounds,
+            seed,
+        },
+        "-".repeat(80)
+    )?;
+    write!(output, "{}", results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::StickNumber;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| String::from(*name)).collect()
+    }
+
+    #[test]
+    fn test_same_seed_same_results() {
+        let registry = Registry::with_builtins();
+        let heaps = Heaps::new(&[3, 4, 5]);
+        let strategies = registry.names();
+        let first = run(&registry, &strategies, &heaps, Rules::default(), 20, 7).unwrap();
+        let again = run(&registry, &strategies, &heaps, Rules::default(), 20, 7).unwrap();
+        assert_eq!(first, again);
+        assert_eq!(first.to_string(), again.to_string());
+        for (strategy, row) in first.wins.iter().enumerate() {
+            assert_eq!(row[strategy], 0);
+            assert_eq!(first.total_games(), 120);
+        }
+        // Each game moves as many points as it takes
+        let total: f64 = first.elo.iter().sum();
+        assert!((total - INITIAL_ELO * 7.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_optimal_beats_random() {
+        let registry = Registry::with_builtins();
+        let heaps = Heaps::single(StickNumber::new(21, 1, 3));
+        let results = run(
+            &registry,
+            &names(&["random", "optimal", "greedy"]),
+            &heaps,
+            Rules::new(Convention::Misere),
+            40,
+            0,
+        )
+        .unwrap();
+        assert!(results.wins[1][0] > results.wins[0][1]);
+        assert!(results.wins[1][2] > results.wins[2][1]);
+        assert_eq!(results.ranking()[0], 1);
+        assert_eq!(results.wins[0][1] + results.wins[1][0], 40);
+    }
+
+    #[test]
+    fn test_plugin_strategy() {
+        // Always forfeits by taking more than allowed
+        struct Reckless;
+        impl Player for Reckless {
+            fn name(&self) -> &str {
+                "reckless"
+            }
+            fn kind(&self) -> PlayerKind {
+                PlayerKind::Computer(Difficulty::Easy)
+            }
+            fn choose_move(&mut self, _: &Game) -> Option<Move> {
+                Some(Move {
+                    heap: 0,
+                    amount: u64::MAX,
+                })
+            }
+            fn choose_sum_move(
+                &mut self,
+                _: &GameSum<Position>,
+                _: &mut GrundyCache<Position>,
+            ) -> Option<(usize, usize)> {
+                None
+            }
+        }
+
+        let mut registry = Registry::with_builtins();
+        registry.register("Reckless", |_| Box::new(Reckless));
+        assert!(registry.names().contains(&String::from("reckless")));
+        let heaps = Heaps::new(&[3, 4]);
+        let results = run(
+            &registry,
+            &names(&["reckless", "random"]),
+            &heaps,
+            Rules::default(),
+            10,
+            3,
+        )
+        .unwrap();
+        assert_eq!(results.wins[1][0], 10);
+        assert!(results.elo[1] > INITIAL_ELO);
+    }
+
+    #[test]
+    fn test_invalid_tournaments() {
+        let registry = Registry::with_builtins();
+        let heaps = Heaps::new(&[3, 4]);
+        let rules = Rules::default();
+        assert!(run(&registry, &names(&["random"]), &heaps, rules, 1, 0).is_err());
+        assert!(run(&registry, &names(&["random", "chess"]), &heaps, rules, 1, 0).is_err());
+        assert!(run(
+            &registry,
+            &names(&["random", "Random"]),
+            &heaps,
+            rules,
+            1,
+            0
+        )
+        .is_err());
+        let over = Heaps::new(&[0, 0]);
+        assert!(run(&registry, &names(&["random", "greedy"]), &over, rules, 1, 0).is_err());
+    }
+
+    #[test]
+    fn test_play_from_config() {
+        let config = cli::Config {
+            sticks: Some(vec![10, 12]),
+            min_remove: 1,
+            max_remove: 3,
+            moves: None,
+            players: None,
+            convention: None,
+            first: None,
+            resume: None,
+            teams: None,
+            shuffle: false,
+            tui: false,
+            coach: false,
+            wythoff: true,
+            games: None,
+            profiles: None,
+            time_control: None,
+            transcript: None,
+            mode: cli::Mode::Tournament {
+                strategies: Some(names(&["optimal", "random"])),
+                rounds: 10,
+                seed: 1,
+            },
+        };
+        let mut output = Vec::new();
+        play(&config, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("Tournament of 2 strategies, 10 games per pair, seed 1"));
+        assert!(output.contains("  |    1  optimal"));
+        assert!(output.contains("Win rate of each row against each column"));
+    }
+}
@@ -0,0 +1,508 @@
+/*
+Game transcripts, a plain text notation for recording games.
+
+A transcript starts with headers, one per line, followed by the numbered moves:
+    Variant: nim              nim, or wythoff for Wythoff's game
+    Convention: normal        normal or misere
+    Heaps: 3 4 5              starting heap sizes
+    Moves: 1-3                single heap games only, a range MIN-MAX or amounts as 1,3,4
+    Player 1: alice           one line per player, in turn order
+    Player 2: bob
+    Teams: 1 2 1 2            optional, the team of each player
+    First: 1                  player moving first, 1 if left out
+    Winner: bob               once the game is over
+
+    1. 2:3                    take 3 from heap 2, heaps counted from 1
+    2. both:1                 take 1 from both heaps in Wythoff's game
+    3. forfeit                the player to move gave up, as when out of time
+Only Heaps and the players are required. Blank lines and lines starting with `#`
+are skipped. Importing replays the moves through the engine, so a transcript that
+loads is a legal game.
+*/
+
+use crate::cli;
+use crate::core::{Convention, Game, Heaps, Move, Rules, StickNumber, StickNumberError};
+use crate::messages::Message;
+use std::{fmt, fs, io, path::Path};
+
+#[derive(Debug)]
+pub enum TranscriptError {
+    Io(io::Error),
+    /// A line not following the notation, counted from 1
+    Format {
+        line: usize,
+        reason: String,
+    },
+    /// A header needed to set up the game is not there
+    Missing(&'static str),
+    /// The first move the rules refuse, counted from 1
+    IllegalMove {
+        number: usize,
+        error: StickNumberError,
+    },
+}
+
+impl fmt::Display for TranscriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TranscriptError::Io(e) => write!(f, "{}", Message::TranscriptAccess(e)),
+            TranscriptError::Format { line, reason } => {
+                let line = Message::TranscriptLine {
+                    line: *line,
+                    reason,
+                };
+                write!(f, "{}", line)
+            }
+            TranscriptError::Missing(header) => {
+                write!(f, "{}", Message::TranscriptMissing(header))
+            }
+            TranscriptError::IllegalMove { number, error } => {
+                let illegal = Message::TranscriptIllegalMove {
+                    number: *number,
+                    error,
+                };
+                write!(f, "{}", illegal)
+            }
+        }
+    }
+}
+
+impl From<io::Error> for TranscriptError {
+    fn from(e: io::Error) -> Self {
+        TranscriptError::Io(e)
+    }
+}
+
+fn format_error(line: usize, reason: impl fmt::Display) -> TranscriptError {
+    TranscriptError::Format {
+        line,
+        reason: reason.to_string(),
+    }
+}
+
+/// How a heap of a single heap game may be taken from
+#[derive(PartialEq, Debug)]
+enum Removals {
+    Range(u64, u64),
+    Set(Vec<u64>),
+}
+
+fn join<T: ToString>(values: impl Iterator<Item = T>, separator: &str) -> String {
+    values
+        .map(|value| value.to_string())
+        .collect::<Vec<String>>()
+        .join(separator)
+}
+
+/// Transcript of a game from its first move, ending with the winner once over
+pub fn export(game: &Game) -> String {
+    let initial = game.initial();
+    let heaps = initial.heaps();
+    let wythoff = heaps.both_heaps().is_some();
+    // The notation keeps to ASCII, where Display writes misère
+    let convention = match initial.rules().convention {
+        Convention::Normal => "normal",
+        Convention::Misere => "misere",
+    };
+    let mut text = format!(
+        "Variant: {}\nConvention: {}\n",
+        if wythoff { "wythoff" } else { "nim" },
+        convention
+    );
+    let sizes = (0..heaps.len())
+        .filter_map(|index| heaps.get_heap(index))
+        .map(|heap| heap.get_remaining());
+    text.push_str(&format!("Heaps: {}\n", join(sizes, " ")));
+    if let (1, Some(sticks)) = (heaps.len(), heaps.get_heap(0)) {
+        let moves = match sticks.get_moves() {
+            Some(moves) => join(moves.iter(), ","),
+            None => format!("{}-{}", sticks.get_min_remove(), sticks.get_max_remove()),
+        };
+        text.push_str(&format!("Moves: {}\n", moves));
+    }
+    for (index, name) in initial.player_names().iter().enumerate() {
+        text.push_str(&format!("Player {}: {}\n", index + 1, name));
+    }
+    if initial.has_teams() {
+        let teams = initial.teams().into_iter().map(|team| team + 1);
+        text.push_str(&format!("Teams: {}\n", join(teams, " ")));
+    }
+    text.push_str(&format!("First: {}\n", initial.current_player() + 1));
+    if let Some(winner) = game.winner() {
+        text.push_str(&format!("Winner: {}\n", game.player_names()[winner]));
+    }
+    text.push('\n');
+    for (index, turn) in game.history().iter().enumerate() {
+        let heap = match heaps.both_heaps() {
+            Some(both) if both == turn.heap => String::from("both"),
+            _ => (turn.heap + 1).to_string(),
+        };
+        text.push_str(&format!("{}. {}:{}\n", index + 1, heap, turn.amount));
+    }
+    if game.forfeited().is_some() {
+        text.push_str(&format!("{}. forfeit\n", game.history().len() + 1));
+    }
+    text
+}
+
+/// Headers read so far, with the line of those checked once all are known
+#[derive(Default)]
+struct Header {
+    wythoff: bool,
+    convention: Option<Convention>,
+    sizes: Option<(usize, Vec<u64>)>,
+    removals: Option<(usize, Removals)>,
+    players: Vec<(usize, String)>,
+    teams: Option<(usize, Vec<usize>)>,
+    first: Option<(usize, usize)>,
+    winner: Option<(usize, String)>,
+}
+
+fn parse_numbers<'a>(
+    line: usize,
+    numbers: impl Iterator<Item = &'a str>,
+) -> Result<Vec<u64>, TranscriptError> {
+    numbers
+        .map(|number| {
+            number
+                .trim()
+                .parse::<u64>()
+                .map_err(|_| format_error(line, Message::NotANumber(number)))
+        })
+        .collect()
+}
+
+impl Header {
+    fn read(&mut self, line: usize, key: &str, value: &str) -> Result<(), TranscriptError> {
+        match key {
+            "Variant" => {
+                self.wythoff = match value {
+                    "nim" => false,
+                    "wythoff" => true,
+                    _ => return Err(format_error(line, Message::VariantNimOrWythoff)),
+                }
+            }
+            "Convention" => {
+                let convention = value
+                    .parse::<Convention>()
+                    .map_err(|e| format_error(line, e))?;
+                self.convention = Some(convention);
+            }
+            "Heaps" => self.sizes = Some((line, parse_numbers(line, value.split_whitespace())?)),
+            "Moves" => {
+                let removals = match value.split_once('-') {
+                    Some((min, max)) => match (min.trim().parse(), max.trim().parse()) {
+                        (Ok(min), Ok(max)) => Removals::Range(min, max),
+                        _ => return Err(format_error(line, Message::RangeNotation)),
+                    },
+                    None => Removals::Set(parse_numbers(line, value.split(','))?),
+                };
+                self.removals = Some((line, removals));
+            }
+            "Teams" => {
+                let teams = parse_numbers(line, value.split_whitespace())?
+                    .into_iter()
+                    .map(|team| (team as usize).checked_sub(1))
+                    .collect::<Option<Vec<usize>>>()
+                    .ok_or_else(|| format_error(line, Message::TeamsFromOne))?;
+                self.teams = Some((line, teams));
+            }
+            "First" => {
+                let first = value
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|first| first.checked_sub(1))
+                    .ok_or_else(|| format_error(line, Message::FirstFromOne))?;
+                self.first = Some((line, first));
+            }
+            "Winner" => self.winner = Some((line, String::from(value))),
+            _ => match key.strip_prefix("Player ").map(str::parse::<usize>) {
+                // Names are kept as typed, even empty ones
+                Some(Ok(number)) if number == self.players.len() + 1 => {
+                    self.players.push((line, String::from(value)))
+                }
+                Some(_) => return Err(format_error(line, Message::PlayersInOrder)),
+                None => return Err(format_error(line, Message::UnknownHeader(key))),
+            },
+        }
+        Ok(())
+    }
+
+    /// The game before any move, as the headers describe it
+    fn game(self) -> Result<Game, TranscriptError> {
+        let (line, sizes) = self.sizes.ok_or(TranscriptError::Missing("Heaps"))?;
+        let heaps = match (&sizes[..], self.removals) {
+            ([], _) => return Err(format_error(line, Message::NoHeap)),
+            (_, Some((line, _))) if self.wythoff => {
+                return Err(format_error(line, Message::WythoffAnyAmount))
+            }
+            (&[first, second], None) if self.wythoff => Heaps::wythoff(first, second),
+            (_, None) if self.wythoff => return Err(format_error(line, Message::WythoffTwoHeaps)),
+            (&[total], Some((line, Removals::Range(min, max)))) => {
+                if min == 0 || min > max {
+                    return Err(format_error(line, Message::RangeFromOne));
+                }
+                Heaps::single(StickNumber::new(total, min, max))
+            }
+            (&[total], Some((line, Removals::Set(moves)))) => {
+                if moves.contains(&0) {
+                    return Err(format_error(line, Message::AmountsPositive));
+                }
+                Heaps::single(StickNumber::with_moves(total, &moves))
+            }
+            (_, Some((line, _))) => return Err(format_error(line, Message::MovesHeaderSingleHeap)),
+            (_, None) => Heaps::new(&sizes),
+        };
+        let names: Vec<String> = self.players.into_iter().map(|(_, name)| name).collect();
+        if names.len() < 2 {
+            return Err(TranscriptError::Missing("Player 2"));
+        }
+        let first = match self.first {
+            Some((line, first)) if first >= names.len() => {
+                return Err(format_error(line, Message::NoSuchPlayer))
+            }
+            Some((_, first)) => first,
+            None => 0,
+        };
+        let rules = Rules::new(self.convention.unwrap_or(Convention::Normal));
+        let game = Game::new(heaps, rules, names, first);
+        match self.teams {
+            Some((line, teams)) => {
+                cli::check_teams(&teams, Some(game.player_names().len()))
+                    .map_err(|e| format_error(line, e))?;
+                Ok(game.with_teams(teams))
+            }
+            None => Ok(game),
+        }
+    }
+}
+
+/// Read a `<heap>:<amount>` move, `both` naming both heaps of Wythoff's game
+fn parse_move(
+    game: &Game,
+    line: usize,
+    text: &str,
+) -> Result<Result<Move, StickNumberError>, TranscriptError> {
+    let (heap, amount) = text
+        .split_once(':')
+        .ok_or_else(|| format_error(line, Message::MoveNotation))?;
+    let heap = match heap.trim() {
+        "both" => Ok(game.heaps().len()),
+        heap => match heap.parse::<usize>() {
+            Ok(heap) => heap.checked_sub(1).ok_or(StickNumberError::HeapOutOfRange),
+            Err(_) => Err(StickNumberError::HeapOutOfRange),
+        },
+    };
+    let amount = amount
+        .trim()
+        .parse::<u64>()
+        .map_err(|_| StickNumberError::ParsingError);
+    Ok(heap.and_then(|heap| amount.map(|amount| Move { heap, amount })))
+}
+
+/// Set up the game a transcript describes and play its moves, stopping at the
+/// first one the rules refuse
+pub fn import(text: &str) -> Result<Game, TranscriptError> {
+    let mut header = Header::default();
+    let mut moves: Vec<(usize, &str)> = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let number = index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with(|c: char| c.is_ascii_digit()) {
+            match line.split_once('.') {
+                Some((count, mv)) if count.parse() == Ok(moves.len() + 1) => {
+                    moves.push((number, mv.trim()))
+                }
+                _ => return Err(format_error(number, Message::MovesInOrder)),
+            }
+        } else if !moves.is_empty() {
+            return Err(format_error(number, Message::HeadersFirst));
+        } else {
+            let (key, value) = line
+                .split_once(':')
+                .ok_or_else(|| format_error(number, Message::HeaderOrMove))?;
+            header.read(number, key.trim(), value.trim())?;
+        }
+    }
+
+    let winner = header.winner.take();
+    let mut game = header.game()?;
+    for (index, &(line, text)) in moves.iter().enumerate() {
+        let illegal = |error| TranscriptError::IllegalMove {
+            number: index + 1,
+            error,
+        };
+        if text == "forfeit" {
+            if index + 1 < moves.len() {
+                return Err(format_error(line, Message::ForfeitEnds));
+            }
+            if game.is_over() {
+                return Err(illegal(StickNumberError::GameOver));
+            }
+            game.forfeit();
+            continue;
+        }
+        parse_move(&game, line, text)?
+            .and_then(|mv| game.apply_move(mv))
+            .map_err(illegal)?;
+    }
+    if let Some((line, name)) = winner {
+        let replayed = game.winner().map(|winner| &game.player_names()[winner]);
+        if replayed != Some(&name) {
+            return Err(format_error(line, Message::OtherWinner));
+        }
+    }
+    Ok(game)
+}
+
+pub fn save(path: &Path, game: &Game) -> io::Result<()> {
+    fs::write(path, export(game))
+}
+
+pub fn load(path: &Path) -> Result<Game, TranscriptError> {
+    import(&fs::read_to_string(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messages;
+
+    fn names() -> Vec<String> {
+        vec![String::from("alice"), String::from("bob")]
+    }
+
+    #[test]
+    fn test_export_format() {
+        let sticks = StickNumber::new(10, 1, 3);
+        let mut game = Game::new(Heaps::single(sticks), Rules::default(), names(), 1);
+        for amount in [2, 3, 1, 3, 1] {
+            game.apply_move(Move { heap: 0, amount }).unwrap();
+        }
+        assert_eq!(
+            export(&game),
+            "Variant: nim\nConvention: normal\nHeaps: 10\nMoves: 1-3\n\
+             Player 1: alice\nPlayer 2: bob\nFirst: 2\nWinner: bob\n\n\
+             1. 1:2\n2. 1:3\n3. 1:1\n4. 1:3\n5. 1:1\n"
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut games = vec![
+            Game::new(
+                Heaps::wythoff(3, 5),
+                Rules::new(Convention::Misere),
+                names(),
+                0,
+            ),
+            Game::new(
+                Heaps::single(StickNumber::with_moves(9, &[1, 3, 4])),
+                Rules::default(),
+                names(),
+                0,
+            ),
+        ];
+        games[0].apply_move(Move { heap: 2, amount: 2 }).unwrap();
+        games[0].forfeit();
+        games[1].apply_move(Move { heap: 0, amount: 4 }).unwrap();
+        let names = ["alice", "bob", "carol", "dave"].map(String::from).to_vec();
+        let mut teams = Game::new(Heaps::new(&[3, 4, 5]), Rules::default(), names, 3)
+            .with_teams(vec![0, 1, 0, 1]);
+        teams.apply_move(Move { heap: 1, amount: 4 }).unwrap();
+        games.push(teams);
+        let unnamed = vec![String::new(), String::from("bob")];
+        let mut unnamed = Game::new(Heaps::new(&[2]), Rules::default(), unnamed, 0);
+        unnamed.apply_move(Move { heap: 0, amount: 2 }).unwrap();
+        games.push(unnamed);
+        assert!(export(&games[0]).starts_with("Variant: wythoff\nConvention: misere\n"));
+        for game in games {
+            let text = export(&game);
+            assert_eq!(import(&text).unwrap(), game, "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_import_reports_first_illegal_move() {
+        let header = "# Saved by hand\nHeaps: 5\nMoves: 1,2\nPlayer 1: alice\nPlayer 2: bob\n";
+        let game = import(&format!("{}\n1. 1:2\n2. 1:1\n", header)).unwrap();
+        assert_eq!(game.heaps().get_heap(0).unwrap().get_remaining(), 2);
+        let error = import(&format!("{}\n1. 1:2\n2. 1:3\n3. 1:1\n", header));
+        assert!(matches!(
+            error,
+            Err(TranscriptError::IllegalMove {
+                number: 2,
+                error: StickNumberError::TakeOverMax
+            })
+        ));
+        for (moves, expected) in [
+            ("1. 2:1\n", StickNumberError::HeapOutOfRange),
+            ("1. both:1\n", StickNumberError::HeapOutOfRange),
+            ("1. 1:x\n", StickNumberError::ParsingError),
+            (
+                "1. 1:2\n2. 1:2\n3. 1:1\n4. 1:1\n",
+                StickNumberError::GameOver,
+            ),
+        ] {
+            match import(&format!("{}{}", header, moves)) {
+                Err(TranscriptError::IllegalMove { error, .. }) => assert_eq!(error, expected),
+                other => panic!("{:?} for {}", other, moves),
+            }
+        }
+        let error = import(&format!("{}1. 1:2\n2. 1:2\n3. 1:2\n", header)).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Move 3 of the transcript is illegal: Taking that many sticks will lead to a negative number"
+        );
+    }
+
+    #[test]
+    fn test_import_rejects_bad_format() {
+        let players = "Player 1: alice\nPlayer 2: bob\n";
+        for (text, line) in [
+            (format!("Heaps: 5\n{}1. 1:1\n3. 1:1\n", players), 5),
+            (format!("Heaps: 5\n{}1. 1:1\nFirst: 2\n", players), 5),
+            (format!("Heaps: 5\nColour: red\n{}", players), 2),
+            (String::from("Heaps: 5\nPlayer 2: bob\n"), 2),
+            (format!("Variant: wythoff\nHeaps: 5\n{}", players), 2),
+            (format!("Heaps: 5 6\nMoves: 1-3\n{}", players), 2),
+            (format!("Heaps: 5\n{}Teams: 1 1\n", players), 4),
+            (format!("Heaps: 5\n{}Winner: bob\n1. 1:5\n", players), 4),
+            (format!("Heaps: 5\n{}1. forfeit\n2. 1:1\n", players), 4),
+        ] {
+            match import(&text) {
+                Err(TranscriptError::Format { line: found, .. }) => {
+                    assert_eq!(found, line, "{}", text)
+                }
+                other => panic!("{:?} for {}", other, text),
+            }
+        }
+        assert!(matches!(
+            import(players),
+            Err(TranscriptError::Missing("Heaps"))
+        ));
+        assert!(matches!(
+            import("Heaps: 5\nPlayer 1: alice\n"),
+            Err(TranscriptError::Missing("Player 2"))
+        ));
+
+        messages::set_locale(messages::Locale {
+            language: messages::Language::French,
+            ascii: false,
+        });
+        let error = import(&format!("Heaps: 5\nColour: red\n{}", players)).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Ligne 2 de la transcription : en-tête inconnu Colour"
+        );
+        let error = import("Heaps: five\n").unwrap_err().to_string();
+        messages::set_locale(messages::Locale::default());
+        assert_eq!(
+            error,
+            "Ligne 1 de la transcription : five n'est pas un nombre valide"
+        );
+    }
+}
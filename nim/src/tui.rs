@@ -0,0 +1,500 @@
+/*
+Full screen front-end drawn with ratatui, over the same game engine as the
+line based terminal one.
+*/
+
+use crate::core::{self, Event, Game, Move, StickNumber, Turn};
+use crate::messages::{Glyph, Message};
+use crate::player::{Player, PlayerKind};
+use crate::terminal;
+use crossterm::event::{self, KeyCode, KeyEventKind};
+use crossterm::execute;
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use ratatui::backend::{Backend, CrosstermBackend};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, Paragraph};
+use ratatui::{Frame, Terminal};
+use std::io;
+use std::thread;
+use std::time::Duration;
+
+/// How long a computer move stays on screen before the next one
+const COMPUTER_DELAY: Duration = Duration::from_millis(600);
+
+/// Width of the move log on the right of the heaps
+const LOG_WIDTH: u16 = 36;
+
+/// What the human player points at, and what happened so far
+struct App {
+    selected_heap: usize,
+    amount: u64,
+    log: Vec<String>,
+    error: Option<String>,
+}
+
+#[derive(PartialEq, Debug)]
+enum Action {
+    Nothing,
+    Play(Move),
+    Quit,
+}
+
+fn log_line(game: &Game, turn: &Turn) -> String {
+    let text = terminal::format_turn(game, turn);
+    String::from(text.trim_start_matches("  | ").trim_end())
+}
+
+/// Smallest amount above `take` that can be taken from a heap, or from both heaps
+fn next_amount(game: &Game, heap: usize, take: u64) -> Option<u64> {
+    let heaps = game.heaps();
+    if Some(heap) == heaps.both_heaps() {
+        let next = take.checked_add(1)?;
+        return (next <= heaps.max_from_both()).then_some(next);
+    }
+    heaps.get_heap(heap)?.next_move(take)
+}
+
+/// Largest amount below `take` that can be taken from a heap, or from both heaps
+fn previous_amount(game: &Game, heap: usize, take: u64) -> Option<u64> {
+    let heaps = game.heaps();
+    if Some(heap) == heaps.both_heaps() {
+        let previous = take.checked_sub(1)?.min(heaps.max_from_both());
+        return (previous >= 1).then_some(previous);
+    }
+    heaps.get_heap(heap)?.previous_move(take)
+}
+
+impl App {
+    fn new(game: &Game) -> App {
+        let mut app = App {
+            selected_heap: 0,
+            amount: 1,
+            log: game
+                .history()
+                .iter()
+                .map(|turn| log_line(game, turn))
+                .collect(),
+            error: None,
+        };
+        app.select_playable(game, 1);
+        app
+    }
+
+    /// Heaps that can be selected, taking from both heaps coming after the last one
+    fn choices(game: &Game) -> usize {
+        let heaps = game.heaps();
+        heaps.len() + usize::from(heaps.both_heaps().is_some())
+    }
+
+    /// Keep the selection on a heap that can be played, looking in `direction`
+    fn select_playable(&mut self, game: &Game, direction: isize) {
+        let number = App::choices(game) as isize;
+        for step in 0..number {
+            let index = (self.selected_heap as isize + direction * step).rem_euclid(number);
+            if next_amount(game, index as usize, 0).is_some() {
+                self.selected_heap = index as usize;
+                break;
+            }
+        }
+        // Fall back to the closest legal amount below, or the smallest one
+        let heap = self.selected_heap;
+        self.amount = previous_amount(game, heap, self.amount.saturating_add(1))
+            .or_else(|| next_amount(game, heap, 0))
+            .unwrap_or(self.amount);
+    }
+
+    fn handle_key(&mut self, game: &Game, code: KeyCode) -> Action {
+        self.error = None;
+        let number = App::choices(game);
+        match code {
+            KeyCode::Up => {
+                self.selected_heap = (self.selected_heap + number - 1) % number;
+                self.select_playable(game, -1);
+            }
+            KeyCode::Down => {
+                self.selected_heap = (self.selected_heap + 1) % number;
+                self.select_playable(game, 1);
+            }
+            KeyCode::Left => {
+                if let Some(take) = previous_amount(game, self.selected_heap, self.amount) {
+                    self.amount = take;
+                }
+            }
+            KeyCode::Right => {
+                if let Some(take) = next_amount(game, self.selected_heap, self.amount) {
+                    self.amount = take;
+                }
+            }
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                return Action::Play(Move {
+                    heap: self.selected_heap,
+                    amount: self.amount,
+                })
+            }
+            KeyCode::Char('q') | KeyCode::Esc => return Action::Quit,
+            _ => {}
+        }
+        Action::Nothing
+    }
+
+    fn play(&mut self, game: &mut Game, mv: Move) {
+        match game.apply_move(mv) {
+            Ok(_) => {
+                if let Some(turn) = game.history().last() {
+                    self.log.push(log_line(game, turn));
+                }
+                if !game.is_over() {
+                    self.select_playable(game, 1);
+                }
+            }
+            Err(e) => self.error = Some(e.to_string()),
+        }
+    }
+}
+
+/// A heap as a row of sticks, the ones about to be taken highlighted
+///
+/// Big heaps are scaled down to `core::DRAWN_STICKS` sticks, their exact size
+/// stays written after the row.
+fn heap_line(index: usize, sticks: &StickNumber, selected: Option<u64>) -> Line<'static> {
+    let remaining = sticks.get_remaining();
+    let total = sticks.get_total();
+    let taken = selected.unwrap_or(0).min(remaining);
+    let drawn = |count: u64| {
+        if total <= core::DRAWN_STICKS {
+            count as usize
+        } else {
+            core::scale(count, total, core::DRAWN_STICKS) as usize
+        }
+    };
+    let (left, kept) = (drawn(remaining), drawn(remaining - taken));
+    let marker = if selected.is_some() { "> " } else { "  " };
+    let mut spans = vec![
+        Span::styled(
+            format!("{}{:>2}: ", marker, index + 1),
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+        Span::styled("| ".repeat(kept), Style::default().fg(Color::Yellow)),
+        Span::styled(
+            "| ".repeat(left - kept),
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(
+            format!("{} ", Glyph::Taken).repeat(drawn(total) - left),
+            Style::default().fg(Color::DarkGray),
+        ),
+    ];
+    spans.push(Span::raw(format!("{}/{}", remaining, total)));
+    Line::from(spans)
+}
+
+fn status_line(game: &Game, players: &[Box<dyn Player>], app: &App) -> Line<'static> {
+    if let Some(winner) = game.winner() {
+        let text = terminal::format_event(
+            game,
+            &Event::GameOver {
+                winner,
+                turns: game.history().len() as u32,
+            },
+        );
+        return Line::styled(
+            Message::PressAnyKey(text.trim()).to_string(),
+            Style::default().fg(Color::Green),
+        );
+    }
+    if let Some(error) = &app.error {
+        return Line::styled(
+            Message::Error(error).to_string(),
+            Style::default().fg(Color::Red),
+        );
+    }
+    let curr_player = game.current_player();
+    let number = curr_player + 1;
+    let name = &game.player_names()[curr_player];
+    match players.get(curr_player).map(|player| player.kind()) {
+        Some(PlayerKind::Computer(_) | PlayerKind::Mcts(_) | PlayerKind::Learned(_)) => {
+            Line::raw(Message::Thinking { number, name }.to_string())
+        }
+        _ => Line::raw(Message::ToMove { number, name }.to_string()),
+    }
+}
+
+fn draw(frame: &mut Frame, game: &Game, players: &[Box<dyn Player>], app: &App) {
+    let [main, status] =
+        Layout::vertical([Constraint::Min(3), Constraint::Length(1)]).areas(frame.size());
+    let [heaps_area, log_area]: [Rect; 2] =
+        Layout::horizontal([Constraint::Min(20), Constraint::Length(LOG_WIDTH)]).areas(main);
+
+    let human_to_move = !game.is_over()
+        && players
+            .get(game.current_player())
+            .is_some_and(|player| player.kind() == PlayerKind::Human);
+    let heaps = game.heaps();
+    let both = heaps.both_heaps();
+    let both_selected = human_to_move && Some(app.selected_heap) == both;
+    let mut lines: Vec<Line> = (0..heaps.len())
+        .filter_map(|index| heaps.get_heap(index).map(|sticks| (index, sticks)))
+        .map(|(index, sticks)| {
+            let selected = (both_selected || human_to_move && index == app.selected_heap)
+                .then_some(app.amount);
+            heap_line(index, sticks, selected)
+        })
+        .collect();
+    if both.is_some() {
+        let marker = if both_selected { "> " } else { "  " };
+        lines.push(Line::styled(
+            format!("{}{}", marker, Message::BothHeaps),
+            Style::default().add_modifier(Modifier::BOLD),
+        ));
+    }
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(" Nim ")),
+        heaps_area,
+    );
+
+    // Only the latest moves fit in the sidebar
+    let visible = usize::from(log_area.height.saturating_sub(2));
+    let skipped = app.log.len().saturating_sub(visible);
+    let log: Vec<String> = app.log.iter().skip(skipped).cloned().collect();
+    frame.render_widget(
+        List::new(log).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" {} ", Message::MovesTitle)),
+        ),
+        log_area,
+    );
+
+    frame.render_widget(Paragraph::new(status_line(game, players, app)), status);
+}
+
+fn run<B: Backend>(
+    screen: &mut Terminal<B>,
+    game: &mut Game,
+    players: &mut [Box<dyn Player>],
+) -> io::Result<()> {
+    let mut app = App::new(game);
+    loop {
+        screen.draw(|frame| draw(frame, game, players, &app))?;
+        let key = if game.is_over() || players[game.current_player()].kind() == PlayerKind::Human {
+            match event::read()? {
+                event::Event::Key(key) if key.kind == KeyEventKind::Press => Some(key.code),
+                _ => None,
+            }
+        } else {
+            None
+        };
+        if game.is_over() {
+            if key.is_some() {
+                return Ok(());
+            }
+            continue;
+        }
+
+        let action = match players[game.current_player()].choose_move(game) {
+            Some(mv) => {
+                thread::sleep(COMPUTER_DELAY);
+                Action::Play(mv)
+            }
+            None => key.map_or(Action::Nothing, |code| app.handle_key(game, code)),
+        };
+        match action {
+            Action::Play(mv) => app.play(game, mv),
+            Action::Quit => return Ok(()),
+            Action::Nothing => {}
+        }
+    }
+}
+
+/// Raw mode of the terminal, turned off again when dropped even on errors
+struct RawMode;
+
+impl RawMode {
+    fn enable() -> io::Result<RawMode> {
+        enable_raw_mode()?;
+        Ok(RawMode)
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = disable_raw_mode();
+    }
+}
+
+/// Play the game full screen, giving the terminal back as it was when leaving
+pub fn play(game: &mut Game, players: &mut [Box<dyn Player>]) -> io::Result<()> {
+    let raw_mode = RawMode::enable()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    let mut screen = Terminal::new(CrosstermBackend::new(stdout))?;
+    let result = run(&mut screen, game, players);
+    drop(raw_mode);
+    execute!(screen.backend_mut(), LeaveAlternateScreen)?;
+    screen.show_cursor()?;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{Heaps, Rules};
+    use crate::player::{ComputerPlayer, Difficulty, HumanPlayer};
+    use ratatui::backend::TestBackend;
+
+    fn two_humans() -> Vec<Box<dyn Player>> {
+        vec![
+            Box::new(HumanPlayer::new(String::from("alice"))),
+            Box::new(HumanPlayer::new(String::from("bob"))),
+        ]
+    }
+
+    fn screen_text(game: &Game, players: &[Box<dyn Player>], app: &App) -> String {
+        let mut screen = Terminal::new(TestBackend::new(90, 8)).unwrap();
+        screen
+            .draw(|frame| draw(frame, game, players, app))
+            .unwrap();
+        let buffer = screen.backend().buffer();
+        buffer
+            .content
+            .chunks(usize::from(buffer.area.width))
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_keys_select_heap_and_amount() {
+        let names = vec![String::from("alice"), String::from("bob")];
+        let mut game = Game::new(Heaps::new(&[3, 0, 5]), Rules::default(), names, 0);
+        let mut app = App::new(&game);
+        assert_eq!((app.selected_heap, app.amount), (0, 1));
+
+        // The empty heap is skipped both ways
+        app.handle_key(&game, KeyCode::Down);
+        assert_eq!(app.selected_heap, 2);
+        app.handle_key(&game, KeyCode::Up);
+        assert_eq!(app.selected_heap, 0);
+
+        for _ in 0..5 {
+            app.handle_key(&game, KeyCode::Right);
+        }
+        assert_eq!(app.amount, 3);
+        app.handle_key(&game, KeyCode::Left);
+        assert_eq!(app.amount, 2);
+
+        let action = app.handle_key(&game, KeyCode::Enter);
+        assert_eq!(action, Action::Play(Move { heap: 0, amount: 2 }));
+        if let Action::Play(mv) = action {
+            app.play(&mut game, mv);
+        }
+        assert_eq!(app.log, vec![String::from("alice removes 2 from heap 1")]);
+        assert_eq!(app.handle_key(&game, KeyCode::Char('q')), Action::Quit);
+    }
+
+    #[test]
+    fn test_keys_select_both_heaps_in_wythoff() {
+        let names = vec![String::from("alice"), String::from("bob")];
+        let mut game = Game::new(Heaps::wythoff(2, 4), Rules::default(), names, 0);
+        let players = two_humans();
+        let mut app = App::new(&game);
+        app.handle_key(&game, KeyCode::Up);
+        assert_eq!(app.selected_heap, 2);
+        for _ in 0..3 {
+            app.handle_key(&game, KeyCode::Right);
+        }
+        assert_eq!(app.amount, 2);
+        let text = screen_text(&game, &players, &app);
+        assert!(text.contains(">  1: | | 2/2"));
+        assert!(text.contains(">  2: | | | | 4/4"));
+        assert!(text.contains("> both heaps"));
+
+        if let Action::Play(mv) = app.handle_key(&game, KeyCode::Enter) {
+            app.play(&mut game, mv);
+        }
+        assert_eq!(
+            app.log,
+            vec![String::from("alice removes 2 from both heaps")]
+        );
+        // The first heap is empty, only the second one can be selected
+        assert_eq!(app.selected_heap, 1);
+        app.handle_key(&game, KeyCode::Down);
+        assert_eq!(app.selected_heap, 1);
+    }
+
+    #[test]
+    fn test_keys_step_through_subtraction_set() {
+        let names = vec![String::from("alice"), String::from("bob")];
+        let sticks = StickNumber::with_moves(10, &[1, 3, 4]);
+        let game = Game::new(Heaps::single(sticks), Rules::default(), names, 0);
+        let mut app = App::new(&game);
+        app.handle_key(&game, KeyCode::Right);
+        assert_eq!(app.amount, 3);
+        app.handle_key(&game, KeyCode::Right);
+        app.handle_key(&game, KeyCode::Right);
+        assert_eq!(app.amount, 4);
+        app.handle_key(&game, KeyCode::Left);
+        assert_eq!(app.amount, 3);
+    }
+
+    #[test]
+    fn test_big_heap_is_scaled() {
+        let mut sticks = StickNumber::new(1000, 1, 1000);
+        sticks.remove_sticks(Ok(500)).unwrap();
+        let text: String = heap_line(0, &sticks, Some(250))
+            .spans
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect();
+        assert_eq!(
+            text,
+            format!(
+                ">  1: {}{}{}500/1000",
+                "| ".repeat(10),
+                "| ".repeat(10),
+                "· ".repeat(20)
+            )
+        );
+    }
+
+    #[test]
+    fn test_screen_shows_heaps_log_and_status() {
+        let names = vec![String::from("alice"), String::from("bob")];
+        let mut game = Game::new(Heaps::new(&[3, 4]), Rules::default(), names, 0);
+        let players = two_humans();
+        let mut app = App::new(&game);
+        app.play(&mut game, Move { heap: 1, amount: 4 });
+        app.play(&mut game, Move { heap: 1, amount: 1 });
+
+        let text = screen_text(&game, &players, &app);
+        assert!(text.contains(">  1: | | | 3/3"));
+        assert!(text.contains("   2: · · · · 0/4"));
+        assert!(text.contains("alice removes 4 from heap 2"));
+        assert!(text.contains("Error: Taking that many sticks will lead to a negative number"));
+
+        app.handle_key(&game, KeyCode::Right);
+        let text = screen_text(&game, &players, &app);
+        assert!(text.contains("Player 2 - bob to move"));
+    }
+
+    #[test]
+    fn test_screen_announces_computer_and_winner() {
+        let names = vec![String::from("alice"), String::from("Computer (hard)")];
+        let mut game = Game::new(Heaps::new(&[2]), Rules::default(), names, 1);
+        let players: Vec<Box<dyn Player>> = vec![
+            Box::new(HumanPlayer::new(String::from("alice"))),
+            Box::new(ComputerPlayer::new(Difficulty::Hard)),
+        ];
+        let mut app = App::new(&game);
+        assert!(screen_text(&game, &players, &app).contains("Computer (hard) is thinking"));
+
+        app.play(&mut game, Move { heap: 0, amount: 2 });
+        assert!(
+            screen_text(&game, &players, &app).contains("Game won by Computer (hard) in 1 turns")
+        );
+    }
+}